@@ -2,12 +2,29 @@
 
 use super::page::Page;
 use crate::{
+    cache::{self, CacheStatus, HttpCache},
     form::{self, Form},
+    har::{HarRecorder, PendingEntry},
+    middleware::{Middleware, Next},
     page,
+    rate_limit::{RateLimit, RateLimiter},
+    retry::RetryPolicy,
+    robots::RobotsTxt,
+    status::{ExpectedStatus, UnexpectedResponse},
+    transport::{self, HttpTransport, Request, Response, Transport},
 };
 use reqwest::{
-    blocking::{Client, Response},
-    Certificate, Method,
+    cookie::{CookieStore, Jar},
+    header::{HeaderValue, CONTENT_TYPE, COOKIE, LOCATION, SET_COOKIE, USER_AGENT},
+    redirect::Policy,
+    Certificate, Method, StatusCode, Url,
+};
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime},
 };
 use thiserror::Error;
 
@@ -22,9 +39,24 @@ pub enum Error {
         source: reqwest::Error,
     },
 
-    /// There was an error with [reqwest](https://crates.io/crates/reqwest) while sending the web request or due to
-    /// exhausting the maximum number of redirects.
-    #[error("Failed to send the request or redirection limit hit!")]
+    /// The user agent given to [`BrowserBuilder`][BrowserBuilder] is not a valid header value.
+    #[error("Invalid user agent '{user_agent}'!")]
+    InvalidUserAgentError {
+        /// The given `user_agent`.
+        user_agent: String,
+    },
+
+    /// The given url could not be parsed.
+    #[error("Failed to parse url '{url}', reason: {reason}")]
+    UrlParseError {
+        /// The given `url` that could not be parsed.
+        url: String,
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+
+    /// There was an error with [reqwest](https://crates.io/crates/reqwest) while sending the web request.
+    #[error("Failed to send the request!")]
     SendRequestError {
         /// The underlying error.
         #[source]
@@ -39,6 +71,48 @@ pub enum Error {
         source: reqwest::Error,
     },
 
+    /// Any other error of the [`Transport`][Transport], e.g. of a cassette or a custom transport. Errors of reqwest are
+    /// reported as `SendRequestError` and `ResponseBodyDecodeError` instead.
+    #[error("{source}")]
+    TransportError {
+        /// The underlying error.
+        #[source]
+        source: transport::Error,
+    },
+
+    /// The robots.txt file of the site disallows access to the url for this browser's user agent. Only checked with
+    /// `BrowserBuilder::respect_robots_txt()` enabled.
+    #[error("Access to '{url}' is disallowed by robots.txt for user agent '{user_agent}'!")]
+    RobotsTxtDisallowedError {
+        /// The disallowed `url`.
+        url: String,
+        /// The `user_agent` the robots.txt rules were matched against.
+        user_agent: String,
+    },
+
+    /// The server redirected more than the allowed maximum number of times.
+    #[error("Too many redirects (more than {max}), last redirect from '{url}'!")]
+    TooManyRedirectsError {
+        /// The `url` of the last redirecting response.
+        url: String,
+        /// The maximum number of redirects followed.
+        max: usize,
+    },
+
+    /// The page was loaded with an unexpected status. Only checked with `BrowserBuilder::error_on_status()` enabled or
+    /// when expecting a status explicitly, e.g. with `Browser::navigate_to_expecting()`.
+    #[error("Unexpected status {status} for '{url}', expected {expected}!")]
+    HttpStatusError {
+        /// The `url` of the page after following all redirects.
+        url: String,
+        /// The actual `status` of the page.
+        status: StatusCode,
+        /// The `expected` status.
+        expected: ExpectedStatus,
+        /// The complete `response`, see `Error::page()`.
+        response: Box<UnexpectedResponse>,
+    },
+
     /// There was an error while building the [`Page`][Page] from the decoded http response.
     #[error("{source}")]
     PageError {
@@ -56,14 +130,50 @@ pub enum Error {
     },
 }
 
+impl From<transport::Error> for Error {
+    fn from(error: transport::Error) -> Self {
+        match error {
+            transport::Error::ConstructHttpClientError { source } => {
+                Self::ConstructHttpClientError { source }
+            }
+            transport::Error::SendRequestError { source } => Self::SendRequestError { source },
+            transport::Error::ResponseBodyDecodeError { source } => {
+                Self::ResponseBodyDecodeError { source }
+            }
+            error => Self::TransportError { source: error },
+        }
+    }
+}
+
+impl Error {
+    /// Returns the page loaded with an unexpected status for an `HttpStatusError`, or `None` for all other errors.
+    pub fn page(&self) -> Option<Page> {
+        match self {
+            Self::HttpStatusError { response, .. } => Some(response.page()),
+            _ => None,
+        }
+    }
+}
+
 /// Short-hand for `std::result::Result<T, no_browser::browser::Error>`.
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The maximum number of redirects followed for a single request.
+const MAX_REDIRECTS: usize = 10;
+
+/// The user agent matched against robots.txt files, if no user agent is set.
+const DEFAULT_ROBOTS_USER_AGENT: &str = "no_browser";
+
 /// A `light-weight` browser wrapped around a [reqwest `Client`](https://crates.io/crates/reqwest) to navigate to web
-/// pages and submit forms.
+/// pages and submit forms. Requests are sent via a pluggable [`Transport`][Transport], which defaults to the network.
 ///
 /// Use `Browser::builder()` to initialize an instance.
 ///
+/// With the `tracing` feature, navigation and form submissions are instrumented with
+/// [tracing](https://crates.io/crates/tracing) spans. Every response and redirect is logged as `DEBUG` event with
+/// method, url, status and duration; submitted form fields are logged with the values of password inputs masked, also
+/// in the query string of forms submitted via `GET`.
+///
 /// # Example
 ///
 /// ```
@@ -78,7 +188,16 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// ```
 #[derive(Debug)]
 pub struct Browser {
-    client: Client,
+    transport: Box<dyn Transport>,
+    cookie_jar: Option<Arc<Jar>>,
+    har_recorder: Option<HarRecorder>,
+    middlewares: Vec<Box<dyn Middleware>>,
+    cache: Option<HttpCache>,
+    user_agent: Option<HeaderValue>,
+    robots: Option<Mutex<HashMap<String, Arc<RobotsTxt>>>>,
+    retry_policy: Option<RetryPolicy>,
+    rate_limiter: Option<RateLimiter>,
+    expected_status: ExpectedStatus,
 }
 
 impl Browser {
@@ -89,51 +208,445 @@ impl Browser {
 
     /// Navigate to a given `url`, optionally appending `query` parameters. Upon success the http response is decoded
     /// and used to initialize and return a [`Page`][Page] instance.
+    ///
+    /// With `BrowserBuilder::error_on_status()` enabled, pages loaded with a status other than `2xx` result in an
+    /// `HttpStatusError`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, query), err)
+    )]
     pub fn navigate_to(&self, url: &str, query: Option<&Vec<(&str, &str)>>) -> Result<Page> {
-        let mut rb = self.client.get(url);
+        self.navigate(url, query)?
+            .expect_status(self.expected_status.clone())
+    }
+
+    /// Builds the page of a response received with `send()` and checks its status like `navigate_to()`, i.e. fails
+    /// with an `HttpStatusError` if `BrowserBuilder::error_on_status()` is enabled and the status is not `2xx`.
+    pub(crate) fn expected_page(
+        &self,
+        request: Request,
+        response: Response,
+        cache_status: Option<CacheStatus>,
+    ) -> Result<Page> {
+        Self::build_page(request, response, cache_status)?
+            .expect_status(self.expected_status.clone())
+    }
+
+    /// Like `navigate_to()`, but fails with an `HttpStatusError` unless the page is loaded with the `expected` status,
+    /// regardless of `BrowserBuilder::error_on_status()`.
+    pub fn navigate_to_expecting<E: Into<ExpectedStatus>>(
+        &self,
+        url: &str,
+        query: Option<&Vec<(&str, &str)>>,
+        expected: E,
+    ) -> Result<Page> {
+        self.navigate(url, query)?.expect_status(expected)
+    }
+
+    fn navigate(&self, url: &str, query: Option<&Vec<(&str, &str)>>) -> Result<Page> {
+        let mut url = Self::parse_url(url)?;
 
         if let Some(query_value) = query {
-            rb = rb.query(query_value)
+            extend_query(&mut url, query_value);
         }
 
-        let resp = rb
-            .send()
-            .map_err(|error| Error::SendRequestError { source: error })?;
-
-        Self::build_page(Method::GET, resp)
+        let (request, response, cache_status) = self.send(Request::new(Method::GET, url))?;
+        Self::build_page(request, response, cache_status)
     }
 
     /// Uses this [`Browser`][Browser] instance to submit a given `form` using a specific input/button
     /// (`submit_button_name`). Upon success the http response is decoded and used to initialize and return a
     /// [`Page`][Page] instance.
+    ///
+    /// With `BrowserBuilder::error_on_status()` enabled, pages loaded with a status other than `2xx` result in an
+    /// `HttpStatusError`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, form), fields(form.id = form.id()), err)
+    )]
     pub fn submit_form(&self, form: &Form, submit_button_name: Option<&str>) -> Result<Page> {
-        let info = form.submit(submit_button_name)?;
+        self.submit(form, submit_button_name)?
+            .expect_status(self.expected_status.clone())
+    }
+
+    /// Like `submit_form()`, but fails with an `HttpStatusError` unless the page is loaded with the `expected` status,
+    /// regardless of `BrowserBuilder::error_on_status()`.
+    pub fn submit_form_expecting<E: Into<ExpectedStatus>>(
+        &self,
+        form: &Form,
+        submit_button_name: Option<&str>,
+        expected: E,
+    ) -> Result<Page> {
+        self.submit(form, submit_button_name)?
+            .expect_status(expected)
+    }
 
-        let rb = if info.method == Method::GET {
-            self.client.get(info.url).query(&info.data)
+    fn submit(&self, form: &Form, submit_button_name: Option<&str>) -> Result<Page> {
+        let info = form.submit(submit_button_name)?;
+        let mut url = Self::parse_url(&info.url)?;
+
+        let request = if info.method == Method::GET {
+            extend_query(&mut url, &info.data);
+            let request = Request::new(Method::GET, url);
+            // keep passwords submitted in the query out of the logs
+            #[cfg(feature = "tracing")]
+            let request = request.with_masked_query_params(form.password_names());
+            request
         } else {
-            self.client.post(info.url).form(&info.data)
+            Request::new(info.method, url)
+                .with_header(
+                    CONTENT_TYPE,
+                    HeaderValue::from_static("application/x-www-form-urlencoded"),
+                )
+                .with_body(form_urlencode(&info.data))
+        };
+
+        let (request, response, cache_status) = self.send(request)?;
+        Self::build_page(request, response, cache_status)
+    }
+
+    /// Fetches the raw response for the given `url`, e.g. for subresources like images or stylesheets.
+    pub(crate) fn fetch(&self, url: &Url) -> Result<Response> {
+        Ok(self.send(Request::new(Method::GET, url.clone()))?.1)
+    }
+
+    fn parse_url(url: &str) -> Result<Url> {
+        Url::parse(url).map_err(|error| Error::UrlParseError {
+            url: url.to_owned(),
+            reason: error.to_string(),
+        })
+    }
+
+    /// Sends the request, following up to `MAX_REDIRECTS` redirects and handling cookies. Every hop is checked against
+    /// robots.txt (if enabled), passes the [`Middleware`][Middleware] chain and is recorded by the
+    /// [`HarRecorder`][HarRecorder], if any. Returns the final request and its response.
+    pub(crate) fn send(
+        &self,
+        request: Request,
+    ) -> Result<(Request, Response, Option<CacheStatus>)> {
+        self.send_with_redirects(request, &mut Vec::new())
+    }
+
+    /// Like `send()`, but additionally pushes the url and status of every redirect followed onto `redirects`.
+    pub(crate) fn send_with_redirects(
+        &self,
+        mut request: Request,
+        redirects: &mut Vec<(Url, StatusCode)>,
+    ) -> Result<(Request, Response, Option<CacheStatus>)> {
+        loop {
+            self.check_robots_txt(request.url())?;
+
+            if let Some(user_agent) = &self.user_agent {
+                request
+                    .headers_mut()
+                    .entry(USER_AGENT)
+                    .or_insert_with(|| user_agent.clone());
+            }
+
+            if let Some(cookie) = self
+                .cookie_jar
+                .as_ref()
+                .and_then(|jar| jar.cookies(request.url()))
+            {
+                request.headers_mut().insert(COOKIE, cookie);
+            }
+
+            #[cfg(feature = "tracing")]
+            let start = Instant::now();
+
+            let (response, cache_status) = self.exchange(&request)?;
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                method = %request.method(),
+                url = %request.loggable_url(),
+                status = response.status().as_u16(),
+                duration_ms = start.elapsed().as_millis() as u64,
+                cache = ?cache_status,
+                "response received"
+            );
+
+            if let Some(jar) = &self.cookie_jar {
+                let mut cookies = response.headers().get_all(SET_COOKIE).iter();
+                jar.set_cookies(&mut cookies, request.url());
+            }
+
+            let location = response
+                .headers()
+                .get(LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| request.url().join(location).ok());
+
+            let Some(location) = location.filter(|_| response.status().is_redirection()) else {
+                return Ok((request, response, cache_status));
+            };
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                status = response.status().as_u16(),
+                from = %request.loggable_url(),
+                to = %transport::mask_query(&location, request.masked_query_params()),
+                "following redirect"
+            );
+
+            redirects.push((request.url().clone(), response.status()));
+            if redirects.len() > MAX_REDIRECTS {
+                return Err(Error::TooManyRedirectsError {
+                    url: request.url().to_string(),
+                    max: MAX_REDIRECTS,
+                });
+            }
+
+            #[cfg(feature = "tracing")]
+            let masked_query_params = request.masked_query_params().to_vec();
+
+            // like web browsers, switch to GET for 303 and for POST requests redirected with 301 or 302
+            let method = request.method().clone();
+            let keep_method = match response.status() {
+                StatusCode::SEE_OTHER => method == Method::HEAD,
+                StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND => method != Method::POST,
+                _ => true,
+            };
+
+            request = if keep_method {
+                let mut redirected = Request::new(method, location);
+                if let Some(content_type) = request.headers().get(CONTENT_TYPE) {
+                    redirected = redirected.with_header(CONTENT_TYPE, content_type.clone());
+                }
+                if let Some(body) = request.body() {
+                    redirected = redirected.with_body(body);
+                }
+                redirected
+            } else {
+                Request::new(Method::GET, location)
+            };
+            #[cfg(feature = "tracing")]
+            {
+                request = request.with_masked_query_params(masked_query_params);
+            }
+        }
+    }
+
+    /// Returns the user agent matched against robots.txt files.
+    fn robots_user_agent(&self) -> &str {
+        self.user_agent
+            .as_ref()
+            .and_then(|user_agent| user_agent.to_str().ok())
+            .unwrap_or(DEFAULT_ROBOTS_USER_AGENT)
+    }
+
+    /// Returns the robots.txt of the site of `url`, or `None` if robots.txt is not respected.
+    fn robots_txt(&self, url: &Url) -> Result<Option<Arc<RobotsTxt>>> {
+        match self.robots {
+            Some(_) => self.site_robots_txt(url).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the robots.txt of the site of `url`, fetching it on first access. Missing robots.txt files allow
+    /// everything, unreachable ones (server errors) disallow everything. Fetched files are only kept for later
+    /// requests if robots.txt is respected.
+    pub(crate) fn site_robots_txt(&self, url: &Url) -> Result<Arc<RobotsTxt>> {
+        let origin = url.origin().ascii_serialization();
+        if let Some(robots_txt) = self
+            .robots
+            .as_ref()
+            .and_then(|robots| robots.lock().unwrap().get(&origin).cloned())
+        {
+            return Ok(robots_txt);
+        }
+
+        let robots_url = url.join("/robots.txt").unwrap();
+        let (_, response, _) = self.send(Request::new(Method::GET, robots_url))?;
+        let robots_txt = Arc::new(match response.status() {
+            status if status.is_success() => {
+                RobotsTxt::parse(&String::from_utf8_lossy(response.body()))
+            }
+            status if status.is_server_error() => RobotsTxt::disallow_all(),
+            _ => RobotsTxt::allow_all(),
+        });
+
+        if let Some(robots) = &self.robots {
+            robots.lock().unwrap().insert(origin, robots_txt.clone());
+        }
+        Ok(robots_txt)
+    }
+
+    /// Fails with `RobotsTxtDisallowedError`, if robots.txt is respected and disallows access to `url`.
+    fn check_robots_txt(&self, url: &Url) -> Result<()> {
+        if url.path() == "/robots.txt" {
+            return Ok(());
+        }
+
+        match self.robots_txt(url)? {
+            Some(robots_txt) if !robots_txt.is_allowed(self.robots_user_agent(), url) => {
+                Err(Error::RobotsTxtDisallowedError {
+                    url: url.to_string(),
+                    user_agent: self.robots_user_agent().to_owned(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the `Crawl-delay` requested by the robots.txt of the site of `url`, if robots.txt is respected.
+    fn robots_crawl_delay(&self, url: &Url) -> Option<Duration> {
+        self.robots_txt(url)
+            .ok()
+            .flatten()
+            .and_then(|robots_txt| robots_txt.crawl_delay(self.robots_user_agent()))
+    }
+
+    /// Exchanges a single request for a response, answering it from the [`HttpCache`][HttpCache], if possible, and
+    /// passing it down the [`Middleware`][Middleware] chain otherwise.
+    fn exchange(&self, request: &Request) -> Result<(Response, Option<CacheStatus>)> {
+        let send = |request: Request| self.send_with_retries(request);
+
+        let Some(cache) = &self.cache else {
+            return Ok((send(request.clone())?, None));
+        };
+
+        if request.method() != Method::GET {
+            let response = send(request.clone())?;
+            ignore_cache_error(cache.invalidate(request, &response));
+            return Ok((response, None));
+        }
+
+        let cached = cache.lookup(request);
+        if let Some(cached) = cached.as_ref().filter(|cached| cached.is_fresh()) {
+            return Ok((cached.response(), Some(CacheStatus::Hit)));
+        }
+
+        let request_time = SystemTime::now();
+        let conditional = cached
+            .as_ref()
+            .and_then(|cached| cached.conditional_request(request));
+        let response = send(conditional.unwrap_or_else(|| request.clone()))?;
+
+        match cached {
+            Some(mut cached) if response.status() == StatusCode::NOT_MODIFIED => {
+                ignore_cache_error(cache.revalidate(&mut cached, &response, request_time));
+                Ok((cached.response(), Some(CacheStatus::Revalidated)))
+            }
+            _ => {
+                ignore_cache_error(cache.store(request, &response, request_time));
+                Ok((response, Some(CacheStatus::Miss)))
+            }
+        }
+    }
+
+    /// Passes the request down the [`Middleware`][Middleware] chain, sending it again for as long as the
+    /// [`RetryPolicy`][RetryPolicy] asks for it.
+    fn send_with_retries(&self, request: Request) -> transport::Result<Response> {
+        let send = |request: Request| {
+            Next::new(&self.middlewares, &|request| self.dispatch(&request)).run(request)
         };
 
-        let resp = rb
-            .send()
-            .map_err(|error| Error::SendRequestError { source: error })?;
+        let Some(retry_policy) = &self.retry_policy else {
+            return send(request);
+        };
+
+        let mut attempt = 1;
+        loop {
+            let result = send(request.clone());
+            let Some(delay) = retry_policy.retry_delay(&request, attempt, &result) else {
+                return result;
+            };
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                method = %request.method(),
+                url = %request.loggable_url(),
+                attempt,
+                delay_ms = delay.as_millis() as u64,
+                "retrying request"
+            );
+
+            thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
+    /// Sends a single request with the [`Transport`][Transport] at the end of the middleware chain, recording it with
+    /// the [`HarRecorder`][HarRecorder], if any. Blocks while the [`RateLimit`][RateLimit] or the robots.txt
+    /// `Crawl-delay` of the host is exceeded.
+    fn dispatch(&self, request: &Request) -> transport::Result<Response> {
+        let _permit = self.rate_limiter.as_ref().map(|rate_limiter| {
+            // robots.txt itself is fetched before its crawl delay is known
+            let crawl_delay = match request.url().path() {
+                "/robots.txt" => None,
+                _ => self.robots_crawl_delay(request.url()),
+            };
+            rate_limiter.acquire(request.url(), crawl_delay)
+        });
+
+        let pending = self
+            .har_recorder
+            .as_ref()
+            .map(|_| PendingEntry::new(request));
+
+        let start = Instant::now();
+        let response = self.transport.send(request)?;
+
+        if let (Some(recorder), Some(pending)) = (&self.har_recorder, pending) {
+            recorder.record(pending.finish(&response, start.elapsed()));
+        }
+
+        Ok(response)
+    }
 
-        Self::build_page(info.method, resp)
+    pub(crate) fn build_page(
+        request: Request,
+        response: Response,
+        cache_status: Option<CacheStatus>,
+    ) -> Result<Page> {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let text = response.text();
+
+        Ok(Page::build(
+            request.method().clone(),
+            request.url().clone(),
+            status,
+            headers,
+            text,
+        )
+        .with_cache_status(cache_status))
     }
+}
 
-    fn build_page(method: Method, resp: Response) -> Result<Page> {
-        let url = resp.url().clone();
-        let status = resp.status();
-        let headers = resp.headers().clone();
-        let text = resp
-            .text()
-            .map_err(|error| Error::ResponseBodyDecodeError { source: error })?;
+/// Appends `pairs` to the query of `url`. Like `reqwest::RequestBuilder::query()`, an empty query is removed
+/// entirely instead of leaving a trailing `?`.
+fn extend_query<I, K, V>(url: &mut Url, pairs: I)
+where
+    I: IntoIterator,
+    I::Item: Borrow<(K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    url.query_pairs_mut().extend_pairs(pairs);
+    if url.query() == Some("") {
+        url.set_query(None);
+    }
+}
 
-        Ok(Page::build(method, url, status, headers, text))
+/// Logs a failure of the [`HttpCache`][HttpCache] to write its cache directory. Cache write errors never fail a
+/// request, the response is just not cached.
+fn ignore_cache_error(result: cache::Result<()>) {
+    if let Err(_error) = result {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(error = %_error, "failed to write http cache");
     }
 }
 
+/// Encodes `data` as `application/x-www-form-urlencoded`.
+fn form_urlencode(data: &[(String, String)]) -> String {
+    let mut url = Url::parse("http://localhost/").unwrap();
+    url.query_pairs_mut().extend_pairs(data);
+
+    url.query().unwrap_or_default().to_owned()
+}
+
 /// A builder to initialize a [`Browser`][Browser] instance. It allows tweaking advanced settings for the http client.
 ///
 /// Refer to the documentation of the public methods to learn about the available settings and their defaults. Use
@@ -143,6 +656,16 @@ pub struct BrowserBuilder {
     cookie_store: bool,
     skip_tls_verify: bool,
     certs: Vec<Certificate>,
+    transport: Option<Box<dyn Transport>>,
+    har_recorder: Option<HarRecorder>,
+    middlewares: Vec<Box<dyn Middleware>>,
+    cache: Option<HttpCache>,
+    user_agent: Option<String>,
+    respect_robots_txt: bool,
+    retry_policy: Option<RetryPolicy>,
+    rate_limit: Option<RateLimit>,
+    host_rate_limits: Vec<(String, RateLimit)>,
+    error_on_status: bool,
 }
 
 impl BrowserBuilder {
@@ -151,6 +674,16 @@ impl BrowserBuilder {
             cookie_store: true,
             skip_tls_verify: false,
             certs: Vec::new(),
+            transport: None,
+            har_recorder: None,
+            middlewares: Vec::new(),
+            cache: None,
+            user_agent: None,
+            respect_robots_txt: false,
+            retry_policy: None,
+            rate_limit: None,
+            host_rate_limits: Vec::new(),
+            error_on_status: false,
         }
     }
 
@@ -187,30 +720,140 @@ impl BrowserBuilder {
         self
     }
 
+    /// Replaces the default [`HttpTransport`][HttpTransport] sending requests over the network with a custom
+    /// [`Transport`][Transport], e.g. a [`CassetteTransport`][crate::cassette::CassetteTransport] to record and
+    /// replay traffic. Settings regarding TLS (`skip_tls_verify()`, `add_cert()`) only apply to the default transport.
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Box::new(transport));
+        self
+    }
+
+    /// Attaches a [`HarRecorder`][HarRecorder] capturing every request and response of this [`Browser`][Browser],
+    /// including redirects. Defaults to no recorder.
+    pub fn har_recorder(mut self, har_recorder: HarRecorder) -> Self {
+        self.har_recorder = Some(har_recorder);
+        self
+    }
+
+    /// Appends a [`Middleware`][Middleware] to the chain intercepting every request and response of this
+    /// [`Browser`][Browser]. Middlewares added first see requests first and responses last. Defaults to no middleware.
+    pub fn middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+
+    /// Attaches an [`HttpCache`][HttpCache] answering `GET` requests with cached responses, if possible. Defaults to no
+    /// cache.
+    pub fn cache(mut self, cache: HttpCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with every request, unless a request already has one. The product token of
+    /// the user agent (e.g. `MyBot` for `MyBot/1.0`) is also used to match robots.txt rules. Defaults to no user
+    /// agent.
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.to_owned());
+        self
+    }
+
+    /// Set whether this [`Browser`][Browser] should fetch the robots.txt file of every site it accesses and refuse
+    /// urls disallowed for its user agent (or `no_browser`, if none is set) with a `RobotsTxtDisallowedError`.
+    /// Requests to a site are spaced by the `Crawl-delay` of its robots.txt, if any. Defaults to `false`.
+    pub const fn respect_robots_txt(mut self, respect_robots_txt: bool) -> Self {
+        self.respect_robots_txt = respect_robots_txt;
+        self
+    }
+
+    /// Sets the [`RetryPolicy`][RetryPolicy] for requests failing with transient errors or retryable status codes, e.g.
+    /// `503 Service Unavailable`. Defaults to no retries.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+
+    /// Sets the [`RateLimit`][RateLimit] applied to every host separately, unless overridden with `host_rate_limit()`.
+    /// Defaults to no limit.
+    pub const fn rate_limit(mut self, rate_limit: RateLimit) -> Self {
+        self.rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Sets the [`RateLimit`][RateLimit] for requests to `host`, overriding the one set with `rate_limit()`.
+    pub fn host_rate_limit(mut self, host: &str, rate_limit: RateLimit) -> Self {
+        self.host_rate_limits
+            .push((host.to_ascii_lowercase(), rate_limit));
+        self
+    }
+
+    /// Set whether navigation and form submissions fail with an `HttpStatusError` for pages loaded with a status other
+    /// than `2xx`, after following redirects. The error still carries the page. Defaults to `false`.
+    pub const fn error_on_status(mut self, error_on_status: bool) -> Self {
+        self.error_on_status = error_on_status;
+        self
+    }
+
     /// Completes configuration of the [reqwest `Client`](https://crates.io/crates/reqwest) and returns the
     /// [`Browser`][Browser].
     pub fn finish(self) -> Result<Browser> {
-        let mut client = reqwest::blocking::ClientBuilder::new().cookie_store(self.cookie_store);
+        let user_agent = self
+            .user_agent
+            .map(|user_agent| {
+                HeaderValue::from_str(&user_agent)
+                    .map_err(|_| Error::InvalidUserAgentError { user_agent })
+            })
+            .transpose()?;
+
+        let transport = match self.transport {
+            Some(transport) => transport,
+            None => {
+                let mut client = reqwest::blocking::ClientBuilder::new().redirect(Policy::none());
+
+                if self.skip_tls_verify {
+                    client = client.danger_accept_invalid_certs(true);
+                }
 
-        if self.skip_tls_verify {
-            client = client.danger_accept_invalid_certs(true);
-        }
+                for cert in self.certs {
+                    client = client.add_root_certificate(cert);
+                }
 
-        for cert in self.certs {
-            client = client.add_root_certificate(cert);
-        }
+                let client = client
+                    .build()
+                    .map_err(|error| Error::ConstructHttpClientError { source: error })?;
 
-        let client = client
-            .build()
-            .map_err(|error| Error::ConstructHttpClientError { source: error })?;
+                Box::new(HttpTransport::from_client(client))
+            }
+        };
 
-        Ok(Browser { client })
+        Ok(Browser {
+            transport,
+            cookie_jar: self.cookie_store.then(|| Arc::new(Jar::default())),
+            har_recorder: self.har_recorder,
+            middlewares: self.middlewares,
+            cache: self.cache,
+            user_agent,
+            robots: self.respect_robots_txt.then(Mutex::default),
+            retry_policy: self.retry_policy,
+            rate_limiter: (self.rate_limit.is_some()
+                || !self.host_rate_limits.is_empty()
+                || self.respect_robots_txt)
+                .then(|| {
+                    RateLimiter::new(self.rate_limit, self.host_rate_limits.into_iter().collect())
+                }),
+            expected_status: match self.error_on_status {
+                true => ExpectedStatus::Success,
+                false => ExpectedStatus::Any,
+            },
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{browser::Browser, input::InputType};
+    use crate::{
+        browser::{Browser, Error},
+        input::InputType,
+    };
     use std::{collections::HashMap, net::SocketAddr, thread};
     use tiny_http::{Response, Server};
 
@@ -342,8 +985,8 @@ mod tests {
         let params = url.find('?');
         let fragment = url.rfind('#');
 
-        if params.is_some() {
-            let url = substring(url, params.unwrap() + 1, fragment);
+        if let Some(params) = params {
+            let url = substring(url, params + 1, fragment);
 
             for param in url.split('&') {
                 let mut param = param.split('=');
@@ -494,6 +1137,83 @@ mod tests {
         assert!(submitted.contains(&"submit=submit".to_owned()));
     }
 
+    #[test]
+    fn omit_empty_query() {
+        use crate::mock::{self, MockTransport};
+
+        let transport = MockTransport::from_fn(|_| {
+            mock::html("<form action='/s' method='get'><input type='submit'></form>")
+        });
+        let log = transport.request_log();
+        let b = Browser::builder().transport(transport).finish().unwrap();
+
+        let p = b.navigate_to("http://a.test/x", Some(&vec![])).unwrap();
+        b.submit_form(p.form(0).unwrap(), None).unwrap();
+        b.navigate_to("http://a.test/x?a=1", Some(&vec![("b", "2")]))
+            .unwrap();
+
+        let urls: Vec<String> = log
+            .requests()
+            .iter()
+            .map(|request| request.url().to_string())
+            .collect();
+        assert_eq!(
+            urls,
+            [
+                "http://a.test/x",
+                "http://a.test/s",
+                "http://a.test/x?a=1&b=2"
+            ]
+        );
+    }
+
+    #[test]
+    fn report_send_request_error() {
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let b = Browser::builder().finish().unwrap();
+
+        let result = b.navigate_to(&format!("http://{addr}/"), None);
+        assert!(matches!(result, Err(Error::SendRequestError { .. })));
+    }
+
+    #[test]
+    fn decode_body_with_charset() {
+        use crate::{mock::MockTransport, transport};
+        use reqwest::{
+            header::{HeaderValue, CONTENT_TYPE},
+            Method, StatusCode,
+        };
+
+        let latin1 = b"<html><body><p>Gr\xfc\xdfe</p></body></html>".to_vec();
+        let transport = MockTransport::new()
+            .respond(
+                Method::GET,
+                "/latin1",
+                transport::Response::new(StatusCode::OK)
+                    .with_header(
+                        CONTENT_TYPE,
+                        HeaderValue::from_static("text/html; charset=iso-8859-1"),
+                    )
+                    .with_body(latin1.clone()),
+            )
+            .respond(
+                Method::GET,
+                "/utf8",
+                transport::Response::new(StatusCode::OK)
+                    .with_header(CONTENT_TYPE, HeaderValue::from_static("text/html"))
+                    .with_body("<p>Grüße</p>"),
+            );
+        let b = Browser::builder().transport(transport).finish().unwrap();
+
+        let p = b.navigate_to("http://a.test/latin1", None).unwrap();
+        assert_eq!(p.select("p").unwrap()[0].inner_html(), "Grüße");
+        let p = b.navigate_to("http://a.test/utf8", None).unwrap();
+        assert_eq!(p.select("p").unwrap()[0].inner_html(), "Grüße");
+    }
+
     #[test]
     fn submit_form_via_post() {
         let addr = echo_server(6);
@@ -531,4 +1251,134 @@ mod tests {
         assert!(submitted.contains(&"text=Testing".to_owned()));
         assert!(submitted.contains(&"submit=submit".to_owned()));
     }
+
+    #[test]
+    fn respect_robots_txt() {
+        use crate::mock::{self, MockTransport};
+        use reqwest::{header::USER_AGENT, Method};
+
+        let transport = MockTransport::new()
+            .respond(
+                Method::GET,
+                "/robots.txt",
+                mock::html("User-agent: *\nDisallow: /private\n\nUser-agent: TestBot\nDisallow: /"),
+            )
+            .respond(Method::GET, "/redirect", mock::redirect("/private/page"))
+            .fallback(|request| mock::html(&format!("{:?}", request.headers().get(USER_AGENT))));
+        let log = transport.request_log();
+        let browser = Browser::builder()
+            .transport(transport)
+            .user_agent("OtherBot/1.0")
+            .respect_robots_txt(true)
+            .finish()
+            .unwrap();
+
+        let page = browser
+            .navigate_to("http://site.test/public", None)
+            .unwrap();
+        assert_eq!(page.text(), "Some(\"OtherBot/1.0\")");
+        assert!(matches!(
+            browser.navigate_to("http://site.test/private/page", None),
+            Err(Error::RobotsTxtDisallowedError { .. })
+        ));
+        assert!(matches!(
+            browser.navigate_to("http://site.test/redirect", None),
+            Err(Error::RobotsTxtDisallowedError { .. })
+        ));
+        // robots.txt is fetched once per site
+        assert_eq!(log.len(), 3);
+
+        let browser = Browser::builder()
+            .transport(MockTransport::from_fn(|_| {
+                mock::html("User-agent: TestBot\nDisallow: /")
+            }))
+            .user_agent("TestBot")
+            .respect_robots_txt(true)
+            .finish()
+            .unwrap();
+        assert!(browser
+            .navigate_to("http://site.test/public", None)
+            .is_err());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn trace_form_submission() {
+        use crate::mock::{self, MockTransport};
+        use reqwest::Method;
+        use std::{
+            io::Write,
+            sync::{Arc, Mutex},
+        };
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let transport = MockTransport::new()
+            .respond(
+                Method::GET,
+                "/login",
+                mock::html(
+                    "<form id='login' action='/login' method='post'>\
+                        <input type='text' name='user'><input type='password' name='pass'></form>\
+                     <form id='unlock' action='/unlock' method='get'>\
+                        <input type='text' name='user'><input type='password' name='pin'></form>",
+                ),
+            )
+            .respond(Method::POST, "/login", mock::redirect("/home"))
+            .respond(Method::GET, "/unlock", mock::redirect("/home"))
+            .respond(Method::GET, "/home", mock::html("welcome"));
+        let browser = Browser::builder().transport(transport).finish().unwrap();
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            let mut page = browser.navigate_to("http://app.test/login", None).unwrap();
+            let form = page.form_mut(0).unwrap();
+            form.input_mut(InputType::Text, "user")
+                .unwrap()
+                .set_value(Some("ferris".to_owned()));
+            form.input_mut(InputType::Password, "pass")
+                .unwrap()
+                .set_value(Some("hunter2".to_owned()));
+            browser.submit_form(form, None).unwrap();
+
+            let form = page.form_mut(1).unwrap();
+            form.input_mut(InputType::Text, "user")
+                .unwrap()
+                .set_value(Some("ferris".to_owned()));
+            form.input_mut(InputType::Password, "pin")
+                .unwrap()
+                .set_value(Some("4711".to_owned()));
+            browser.submit_form(form, None).unwrap();
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(output.contains("navigate_to{url=\"http://app.test/login\"}"));
+        assert!(output.contains("submit_form{submit_button_name=None form.id=\"login\"}"));
+        assert!(output.contains("form submitted form.id=\"login\" method=POST"));
+        assert!(output.contains("fields=user=ferris, pass=********"));
+        assert!(output.contains("following redirect status=303"));
+        assert!(output.contains("status=200"));
+        assert!(!output.contains("hunter2"));
+        assert!(output.contains("url=http://app.test/unlock?user=ferris&pin=********"));
+        assert!(output.contains("from=http://app.test/unlock?user=ferris&pin=********"));
+        assert!(!output.contains("4711"));
+    }
 }
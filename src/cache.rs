@@ -0,0 +1,686 @@
+//! Module containing the [`HttpCache`][HttpCache], an optional [RFC 9111](https://www.rfc-editor.org/rfc/rfc9111)
+//! private http cache for a [`Browser`][crate::Browser].
+//!
+//! The cache stores responses to `GET` requests in memory (`HttpCache::memory()`) or, additionally, on disk
+//! (`HttpCache::disk()`) to survive repeated test runs. It honours the `Cache-Control` directives `no-store`,
+//! `no-cache` and `max-age`, the `Expires`, `Age` and `Vary` headers, and falls back to a heuristic freshness for
+//! responses with a `Last-Modified` header. Responses without explicit freshness or validators (`ETag`,
+//! `Last-Modified`) are not stored. Stale responses are revalidated with conditional requests (`If-None-Match`,
+//! `If-Modified-Since`). Successful unsafe requests (e.g. `POST`) invalidate the cached responses of their url.
+//! Failing to write the cache directory does not fail a request, the response is just not cached on disk.
+//!
+//! Whether a [`Page`][crate::page::Page] came from the cache is reported by its `cache_status()`.
+//!
+//! ```no_run
+//! use no_browser::{
+//!     cache::{CacheStatus, HttpCache},
+//!     Browser,
+//! };
+//!
+//! let browser = Browser::builder()
+//!     .cache(HttpCache::disk("target/http-cache")?)
+//!     .finish()?;
+//!
+//! let page = browser.navigate_to("https://example.com/", None)?;
+//! let page = browser.navigate_to("https://example.com/", None)?;
+//! assert_eq!(page.cache_status(), Some(CacheStatus::Hit));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{
+    headers::{CacheControl, TypedHeaders},
+    transport::{Request, Response},
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use reqwest::{
+    header::{
+        HeaderMap, HeaderName, HeaderValue, AGE, CONTENT_LENGTH, DATE, ETAG, EXPIRES,
+        IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, LOCATION, VARY,
+    },
+    Method, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+/// An error occurred while reading or writing the cache directory of an [`HttpCache`][HttpCache].
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A file or directory of the cache could not be read or written.
+    #[error("Failed to access '{path}'!")]
+    IoError {
+        /// The `path` of the file or directory.
+        path: String,
+        /// The underlying error.
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::cache::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Reports how the [`HttpCache`][HttpCache] of a [`Browser`][crate::Browser] was involved in loading a page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheStatus {
+    /// The response was not cached (or stale without validators) and was fetched from the server.
+    Miss,
+    /// A fresh cached response was used without contacting the server.
+    Hit,
+    /// A stale cached response was used after the server confirmed it with `304 Not Modified`.
+    Revalidated,
+}
+
+/// The fraction of the time since `Last-Modified` used as heuristic freshness lifetime.
+const HEURISTIC_FRACTION: u32 = 10;
+
+/// Status codes cacheable without explicit freshness information, see RFC 9110, section 15.1.
+const HEURISTICALLY_CACHEABLE: [u16; 11] = [200, 203, 204, 300, 301, 308, 404, 405, 410, 414, 501];
+
+/// An [RFC 9111](https://www.rfc-editor.org/rfc/rfc9111) private http cache. Clones share the same cache, so a single
+/// cache can be attached to several browsers with `BrowserBuilder::cache()`. See the
+/// [module documentation][crate::cache] for details.
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    dir: Option<PathBuf>,
+    entries: HashMap<String, Vec<Entry>>,
+}
+
+impl HttpCache {
+    /// Returns an empty [`HttpCache`][HttpCache] keeping responses in memory only.
+    pub fn memory() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                dir: None,
+                entries: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Returns an [`HttpCache`][HttpCache] persisting responses in directory `dir`, which is created if necessary.
+    /// Responses cached in `dir` by earlier runs are loaded. Cache files that can't be read are skipped, corrupt ones are
+    /// removed.
+    pub fn disk<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).map_err(|error| io_error(dir, error))?;
+
+        let mut entries = HashMap::new();
+        for file in fs::read_dir(dir).map_err(|error| io_error(dir, error))? {
+            let path = file.map_err(|error| io_error(dir, error))?.path();
+            if path.extension().is_none_or(|extension| extension != "json") {
+                continue;
+            }
+
+            let Ok(json) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let Ok(variants) = serde_json::from_str::<Vec<Entry>>(&json) else {
+                // e.g. left truncated by an interrupted run; it would be overwritten anyway
+                let _ = fs::remove_file(&path);
+                continue;
+            };
+            if let Some(first) = variants.first() {
+                entries.insert(first.url.clone(), variants);
+            }
+        }
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(Inner {
+                dir: Some(dir.to_owned()),
+                entries,
+            })),
+        })
+    }
+
+    /// Returns the number of cached responses, counting each variant selected by `Vary` separately.
+    pub fn len(&self) -> usize {
+        let inner = self.inner.lock().unwrap();
+        inner.entries.values().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if no responses are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all cached responses, including the files in the cache directory, if any.
+    pub fn clear(&self) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        for url in inner.entries.keys().cloned().collect::<Vec<String>>() {
+            inner.remove(&url)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the cached response matching the `GET` `request`, if any.
+    pub(crate) fn lookup(&self, request: &Request) -> Option<Cached> {
+        let inner = self.inner.lock().unwrap();
+        let variants = inner.entries.get(request.url().as_str())?;
+
+        variants
+            .iter()
+            .find(|entry| entry.matches(request))
+            .map(|entry| Cached {
+                entry: entry.clone(),
+            })
+    }
+
+    /// Stores the `response` to the `GET` `request` (sent at `request_time`), if it is storable.
+    pub(crate) fn store(
+        &self,
+        request: &Request,
+        response: &Response,
+        request_time: SystemTime,
+    ) -> Result<()> {
+        let directives = TypedHeaders::new(response.headers()).cache_control();
+        let vary = vary_names(response.headers());
+        if !is_storable(response, &directives) || vary.iter().any(|name| name == "*") {
+            return Ok(());
+        }
+
+        let entry = Entry {
+            url: request.url().to_string(),
+            vary: vary
+                .iter()
+                .map(|name| (name.clone(), header_value(request.headers(), name)))
+                .collect(),
+            status: response.status().as_u16(),
+            headers: headers_to_pairs(response.headers()),
+            body: STANDARD.encode(response.body()),
+            request_time: unix_secs(request_time),
+            response_time: unix_secs(SystemTime::now()),
+        };
+
+        let mut inner = self.inner.lock().unwrap();
+        let variants = inner.entries.entry(entry.url.clone()).or_default();
+        variants.retain(|variant| variant.vary != entry.vary);
+        variants.push(entry);
+        inner.persist(request.url().as_str())
+    }
+
+    /// Updates the `cached` response with the headers of the `304 Not Modified` response to a conditional request
+    /// (sent at `request_time`). `cached` is updated even if the cache fails to persist the update.
+    pub(crate) fn revalidate(
+        &self,
+        cached: &mut Cached,
+        not_modified: &Response,
+        request_time: SystemTime,
+    ) -> Result<()> {
+        let entry = &mut cached.entry;
+
+        let mut headers = pairs_to_headers(&entry.headers);
+        for name in not_modified.headers().keys() {
+            if name == CONTENT_LENGTH {
+                continue;
+            }
+            headers.remove(name);
+            for value in not_modified.headers().get_all(name) {
+                headers.append(name.clone(), value.clone());
+            }
+        }
+        entry.headers = headers_to_pairs(&headers);
+        entry.request_time = unix_secs(request_time);
+        entry.response_time = unix_secs(SystemTime::now());
+
+        let mut inner = self.inner.lock().unwrap();
+        match inner.entries.get_mut(&entry.url) {
+            Some(variants) => {
+                variants.retain(|variant| variant.vary != entry.vary);
+                variants.push(entry.clone());
+                inner.persist(&entry.url)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Invalidates the cached responses of the url (and `Location`) of an unsafe `request` answered with a non-error
+    /// `response`, see RFC 9111, section 4.4.
+    pub(crate) fn invalidate(&self, request: &Request, response: &Response) -> Result<()> {
+        let unsafe_method = !matches!(
+            *request.method(),
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+        );
+        if !unsafe_method
+            || response.status().is_client_error()
+            || response.status().is_server_error()
+        {
+            return Ok(());
+        }
+
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .and_then(|location| request.url().join(location).ok())
+            .filter(|location| location.origin() == request.url().origin());
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.remove(request.url().as_str())?;
+        if let Some(location) = location {
+            inner.remove(location.as_str())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Inner {
+    fn remove(&mut self, url: &str) -> Result<()> {
+        if self.entries.remove(url).is_none() {
+            return Ok(());
+        }
+
+        match &self.dir {
+            Some(dir) => {
+                let path = dir.join(file_name(url));
+                fs::remove_file(&path).map_err(|error| io_error(&path, error))
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn persist(&self, url: &str) -> Result<()> {
+        let (Some(dir), Some(variants)) = (&self.dir, self.entries.get(url)) else {
+            return Ok(());
+        };
+
+        let path = dir.join(file_name(url));
+        let json = serde_json::to_string_pretty(variants).unwrap();
+        fs::write(&path, json).map_err(|error| io_error(&path, error))
+    }
+}
+
+/// A cached response found by `HttpCache::lookup()`.
+#[derive(Debug)]
+pub(crate) struct Cached {
+    entry: Entry,
+}
+
+impl Cached {
+    /// Returns `true` if the cached response may be used without revalidation.
+    pub(crate) fn is_fresh(&self) -> bool {
+        let headers = pairs_to_headers(&self.entry.headers);
+        let directives = TypedHeaders::new(&headers).cache_control();
+        if directives.no_cache() {
+            return false;
+        }
+
+        freshness_lifetime(&self.entry, &headers, &directives)
+            .is_some_and(|lifetime| lifetime > current_age(&self.entry, &headers))
+    }
+
+    /// Returns the conditional version of `request` to revalidate the cached response, or `None` if the cached
+    /// response has no validators.
+    pub(crate) fn conditional_request(&self, request: &Request) -> Option<Request> {
+        let headers = pairs_to_headers(&self.entry.headers);
+        let etag = headers.get(ETAG);
+        let last_modified = headers.get(LAST_MODIFIED);
+        if etag.is_none() && last_modified.is_none() {
+            return None;
+        }
+
+        let mut request = request.clone();
+        if let Some(etag) = etag {
+            request.headers_mut().insert(IF_NONE_MATCH, etag.clone());
+        }
+        if let Some(last_modified) = last_modified {
+            request
+                .headers_mut()
+                .insert(IF_MODIFIED_SINCE, last_modified.clone());
+        }
+
+        Some(request)
+    }
+
+    /// Returns the cached response.
+    pub(crate) fn response(&self) -> Response {
+        self.entry.response()
+    }
+}
+
+/// A single cached response, serialized as part of a cache file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Entry {
+    url: String,
+    /// The request header values selecting this variant, by lowercase header names listed in `Vary`.
+    vary: Vec<(String, Option<String>)>,
+    status: u16,
+    headers: Vec<(String, String)>,
+    /// The base64 encoded body.
+    body: String,
+    /// The time the request was sent, in seconds since the unix epoch.
+    request_time: u64,
+    /// The time the response was received, in seconds since the unix epoch.
+    response_time: u64,
+}
+
+impl Entry {
+    fn matches(&self, request: &Request) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, value)| header_value(request.headers(), name) == *value)
+    }
+
+    fn response(&self) -> Response {
+        let mut response =
+            Response::new(StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK))
+                .with_body(STANDARD.decode(&self.body).unwrap_or_default());
+        *response.headers_mut() = pairs_to_headers(&self.headers);
+
+        response
+    }
+}
+
+fn is_storable(response: &Response, directives: &CacheControl) -> bool {
+    if directives.no_store()
+        || response.status() == StatusCode::PARTIAL_CONTENT
+        || response.status() == StatusCode::NOT_MODIFIED
+    {
+        return false;
+    }
+
+    let explicit = directives.max_age().is_some() || response.headers().contains_key(EXPIRES);
+    let validators =
+        response.headers().contains_key(ETAG) || response.headers().contains_key(LAST_MODIFIED);
+
+    explicit || validators
+}
+
+/// Returns the freshness lifetime of the cached `entry`, see RFC 9111, section 4.2.1.
+fn freshness_lifetime(
+    entry: &Entry,
+    headers: &HeaderMap,
+    directives: &CacheControl,
+) -> Option<Duration> {
+    if let Some(max_age) = directives.max_age() {
+        return Some(max_age);
+    }
+
+    let date =
+        http_date(headers, DATE).unwrap_or(UNIX_EPOCH + Duration::from_secs(entry.response_time));
+    if let Some(expires) = headers.get(EXPIRES) {
+        // invalid dates, like "0", represent a time in the past
+        let expires = expires
+            .to_str()
+            .ok()
+            .and_then(|expires| httpdate::parse_http_date(expires).ok());
+        return Some(
+            expires
+                .and_then(|expires| expires.duration_since(date).ok())
+                .unwrap_or_default(),
+        );
+    }
+
+    if !HEURISTICALLY_CACHEABLE.contains(&entry.status) {
+        return None;
+    }
+    http_date(headers, LAST_MODIFIED)
+        .and_then(|last_modified| date.duration_since(last_modified).ok())
+        .map(|age| age / HEURISTIC_FRACTION)
+}
+
+/// Returns the current age of the cached `entry`, see RFC 9111, section 4.2.3.
+fn current_age(entry: &Entry, headers: &HeaderMap) -> Duration {
+    let age = headers
+        .get(AGE)
+        .and_then(|age| age.to_str().ok())
+        .and_then(|age| age.trim().parse().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_default();
+    let request_time = UNIX_EPOCH + Duration::from_secs(entry.request_time);
+    let response_time = UNIX_EPOCH + Duration::from_secs(entry.response_time);
+    let date = http_date(headers, DATE).unwrap_or(response_time);
+
+    let apparent_age = response_time.duration_since(date).unwrap_or_default();
+    let response_delay = response_time
+        .duration_since(request_time)
+        .unwrap_or_default();
+    let resident_time = SystemTime::now()
+        .duration_since(response_time)
+        .unwrap_or_default();
+
+    apparent_age.max(age + response_delay) + resident_time
+}
+
+fn http_date(headers: &HeaderMap, name: HeaderName) -> Option<SystemTime> {
+    let value = headers.get(name)?.to_str().ok()?;
+    httpdate::parse_http_date(value).ok()
+}
+
+fn vary_names(headers: &HeaderMap) -> Vec<String> {
+    headers
+        .get_all(VARY)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim().to_ascii_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String> {
+    let values = headers
+        .get_all(name)
+        .iter()
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+        .collect::<Vec<String>>();
+
+    (!values.is_empty()).then(|| values.join(", "))
+}
+
+fn headers_to_pairs(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+fn pairs_to_headers(pairs: &[(String, String)]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for (name, value) in pairs {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            headers.append(name, value);
+        }
+    }
+
+    headers
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Returns the name of the cache file for `url`, derived from a (stable) FNV-1a hash of the url.
+fn file_name(url: &str) -> String {
+    let hash = url.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+
+    format!("{hash:016x}.json")
+}
+
+fn io_error(path: &Path, error: std::io::Error) -> Error {
+    Error::IoError {
+        path: path.display().to_string(),
+        source: error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CacheStatus, HttpCache};
+    use crate::{browser::Browser, mock::MockTransport, transport::Response};
+    use reqwest::{
+        header::{HeaderValue, ACCEPT_LANGUAGE, CACHE_CONTROL, ETAG, IF_NONE_MATCH, VARY},
+        Method, StatusCode,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    fn transport() -> MockTransport {
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        MockTransport::new()
+            .route(Method::GET, "/fresh", move |_| {
+                let count = counter.fetch_add(1, Ordering::SeqCst);
+                Response::new(StatusCode::OK)
+                    .with_header(CACHE_CONTROL, HeaderValue::from_static("max-age=3600"))
+                    .with_body(format!("fresh {count}"))
+            })
+            .route(Method::GET, "/etag", |request| {
+                match request.headers().get(IF_NONE_MATCH) {
+                    Some(etag) if etag == "\"v1\"" => Response::new(StatusCode::NOT_MODIFIED)
+                        .with_header(ETAG, HeaderValue::from_static("\"v1\"")),
+                    _ => Response::new(StatusCode::OK)
+                        .with_header(CACHE_CONTROL, HeaderValue::from_static("no-cache"))
+                        .with_header(ETAG, HeaderValue::from_static("\"v1\""))
+                        .with_body("etag"),
+                }
+            })
+            .route(Method::GET, "/vary", |request| {
+                let language = request.headers().get(ACCEPT_LANGUAGE).cloned();
+                Response::new(StatusCode::OK)
+                    .with_header(CACHE_CONTROL, HeaderValue::from_static("max-age=3600"))
+                    .with_header(VARY, HeaderValue::from_static("Accept-Language"))
+                    .with_body(format!("{language:?}"))
+            })
+            .route(Method::GET, "/no-store", |_| {
+                Response::new(StatusCode::OK)
+                    .with_header(CACHE_CONTROL, HeaderValue::from_static("no-store"))
+            })
+            .respond(Method::GET, "/plain", Response::new(StatusCode::OK))
+            .respond(Method::POST, "/fresh", Response::new(StatusCode::OK))
+    }
+
+    #[test]
+    fn cache_in_memory() {
+        let cache = HttpCache::memory();
+        let browser = Browser::builder()
+            .transport(transport())
+            .cache(cache.clone())
+            .finish()
+            .unwrap();
+        let navigate = |path: &str| {
+            let page = browser
+                .navigate_to(&format!("http://cache.test{path}"), None)
+                .unwrap();
+            (page.text().to_owned(), page.cache_status())
+        };
+
+        assert_eq!(
+            navigate("/fresh"),
+            ("fresh 0".to_owned(), Some(CacheStatus::Miss))
+        );
+        assert_eq!(
+            navigate("/fresh"),
+            ("fresh 0".to_owned(), Some(CacheStatus::Hit))
+        );
+
+        assert_eq!(
+            navigate("/etag"),
+            ("etag".to_owned(), Some(CacheStatus::Miss))
+        );
+        assert_eq!(
+            navigate("/etag"),
+            ("etag".to_owned(), Some(CacheStatus::Revalidated))
+        );
+
+        assert_eq!(navigate("/no-store").1, Some(CacheStatus::Miss));
+        assert_eq!(navigate("/no-store").1, Some(CacheStatus::Miss));
+        // neither explicit freshness nor validators
+        assert_eq!(navigate("/plain").1, Some(CacheStatus::Miss));
+        assert_eq!(navigate("/plain").1, Some(CacheStatus::Miss));
+        assert_eq!(cache.len(), 2);
+
+        let mut request =
+            crate::transport::Request::new(Method::GET, "http://cache.test/vary".parse().unwrap());
+        assert!(cache.lookup(&request).is_none());
+        browser.navigate_to("http://cache.test/vary", None).unwrap();
+        assert!(cache.lookup(&request).is_some());
+        request
+            .headers_mut()
+            .insert(ACCEPT_LANGUAGE, HeaderValue::from_static("de"));
+        assert!(cache.lookup(&request).is_none());
+
+        // unsafe requests invalidate the url
+        let form = crate::page::Page::from_html(
+            "http://cache.test/",
+            "<form action='/fresh' method='post'></form>",
+        )
+        .unwrap();
+        browser.submit_form(form.form(0).unwrap(), None).unwrap();
+        assert_eq!(
+            navigate("/fresh"),
+            ("fresh 1".to_owned(), Some(CacheStatus::Miss))
+        );
+    }
+
+    #[test]
+    fn cache_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let browser = Browser::builder()
+            .transport(transport())
+            .cache(HttpCache::disk(dir.path()).unwrap())
+            .finish()
+            .unwrap();
+        browser
+            .navigate_to("http://cache.test/fresh", None)
+            .unwrap();
+
+        let cache = HttpCache::disk(dir.path()).unwrap();
+        assert_eq!(cache.len(), 1);
+        let browser = Browser::builder()
+            .transport(MockTransport::new())
+            .cache(cache.clone())
+            .finish()
+            .unwrap();
+        let page = browser
+            .navigate_to("http://cache.test/fresh", None)
+            .unwrap();
+        assert_eq!(page.text(), "fresh 0");
+        assert_eq!(page.cache_status(), Some(CacheStatus::Hit));
+
+        // corrupt cache files are removed and unreadable ones skipped instead of failing to open the cache
+        let corrupt = dir.path().join("corrupt.json");
+        std::fs::write(&corrupt, "[{\"url\":").unwrap();
+        std::fs::create_dir(dir.path().join("unreadable.json")).unwrap();
+        assert_eq!(HttpCache::disk(dir.path()).unwrap().len(), 1);
+        assert!(!corrupt.exists());
+
+        cache.clear().unwrap();
+        assert!(HttpCache::disk(dir.path()).unwrap().is_empty());
+
+        // failing to write the cache directory does not fail the request
+        std::fs::remove_dir_all(dir.path()).unwrap();
+        let browser = Browser::builder()
+            .transport(transport())
+            .cache(cache.clone())
+            .finish()
+            .unwrap();
+        let page = browser
+            .navigate_to("http://cache.test/fresh", None)
+            .unwrap();
+        assert_eq!(page.text(), "fresh 0");
+        assert_eq!(page.cache_status(), Some(CacheStatus::Miss));
+    }
+}
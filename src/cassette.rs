@@ -0,0 +1,490 @@
+//! Module containing the [`CassetteTransport`][CassetteTransport] to record and replay http traffic, VCR-style.
+//!
+//! In record mode, every request is sent with an inner [`Transport`][Transport] (usually the
+//! [`HttpTransport`][crate::transport::HttpTransport]) and the interaction is appended to a cassette file. In replay
+//! mode, responses are served from the cassette without any network access, which makes tests deterministic.
+//!
+//! ```no_run
+//! use no_browser::{
+//!     cassette::{CassetteTransport, MatchRules},
+//!     transport::HttpTransport,
+//!     Browser,
+//! };
+//!
+//! let path = "tests/cassettes/wikipedia.json";
+//!
+//! // record once ...
+//! let transport = CassetteTransport::record(path, HttpTransport::new()?);
+//! let browser = Browser::builder().transport(transport).finish()?;
+//! browser.navigate_to("https://en.wikipedia.org/", None)?;
+//!
+//! // ... and replay ever after
+//! let transport = CassetteTransport::replay(path)?
+//!     .match_rules(MatchRules::new().body(true))
+//!     .strict(true);
+//! let browser = Browser::builder().transport(transport).finish()?;
+//! let page = browser.navigate_to("https://en.wikipedia.org/", None)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::transport::{self, Request, Response, Transport};
+use base64::Engine;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Debug,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use thiserror::Error;
+
+/// An error occurred while recording or replaying a cassette.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The cassette file could not be read or written.
+    #[error("Failed to access cassette '{path}'!")]
+    IoError {
+        /// The `path` of the cassette.
+        path: String,
+        /// The underlying error.
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The cassette file could not be parsed.
+    #[error("Invalid cassette '{path}', reason: {reason}")]
+    CassetteParseError {
+        /// The `path` of the cassette.
+        path: String,
+        /// The `reason` the cassette is invalid.
+        reason: String,
+    },
+
+    /// In strict replay mode, the cassette contains no (unused) interaction matching the request.
+    #[error("No recorded interaction in cassette '{path}' matches request {method} {url}!")]
+    UnmatchedRequestError {
+        /// The `path` of the cassette.
+        path: String,
+        /// The http method of the unmatched request.
+        method: String,
+        /// The url of the unmatched request.
+        url: String,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::cassette::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Configures which parts of a request must be equal to a recorded request to replay its response. By default, the
+/// http method and the url are matched.
+#[derive(Debug, Clone)]
+pub struct MatchRules {
+    method: bool,
+    url: bool,
+    body: bool,
+    headers: Vec<HeaderName>,
+}
+
+impl Default for MatchRules {
+    fn default() -> Self {
+        Self {
+            method: true,
+            url: true,
+            body: false,
+            headers: Vec::new(),
+        }
+    }
+}
+
+impl MatchRules {
+    /// Returns the default [`MatchRules`][MatchRules], matching http method and url.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether the http method must match. Defaults to `true`.
+    pub const fn method(mut self, method: bool) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Set whether the url (including the query string) must match. Defaults to `true`.
+    pub const fn url(mut self, url: bool) -> Self {
+        self.url = url;
+        self
+    }
+
+    /// Set whether the request body must match. Defaults to `false`.
+    pub const fn body(mut self, body: bool) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Adds a header whose values must match. Defaults to no headers.
+    pub fn header(mut self, name: HeaderName) -> Self {
+        self.headers.push(name);
+        self
+    }
+
+    fn matches(&self, recorded: &RecordedRequest, request: &Request) -> bool {
+        (!self.method || recorded.method == request.method().as_str())
+            && (!self.url || recorded.url == request.url().as_str())
+            && (!self.body || recorded.body.decode() == request.body().unwrap_or_default())
+            && self.headers.iter().all(|name| {
+                let recorded = recorded
+                    .headers
+                    .iter()
+                    .filter(|(n, _)| n.eq_ignore_ascii_case(name.as_str()))
+                    .map(|(_, v)| v.as_bytes());
+                let actual = request.headers().get_all(name).iter().map(|v| v.as_bytes());
+                recorded.eq(actual)
+            })
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RecordedRequest {
+    method: String,
+    url: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<(String, String)>,
+    #[serde(flatten)]
+    body: Body,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RecordedResponse {
+    status: u16,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<(String, String)>,
+    #[serde(flatten)]
+    body: Body,
+}
+
+/// A body stored as plain text, if it is valid UTF-8, or base64 encoded otherwise.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Body {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body_base64: Option<String>,
+}
+
+impl Body {
+    fn encode(bytes: &[u8]) -> Self {
+        match std::str::from_utf8(bytes) {
+            Ok(_) if bytes.is_empty() => Self::default(),
+            Ok(text) => Self {
+                body: Some(text.to_owned()),
+                body_base64: None,
+            },
+            Err(_) => Self {
+                body: None,
+                body_base64: Some(base64::engine::general_purpose::STANDARD.encode(bytes)),
+            },
+        }
+    }
+
+    fn decode(&self) -> Vec<u8> {
+        match (&self.body, &self.body_base64) {
+            (Some(text), _) => text.as_bytes().to_vec(),
+            (None, Some(encoded)) => base64::engine::general_purpose::STANDARD
+                .decode(encoded)
+                .unwrap_or_default(),
+            (None, None) => Vec::new(),
+        }
+    }
+}
+
+fn encode_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.to_string(),
+                String::from_utf8_lossy(v.as_bytes()).into_owned(),
+            )
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct State {
+    cassette: Cassette,
+    used: Vec<bool>,
+}
+
+/// A [`Transport`][Transport] recording interactions to or replaying them from a cassette file (JSON). Attach it with
+/// `BrowserBuilder::transport()`. See the [module documentation][crate::cassette] for an example.
+#[derive(Debug)]
+pub struct CassetteTransport {
+    path: PathBuf,
+    inner: Option<Box<dyn Transport>>,
+    rules: MatchRules,
+    strict: bool,
+    state: Mutex<State>,
+}
+
+impl CassetteTransport {
+    /// Returns a [`CassetteTransport`][CassetteTransport] in record mode, sending all requests with the `inner`
+    /// transport. The cassette file at `path` is (over-)written after every interaction.
+    pub fn record<P: AsRef<Path>, T: Transport + 'static>(path: P, inner: T) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            inner: Some(Box::new(inner)),
+            rules: MatchRules::default(),
+            strict: false,
+            state: Mutex::new(State {
+                cassette: Cassette::default(),
+                used: Vec::new(),
+            }),
+        }
+    }
+
+    /// Returns a [`CassetteTransport`][CassetteTransport] in replay mode, serving responses from the cassette file at
+    /// `path` without any network access.
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|error| Error::IoError {
+            path: path.display().to_string(),
+            source: error,
+        })?;
+        let cassette: Cassette =
+            serde_json::from_str(&json).map_err(|error| Error::CassetteParseError {
+                path: path.display().to_string(),
+                reason: error.to_string(),
+            })?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            inner: None,
+            rules: MatchRules::default(),
+            strict: false,
+            state: Mutex::new(State {
+                used: vec![false; cassette.interactions.len()],
+                cassette,
+            }),
+        })
+    }
+
+    /// Set the [`MatchRules`][MatchRules] used to find the recorded interaction for a request in replay mode.
+    pub fn match_rules(mut self, rules: MatchRules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    /// Set whether replay mode is strict. Defaults to `false`.
+    ///
+    /// In strict mode, every recorded interaction is replayed at most once (in recording order) and requests without
+    /// a matching interaction fail with an `UnmatchedRequestError`. Otherwise, interactions may be replayed repeatedly
+    /// and unmatched requests receive a `404 Not Found` response.
+    pub const fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Returns the number of interactions in the cassette.
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().cassette.interactions.len()
+    }
+
+    /// Returns `true` if the cassette contains no interactions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn record_interaction(
+        &self,
+        inner: &dyn Transport,
+        request: &Request,
+    ) -> transport::Result<Response> {
+        let response = inner.send(request)?;
+
+        let mut state = self.state.lock().unwrap();
+        state.cassette.interactions.push(Interaction {
+            request: RecordedRequest {
+                method: request.method().to_string(),
+                url: request.url().to_string(),
+                headers: encode_headers(request.headers()),
+                body: Body::encode(request.body().unwrap_or_default()),
+            },
+            response: RecordedResponse {
+                status: response.status().as_u16(),
+                headers: encode_headers(response.headers()),
+                body: Body::encode(response.body()),
+            },
+        });
+        state.used.push(true);
+
+        let json = serde_json::to_string_pretty(&state.cassette).expect("cassette is serializable");
+        if let Some(dir) = self.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(|error| self.io_error(error))?;
+        }
+        fs::write(&self.path, json).map_err(|error| self.io_error(error))?;
+
+        Ok(response)
+    }
+
+    fn replay_interaction(&self, request: &Request) -> transport::Result<Response> {
+        let mut state = self.state.lock().unwrap();
+        let State { cassette, used } = &mut *state;
+
+        let candidates = cassette.interactions.iter().enumerate();
+        let found = candidates
+            .filter(|(idx, _)| !self.strict || !used[*idx])
+            .find(|(_, interaction)| self.rules.matches(&interaction.request, request));
+
+        let Some((idx, interaction)) = found else {
+            if self.strict {
+                return Err(Error::UnmatchedRequestError {
+                    path: self.path.display().to_string(),
+                    method: request.method().to_string(),
+                    url: request.url().to_string(),
+                }
+                .into());
+            }
+            return Ok(Response::new(StatusCode::NOT_FOUND));
+        };
+        used[idx] = true;
+
+        let recorded = &interaction.response;
+        let invalid = |reason: String| Error::CassetteParseError {
+            path: self.path.display().to_string(),
+            reason,
+        };
+        let status =
+            StatusCode::from_u16(recorded.status).map_err(|error| invalid(error.to_string()))?;
+        let mut response = Response::new(status).with_body(recorded.body.decode());
+        for (name, value) in &recorded.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| invalid(format!("invalid header name '{name}'")))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| invalid(format!("invalid value for header '{name}'")))?;
+            response = response.with_header(name, value);
+        }
+
+        Ok(response)
+    }
+
+    fn io_error(&self, error: std::io::Error) -> Error {
+        Error::IoError {
+            path: self.path.display().to_string(),
+            source: error,
+        }
+    }
+}
+
+impl Transport for CassetteTransport {
+    fn send(&self, request: &Request) -> transport::Result<Response> {
+        match &self.inner {
+            Some(inner) => self.record_interaction(inner.as_ref(), request),
+            None => self.replay_interaction(request),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CassetteTransport, Error, MatchRules};
+    use crate::{
+        browser::{self, Browser},
+        transport::{self, HttpTransport},
+    };
+    use reqwest::StatusCode;
+    use std::thread;
+    use tiny_http::{Header, Response, Server};
+
+    fn server() -> String {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        thread::spawn(move || {
+            for (count, request) in server.incoming_requests().take(2).enumerate() {
+                let response = Response::from_string(format!("<p>{} #{count}</p>", request.url()))
+                    .with_header(Header::from_bytes("Set-Cookie", "visited=yes").unwrap());
+                request.respond(response).unwrap();
+            }
+        });
+
+        format!("http://{addr}")
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let base = server();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cassettes/test.json");
+
+        let transport = CassetteTransport::record(&path, HttpTransport::new().unwrap());
+        let browser = Browser::builder().transport(transport).finish().unwrap();
+        let recorded_a = browser.navigate_to(&format!("{base}/a"), None).unwrap();
+        let recorded_b = browser.navigate_to(&format!("{base}/b"), None).unwrap();
+        assert_eq!(recorded_a.text(), "<p>/a #0</p>");
+
+        // the server is gone by now, all responses are served from the cassette
+        let transport = CassetteTransport::replay(&path).unwrap();
+        assert_eq!(transport.len(), 2);
+        let browser = Browser::builder().transport(transport).finish().unwrap();
+        let replayed_b = browser.navigate_to(&format!("{base}/b"), None).unwrap();
+        let replayed_a = browser.navigate_to(&format!("{base}/a"), None).unwrap();
+        let again_a = browser.navigate_to(&format!("{base}/a"), None).unwrap();
+
+        assert_eq!(replayed_a.text(), recorded_a.text());
+        assert_eq!(replayed_b.text(), recorded_b.text());
+        assert_eq!(again_a.text(), recorded_a.text());
+        assert_eq!(replayed_a.headers()["set-cookie"], "visited=yes");
+
+        let unmatched = browser.navigate_to(&format!("{base}/c"), None).unwrap();
+        assert_eq!(*unmatched.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn replay_strictly() {
+        let base = server();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.json");
+
+        let transport = CassetteTransport::record(&path, HttpTransport::new().unwrap());
+        let browser = Browser::builder().transport(transport).finish().unwrap();
+        browser.navigate_to(&format!("{base}/a"), None).unwrap();
+        browser
+            .navigate_to(&format!("{base}/a"), Some(&vec![("q", "1")]))
+            .unwrap();
+
+        let transport = CassetteTransport::replay(&path)
+            .unwrap()
+            .match_rules(MatchRules::new().url(false).header(reqwest::header::COOKIE))
+            .strict(true);
+        let browser = Browser::builder().transport(transport).finish().unwrap();
+
+        // the second request carries the cookie set by the first response
+        let page = browser.navigate_to(&format!("{base}/x"), None).unwrap();
+        assert_eq!(page.text(), "<p>/a #0</p>");
+        let page = browser.navigate_to(&format!("{base}/y"), None).unwrap();
+        assert_eq!(page.text(), "<p>/a?q=1 #1</p>");
+
+        let result = browser.navigate_to(&format!("{base}/a"), None);
+        assert!(matches!(
+            result,
+            Err(browser::Error::TransportError {
+                source: transport::Error::CassetteError {
+                    source: Error::UnmatchedRequestError { .. }
+                }
+            })
+        ));
+    }
+}
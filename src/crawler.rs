@@ -0,0 +1,659 @@
+//! Module containing the [`Crawler`][Crawler] to walk whole web sites with a [`Browser`][crate::Browser].
+//!
+//! Starting from one or more seed urls, a crawler follows the links (`<a href>`, `<area href>`) of every html page
+//! within its scope. Urls are normalised (fragment removed, query parameters sorted) and every url is visited at most
+//! once. Pages are loaded like with `Browser::navigate_to()`, so the settings of the browser apply: requests are spaced and
+//! limited in concurrency per host by its [`RateLimit`][crate::rate_limit::RateLimit], `BrowserBuilder::error_on_status()`
+//! turns pages with an error status into errors, and with `BrowserBuilder::respect_robots_txt()` enabled, disallowed
+//! urls yield errors and the `Crawl-delay` of robots.txt is honoured.
+//!
+//! A crawler is an [`Iterator`] yielding each loaded [`Page`][Page] in crawl order. The remaining work (the
+//! [`Frontier`][Frontier]) can be saved at any time to resume an interrupted crawl later on.
+//!
+//! ```no_run
+//! use no_browser::{crawler::Crawler, rate_limit::RateLimit, Browser};
+//!
+//! let browser = Browser::builder()
+//!     .rate_limit(RateLimit::per_second(4.0).max_concurrent(1))
+//!     .respect_robots_txt(true)
+//!     .finish()?;
+//! let mut crawler = Crawler::builder(&browser)
+//!     .seed("https://example.com/docs/")
+//!     .path_prefix("/docs/")
+//!     .deny(r"\.pdf$")
+//!     .max_depth(3)
+//!     .max_pages(500)
+//!     .finish()?;
+//!
+//! for page in crawler.by_ref().take(100) {
+//!     let page = page?;
+//!     println!("{} {}", page.status(), page.url());
+//! }
+//!
+//! // continue later on with `CrawlerBuilder::resume()`
+//! crawler.frontier().save("target/frontier.json")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{
+    browser::{self, Browser},
+    page::Page,
+    transport::Request,
+};
+use regex::Regex;
+use reqwest::{header::CONTENT_TYPE, Method, Url};
+use scraper::Selector;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    fs,
+    path::Path,
+    thread,
+};
+use thiserror::Error;
+
+/// An error occurred while configuring or running a [`Crawler`][Crawler].
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A seed url could not be parsed.
+    #[error("Failed to parse url '{url}', reason: {reason}")]
+    UrlParseError {
+        /// The given `url` that could not be parsed.
+        url: String,
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+
+    /// An allow or deny pattern could not be parsed.
+    #[error("Failed to parse regex '{pattern}', reason: {reason}")]
+    RegexParseError {
+        /// The given `pattern` that could not be parsed.
+        pattern: String,
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+
+    /// A url could not be loaded by the [`Browser`][Browser]. The crawler continues with the next url.
+    #[error("Failed to crawl '{url}': {source}")]
+    BrowserError {
+        /// The `url` that could not be loaded.
+        url: String,
+        /// The underlying error.
+        #[source]
+        source: Box<browser::Error>,
+    },
+
+    /// A frontier file could not be read or written.
+    #[error("Failed to access '{path}'!")]
+    IoError {
+        /// The `path` of the frontier file.
+        path: String,
+        /// The underlying error.
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// A frontier file could not be parsed.
+    #[error("Invalid frontier file '{path}', reason: {reason}")]
+    FrontierParseError {
+        /// The `path` of the frontier file.
+        path: String,
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::crawler::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The remaining work of a [`Crawler`][Crawler]: the queued urls with their link depth, all urls seen so far, the hosts
+/// of the seed urls and the number of pages crawled. Save it with `save()` and resume with `CrawlerBuilder::resume()`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Frontier {
+    queue: VecDeque<QueuedUrl>,
+    seen: BTreeSet<String>,
+    hosts: BTreeSet<String>,
+    crawled: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct QueuedUrl {
+    url: String,
+    depth: usize,
+}
+
+impl Frontier {
+    /// Loads a frontier saved with `save()` from file `path`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|error| Error::IoError {
+            path: path.display().to_string(),
+            source: error,
+        })?;
+
+        serde_json::from_str(&json).map_err(|error| Error::FrontierParseError {
+            path: path.display().to_string(),
+            reason: error.to_string(),
+        })
+    }
+
+    /// Saves this frontier as JSON to file `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self).unwrap();
+
+        fs::write(path, json).map_err(|error| Error::IoError {
+            path: path.display().to_string(),
+            source: error,
+        })
+    }
+
+    /// Returns the number of queued urls.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if no urls are queued.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns the number of pages crawled so far, including failed ones.
+    pub const fn crawled(&self) -> usize {
+        self.crawled
+    }
+
+    /// Queues `url` unless its normalised form was seen before. The url itself is fetched as discovered, only without
+    /// its fragment, as normalising the query may change its meaning to the server.
+    fn enqueue(&mut self, mut url: Url, depth: usize) {
+        if self.seen.insert(normalize_url(&url).to_string()) {
+            url.set_fragment(None);
+            self.queue.push_back(QueuedUrl {
+                url: url.to_string(),
+                depth,
+            });
+        }
+    }
+}
+
+/// A builder to configure a [`Crawler`][Crawler]. Use `finish()` to get the crawler.
+///
+/// By default, a crawler stays on the hosts of its seed urls, follows links to any depth without a page limit and sends
+/// up to 4 concurrent requests. Limits per host are set on the [`Browser`][Browser] with
+/// `BrowserBuilder::rate_limit()`.
+#[derive(Debug)]
+pub struct CrawlerBuilder<'a> {
+    browser: &'a Browser,
+    seeds: Vec<String>,
+    frontier: Option<Frontier>,
+    same_host: bool,
+    path_prefixes: Vec<String>,
+    allow: Vec<String>,
+    deny: Vec<String>,
+    max_depth: Option<usize>,
+    max_pages: Option<usize>,
+    concurrency: usize,
+}
+
+impl<'a> CrawlerBuilder<'a> {
+    /// Adds a seed `url` to start crawling from.
+    pub fn seed(mut self, url: &str) -> Self {
+        self.seeds.push(url.to_owned());
+        self
+    }
+
+    /// Resumes the crawl of a saved `frontier`. Seed urls already seen in the frontier are ignored.
+    pub fn resume(mut self, frontier: Frontier) -> Self {
+        self.frontier = Some(frontier);
+        self
+    }
+
+    /// Sets whether only urls on the hosts of the seed urls are crawled. Defaults to `true`.
+    pub const fn same_host(mut self, same_host: bool) -> Self {
+        self.same_host = same_host;
+        self
+    }
+
+    /// Restricts the crawl to urls whose path starts with `prefix`. Multiple prefixes are alternatives.
+    pub fn path_prefix(mut self, prefix: &str) -> Self {
+        self.path_prefixes.push(prefix.to_owned());
+        self
+    }
+
+    /// Restricts the crawl to urls matching the regex `pattern`. Multiple patterns are alternatives.
+    pub fn allow(mut self, pattern: &str) -> Self {
+        self.allow.push(pattern.to_owned());
+        self
+    }
+
+    /// Excludes urls matching the regex `pattern` from the crawl.
+    pub fn deny(mut self, pattern: &str) -> Self {
+        self.deny.push(pattern.to_owned());
+        self
+    }
+
+    /// Sets the maximum link depth to follow; the seed urls have depth 0. Defaults to no limit.
+    pub const fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets the maximum number of pages to crawl, including failed ones and pages crawled before resuming. Defaults to
+    /// no limit.
+    pub const fn max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    /// Sets the maximum number of concurrent requests. Defaults to 4.
+    pub const fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency;
+        self
+    }
+
+    /// Validates the configuration and returns the [`Crawler`][Crawler].
+    pub fn finish(self) -> Result<Crawler<'a>> {
+        let compile = |pattern: &String| {
+            Regex::new(pattern).map_err(|error| Error::RegexParseError {
+                pattern: pattern.clone(),
+                reason: error.to_string(),
+            })
+        };
+        let allow = self
+            .allow
+            .iter()
+            .map(compile)
+            .collect::<Result<Vec<Regex>>>()?;
+        let deny = self
+            .deny
+            .iter()
+            .map(compile)
+            .collect::<Result<Vec<Regex>>>()?;
+
+        let mut frontier = self.frontier.unwrap_or_default();
+        for seed in &self.seeds {
+            let url = parse_url(seed)?;
+            frontier.hosts.extend(url.host_str().map(str::to_owned));
+            frontier.enqueue(url, 0);
+        }
+
+        Ok(Crawler {
+            browser: self.browser,
+            frontier,
+            scope: Scope {
+                same_host: self.same_host,
+                path_prefixes: self.path_prefixes,
+                allow,
+                deny,
+            },
+            max_depth: self.max_depth,
+            max_pages: self.max_pages,
+            concurrency: self.concurrency.max(1),
+            ready: VecDeque::new(),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Scope {
+    same_host: bool,
+    path_prefixes: Vec<String>,
+    allow: Vec<Regex>,
+    deny: Vec<Regex>,
+}
+
+impl Scope {
+    fn contains(&self, url: &Url, hosts: &BTreeSet<String>) -> bool {
+        let host = url.host_str().unwrap_or_default();
+
+        matches!(url.scheme(), "http" | "https")
+            && (!self.same_host || hosts.contains(host))
+            && (self.path_prefixes.is_empty()
+                || self
+                    .path_prefixes
+                    .iter()
+                    .any(|prefix| url.path().starts_with(prefix)))
+            && (self.allow.is_empty()
+                || self.allow.iter().any(|allow| allow.is_match(url.as_str())))
+            && !self.deny.iter().any(|deny| deny.is_match(url.as_str()))
+    }
+}
+
+/// A polite web crawler walking a site with a [`Browser`][Browser], yielding each loaded [`Page`][Page]. Failing urls
+/// yield an error, but don't stop the crawl. See the [module documentation][crate::crawler] for an example.
+#[derive(Debug)]
+pub struct Crawler<'a> {
+    browser: &'a Browser,
+    frontier: Frontier,
+    scope: Scope,
+    max_depth: Option<usize>,
+    max_pages: Option<usize>,
+    concurrency: usize,
+    ready: VecDeque<Result<Page>>,
+}
+
+impl<'a> Crawler<'a> {
+    /// Returns a [`CrawlerBuilder`][CrawlerBuilder] to configure a crawler using the given `browser`.
+    pub fn builder(browser: &'a Browser) -> CrawlerBuilder<'a> {
+        CrawlerBuilder {
+            browser,
+            seeds: Vec::new(),
+            frontier: None,
+            same_host: true,
+            path_prefixes: Vec::new(),
+            allow: Vec::new(),
+            deny: Vec::new(),
+            max_depth: None,
+            max_pages: None,
+            concurrency: 4,
+        }
+    }
+
+    /// Returns the remaining work of this crawler. Pages already loaded, but not yet yielded, are not part of the
+    /// frontier anymore.
+    pub const fn frontier(&self) -> &Frontier {
+        &self.frontier
+    }
+
+    /// Takes the next batch of urls from the frontier, loads them concurrently and queues the resulting pages.
+    fn crawl_batch(&mut self) {
+        let remaining = self.max_pages.map_or(usize::MAX, |max_pages| {
+            max_pages.saturating_sub(self.frontier.crawled)
+        });
+
+        let mut batch = Vec::new();
+        while batch.len() < self.concurrency.min(remaining) {
+            let Some(queued) = self.frontier.queue.pop_front() else {
+                break;
+            };
+            let url = match parse_url(&queued.url) {
+                Ok(url) => url,
+                Err(error) => {
+                    self.frontier.crawled += 1;
+                    self.ready.push_back(Err(error));
+                    continue;
+                }
+            };
+            // the scope may have changed when resuming a saved frontier
+            if queued.depth > 0 && !self.scope.contains(&url, &self.frontier.hosts) {
+                continue;
+            }
+
+            batch.push(queued);
+        }
+        self.frontier.crawled += batch.len();
+
+        // the browser's rate limits block the threads of requests to the same host as needed; pages are built
+        // afterwards, as they can't be sent between threads
+        let browser = self.browser;
+        let results = thread::scope(|scope| {
+            let handles = batch
+                .iter()
+                .map(|queued| {
+                    scope.spawn(move || {
+                        let url = parse_url(&queued.url).unwrap();
+                        browser.send(Request::new(Method::GET, url))
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        for (queued, result) in batch.into_iter().zip(results) {
+            let page = result
+                .and_then(|(request, response, cache_status)| {
+                    browser.expected_page(request, response, cache_status)
+                })
+                .map_err(|error| Error::BrowserError {
+                    url: queued.url.clone(),
+                    source: Box::new(error),
+                });
+
+            if let Ok(page) = &page {
+                // mark the final url after redirects as seen, too
+                self.frontier
+                    .seen
+                    .insert(normalize_url(page.url()).to_string());
+
+                if self
+                    .max_depth
+                    .is_none_or(|max_depth| queued.depth < max_depth)
+                {
+                    for link in links(page) {
+                        if self.scope.contains(&link, &self.frontier.hosts) {
+                            self.frontier.enqueue(link, queued.depth + 1);
+                        }
+                    }
+                }
+            }
+
+            self.ready.push_back(page);
+        }
+    }
+}
+
+impl Iterator for Crawler<'_> {
+    type Item = Result<Page>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(page) = self.ready.pop_front() {
+                return Some(page);
+            }
+
+            let limit_reached = self
+                .max_pages
+                .is_some_and(|max_pages| self.frontier.crawled >= max_pages);
+            if self.frontier.is_empty() || limit_reached {
+                return None;
+            }
+
+            self.crawl_batch();
+        }
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    Url::parse(url).map_err(|error| Error::UrlParseError {
+        url: url.to_owned(),
+        reason: error.to_string(),
+    })
+}
+
+/// Returns the normalised form of `url` used to detect duplicates: without fragment and with sorted query parameters.
+/// Scheme and host are lowercased and default ports removed by [`Url`] already.
+pub(crate) fn normalize_url(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_fragment(None);
+
+    let mut pairs = url
+        .query_pairs()
+        .into_owned()
+        .collect::<Vec<(String, String)>>();
+    if pairs.is_empty() {
+        url.set_query(None);
+    } else {
+        pairs.sort();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+
+    url
+}
+
+/// Returns the absolute urls of all links on the html `page`, resolved against `<base href>`, if any.
+fn links(page: &Page) -> Vec<Url> {
+    let is_html = page
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_none_or(|content_type| content_type.contains("html"));
+    if !is_html {
+        return Vec::new();
+    }
+
+    let base = page.base_url();
+    page.html()
+        .select(&Selector::parse("a[href], area[href]").unwrap())
+        .filter(|link| {
+            !link.value().attr("rel").is_some_and(|rel| {
+                rel.split_whitespace()
+                    .any(|rel| rel.eq_ignore_ascii_case("nofollow"))
+            })
+        })
+        .filter_map(|link| base.join(link.value().attr("href").unwrap().trim()).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_url, Crawler, Error, Frontier};
+    use crate::{
+        browser::{self, Browser},
+        mock::{self, MockTransport},
+        rate_limit::RateLimit,
+        transport::Response,
+    };
+    use reqwest::{StatusCode, Url};
+    use std::time::{Duration, Instant};
+
+    fn browser() -> Browser {
+        let transport = MockTransport::from_fn(|request| {
+            let links = match request.url().path() {
+                "/" => "<a href='/a?y=2&x=1#top'>a</a><a href='/b'>b</a><a href='https://other.test/'>o</a>",
+                "/a" => "<a href='/a?x=1&y=2'>self</a><a href='/c' rel='nofollow'>c</a><a href='/d.pdf'>d</a>",
+                "/b" => "<base href='/docs/'><a href='e'>e</a><a href='/b'>b</a>",
+                "/docs/e" => "<a href='/f'>f</a>",
+                _ => "",
+            };
+            mock::html(links)
+        });
+
+        Browser::builder().transport(transport).finish().unwrap()
+    }
+
+    fn paths(crawler: Crawler<'_>) -> Vec<String> {
+        crawler
+            .map(|page| page.unwrap().url().path().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn crawl_site_within_scope() {
+        let browser = browser();
+
+        let crawler = Crawler::builder(&browser)
+            .seed("http://site.test/")
+            .deny(r"\.pdf$")
+            .finish()
+            .unwrap();
+        assert_eq!(paths(crawler), ["/", "/a", "/b", "/docs/e", "/f"]);
+
+        let crawler = Crawler::builder(&browser)
+            .seed("http://site.test/")
+            .max_depth(1)
+            .concurrency(1)
+            .finish()
+            .unwrap();
+        let urls: Vec<String> = crawler
+            .map(|page| page.unwrap().url().to_string())
+            .collect();
+        // duplicates are detected on the normalised url, but the url is fetched as linked
+        assert_eq!(
+            urls,
+            [
+                "http://site.test/",
+                "http://site.test/a?y=2&x=1",
+                "http://site.test/b"
+            ]
+        );
+
+        let crawler = Crawler::builder(&browser)
+            .seed("http://site.test/b")
+            .path_prefix("/docs/")
+            .finish()
+            .unwrap();
+        assert_eq!(paths(crawler), ["/b", "/docs/e"]);
+
+        let crawler = Crawler::builder(&browser)
+            .seed("http://site.test/")
+            .max_pages(3)
+            .finish()
+            .unwrap();
+        assert_eq!(paths(crawler).len(), 3);
+
+        assert!(Crawler::builder(&browser).deny("(").finish().is_err());
+    }
+
+    #[test]
+    fn load_pages_like_browser() {
+        let transport = MockTransport::from_fn(|request| match request.url().path() {
+            "/" => mock::html("<a href='/a'>a</a><a href='/missing'>m</a>"),
+            "/a" => mock::html(""),
+            _ => Response::new(StatusCode::NOT_FOUND),
+        });
+        let browser = Browser::builder()
+            .transport(transport)
+            .error_on_status(true)
+            .rate_limit(RateLimit::per_second(20.0))
+            .finish()
+            .unwrap();
+
+        let start = Instant::now();
+        let results: Vec<_> = Crawler::builder(&browser)
+            .seed("http://site.test/")
+            .finish()
+            .unwrap()
+            .map(|page| page.map(|page| page.url().path().to_owned()))
+            .collect();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_deref().unwrap(), "/");
+        assert_eq!(results[1].as_deref().unwrap(), "/a");
+        assert!(matches!(
+            &results[2],
+            Err(Error::BrowserError { source, .. })
+                if matches!(**source, browser::Error::HttpStatusError { .. })
+        ));
+        // the browser's rate limit spaces the requests of the crawler
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[test]
+    fn resume_from_saved_frontier() {
+        let browser = browser();
+        let file = tempfile::NamedTempFile::new().unwrap();
+
+        let mut crawler = Crawler::builder(&browser)
+            .seed("http://site.test/")
+            .max_pages(2)
+            .finish()
+            .unwrap();
+        assert_eq!(crawler.by_ref().count(), 2);
+        crawler.frontier().save(file.path()).unwrap();
+
+        let frontier = Frontier::load(file.path()).unwrap();
+        assert_eq!(frontier.crawled(), 2);
+        let crawler = Crawler::builder(&browser)
+            .seed("http://site.test/")
+            .resume(frontier)
+            .deny(r"\.pdf$")
+            .finish()
+            .unwrap();
+        assert_eq!(paths(crawler), ["/b", "/docs/e", "/f"]);
+    }
+
+    #[test]
+    fn normalize() {
+        let url = Url::parse("HTTP://Example.COM:80/path?b=2&a=1#frag").unwrap();
+        assert_eq!(
+            normalize_url(&url).as_str(),
+            "http://example.com/path?a=1&b=2"
+        );
+
+        let url = Url::parse("https://example.com/?").unwrap();
+        assert_eq!(normalize_url(&url).as_str(), "https://example.com/");
+    }
+}
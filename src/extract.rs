@@ -0,0 +1,755 @@
+//! Module containing the [`Schema`][Schema] struct for declarative data extraction.
+//!
+//! A [`Schema`][Schema] maps field names to [`Field`][Field] specs, each describing which element to select (via CSS
+//! selector), what to read from it (text, inner html or an attribute), how to post-process the value
+//! ([`Transform`][Transform]) and whether the field is a list, optional or a nested object. Schemas can be built in
+//! Rust or loaded from JSON (and YAML with the `yaml` feature). Extracting a schema from a [`Page`][Page] yields a
+//! [`serde_json::Value`] or any type implementing [`serde::Deserialize`].
+//!
+//! ```no_run
+//! # let page: Option<no_browser::page::Page> = None;
+//! # let page = page.unwrap();
+//! use no_browser::extract::{Field, Schema, Transform};
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Product {
+//!     name: String,
+//!     price: f64,
+//!     tags: Vec<String>,
+//! }
+//!
+//! let schema = Schema::new()
+//!     .field("name", Field::text("h1.product-name").transform(Transform::Trim))
+//!     .field("price", Field::attr("[itemprop=price]", "content").transform(Transform::Number))
+//!     .field("tags", Field::text("ul.tags > li").list());
+//!
+//! let product: Product = schema.extract_into(&page)?;
+//! # Ok::<(), no_browser::extract::Error>(())
+//! ```
+//!
+//! The same schema in JSON:
+//!
+//! ```json
+//! {
+//!   "fields": {
+//!     "name": { "selector": "h1.product-name", "transforms": ["trim"] },
+//!     "price": { "selector": "[itemprop=price]", "attr": "content", "transforms": ["number"] },
+//!     "tags": { "selector": "ul.tags > li", "list": true }
+//!   }
+//! }
+//! ```
+
+use crate::page::Page;
+use reqwest::Url;
+use scraper::{ElementRef, Selector};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use std::{collections::BTreeMap, fmt};
+use thiserror::Error;
+
+/// An error occurred while loading a schema or extracting data with it.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The schema could not be parsed from JSON or YAML.
+    #[error("Failed to parse extraction schema, reason: {reason}")]
+    SchemaParseError {
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+
+    /// The CSS selector of a field could not be parsed.
+    #[error("Field '{path}': failed to parse CSS selector '{selector}', reason: {reason}")]
+    CssSelectorParseError {
+        /// The path of the failing field, e.g. `items[2].price`.
+        path: String,
+        /// The given `selector` that could not be parsed.
+        selector: String,
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+
+    /// The regular expression of a [`Transform::Regex`][Transform::Regex] could not be parsed.
+    #[error("Failed to parse regular expression '{pattern}', reason: {reason}")]
+    RegexParseError {
+        /// The given `pattern` that could not be parsed.
+        pattern: String,
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+
+    /// The CSS selector of a mandatory field matched nothing.
+    #[error("Field '{path}': CSS selector '{selector}' matched no elements.")]
+    MissingElementError {
+        /// The path of the failing field, e.g. `items[2].price`.
+        path: String,
+        /// The given `selector` that had no matches.
+        selector: String,
+    },
+
+    /// The selected element of a mandatory field lacks the requested attribute.
+    #[error("Field '{path}': element has no attribute '{attr}'.")]
+    MissingAttributeError {
+        /// The path of the failing field, e.g. `items[2].price`.
+        path: String,
+        /// The missing attribute.
+        attr: String,
+    },
+
+    /// A transform could not be applied to the extracted value.
+    #[error(
+        "Field '{path}': failed to apply transform {transform:?} to {value}, reason: {reason}"
+    )]
+    TransformError {
+        /// The path of the failing field, e.g. `items[2].price`.
+        path: String,
+        /// The failing transform.
+        transform: Transform,
+        /// The value the transform was applied to, in JSON notation.
+        value: String,
+        /// The `reason` the transform failed.
+        reason: String,
+    },
+
+    /// The extracted data could not be deserialized into the requested type.
+    #[error("Field '{path}': failed to deserialize extracted data, reason: {source}")]
+    DeserializeError {
+        /// The path of the failing field, e.g. `items[2].price`.
+        path: String,
+        /// The underlying error.
+        #[source]
+        source: serde_json::Error,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::extract::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A post-processing step applied to an extracted value. Transforms are applied in order; string transforms fail on
+/// values already converted to numbers or booleans.
+///
+/// In JSON or YAML, transforms without arguments are given by their snake case name (e.g. `"trim"`), transforms with
+/// arguments as single-entry map (e.g. `{ "regex": "(\\d+)" }`).
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Transform {
+    /// Removes leading and trailing whitespace.
+    Trim,
+    /// Removes leading and trailing whitespace and collapses inner whitespace into single spaces.
+    NormalizeWhitespace,
+    /// Converts the string to lower case.
+    Lowercase,
+    /// Converts the string to upper case.
+    Uppercase,
+    /// Parses the string as integer, ignoring surrounding whitespace.
+    Integer,
+    /// Parses the string as floating point number, ignoring surrounding whitespace.
+    Number,
+    /// Parses `true`/`false` (case-insensitive), ignoring surrounding whitespace.
+    Boolean,
+    /// Resolves the string as url relative to the url of the page.
+    AbsoluteUrl,
+    /// Replaces the string with the first capture group (or the whole match, if the pattern has no groups) of the
+    /// given regular expression. Fails if the expression doesn't match.
+    Regex(Pattern),
+    /// Replaces all occurrences of the first string with the second.
+    Replace(String, String),
+}
+
+/// The regular expression of a [`Transform::Regex`][Transform::Regex], compiled once when it is created or the
+/// schema is loaded.
+#[derive(Clone, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Pattern(regex::Regex);
+
+impl Pattern {
+    /// Compiles the regular expression `pattern`.
+    pub fn new(pattern: &str) -> Result<Self> {
+        regex::Regex::new(pattern)
+            .map(Self)
+            .map_err(|error| Error::RegexParseError {
+                pattern: pattern.to_owned(),
+                reason: error.to_string(),
+            })
+    }
+
+    /// Returns the source of the regular expression.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl fmt::Debug for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for Pattern {}
+
+impl TryFrom<String> for Pattern {
+    type Error = Error;
+
+    fn try_from(pattern: String) -> Result<Self> {
+        Self::new(&pattern)
+    }
+}
+
+impl From<Pattern> for String {
+    fn from(pattern: Pattern) -> Self {
+        pattern.as_str().to_owned()
+    }
+}
+
+/// Describes how to extract a single field. Use the constructors `text()`, `html()`, `attr()`, `object()` and
+/// `element()` and refine the field with `list()`, `optional()` and `transform()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Field {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selector: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    attr: Option<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    html: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    transforms: Vec<Transform>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    optional: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    list: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fields: Option<BTreeMap<String, Field>>,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+impl Field {
+    /// Extracts the text content of the first element matching `selector`.
+    pub fn text(selector: &str) -> Self {
+        Self {
+            selector: Some(selector.to_owned()),
+            ..Self::default()
+        }
+    }
+
+    /// Extracts the inner html of the first element matching `selector`.
+    pub fn html(selector: &str) -> Self {
+        Self {
+            selector: Some(selector.to_owned()),
+            html: true,
+            ..Self::default()
+        }
+    }
+
+    /// Extracts the attribute `attr` of the first element matching `selector`.
+    pub fn attr(selector: &str, attr: &str) -> Self {
+        Self {
+            selector: Some(selector.to_owned()),
+            attr: Some(attr.to_owned()),
+            ..Self::default()
+        }
+    }
+
+    /// Extracts a nested object described by `schema` from the first element matching `selector`. The selectors of
+    /// the nested fields are evaluated relative to that element.
+    pub fn object(selector: &str, schema: Schema) -> Self {
+        Self {
+            selector: Some(selector.to_owned()),
+            fields: Some(schema.fields),
+            ..Self::default()
+        }
+    }
+
+    /// Extracts the text content of the current element, i.e. the element selected by the enclosing object field.
+    /// Use `with_attr()` to read an attribute instead.
+    pub fn element() -> Self {
+        Self::default()
+    }
+
+    /// Reads the attribute `attr` instead of the text content or inner html.
+    pub fn with_attr(mut self, attr: &str) -> Self {
+        self.attr = Some(attr.to_owned());
+        self.html = false;
+        self
+    }
+
+    /// Extracts a list with one value per matching element instead of a single value from the first match.
+    pub const fn list(mut self) -> Self {
+        self.list = true;
+        self
+    }
+
+    /// Extracts `null` instead of failing if no element matches or the attribute is missing.
+    pub const fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+
+    /// Appends a [`Transform`][Transform] to post-process the extracted value.
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.transforms.push(transform);
+        self
+    }
+}
+
+/// A declarative description of the data to extract from a [`Page`][Page]. See the
+/// [module documentation][crate::extract] for an example.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schema {
+    #[serde(default)]
+    fields: BTreeMap<String, Field>,
+}
+
+impl Schema {
+    /// Returns an empty [`Schema`][Schema]. Use `field()` to add fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a field `name` described by `field`.
+    pub fn field(mut self, name: &str, field: Field) -> Self {
+        self.fields.insert(name.to_owned(), field);
+        self
+    }
+
+    /// Loads a [`Schema`][Schema] from its JSON representation. Fails with a `SchemaParseError` if the JSON is not a
+    /// valid schema, e.g. if a regular expression can't be parsed or a field sets both `html` and `attr`.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str::<Self>(json)
+            .map_err(|error| Error::SchemaParseError {
+                reason: error.to_string(),
+            })?
+            .validate()
+    }
+
+    /// Loads a [`Schema`][Schema] from its YAML representation. Fails like `from_json()`.
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        serde_norway::from_str::<Self>(yaml)
+            .map_err(|error| Error::SchemaParseError {
+                reason: error.to_string(),
+            })?
+            .validate()
+    }
+
+    /// Checks the combinations of field options serde can't, i.e. that no field sets both `html` and `attr`.
+    fn validate(self) -> Result<Self> {
+        fn check(fields: &BTreeMap<String, Field>, path: &str) -> Result<()> {
+            for (name, field) in fields {
+                let path = if path.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{path}.{name}")
+                };
+                if field.html && field.attr.is_some() {
+                    return Err(Error::SchemaParseError {
+                        reason: format!("field '{path}' sets both 'html' and 'attr'"),
+                    });
+                }
+                if let Some(fields) = &field.fields {
+                    check(fields, &path)?;
+                }
+            }
+            Ok(())
+        }
+
+        check(&self.fields, "")?;
+        Ok(self)
+    }
+
+    /// Extracts the data described by this schema from the given `page` as JSON object.
+    pub fn extract(&self, page: &Page) -> Result<Value> {
+        let extractor = Extractor { url: page.url() };
+        let root = page.html().root_element();
+
+        Ok(Value::Object(extractor.fields(&self.fields, root, "")?))
+    }
+
+    /// Extracts the data described by this schema from the given `page` and deserializes it into `T`.
+    pub fn extract_into<T: DeserializeOwned>(&self, page: &Page) -> Result<T> {
+        let value = self.extract(page)?;
+
+        serde_path_to_error::deserialize(value).map_err(|error| Error::DeserializeError {
+            path: error.path().to_string(),
+            source: error.into_inner(),
+        })
+    }
+}
+
+struct Extractor<'u> {
+    url: &'u Url,
+}
+
+impl Extractor<'_> {
+    fn fields(
+        &self,
+        fields: &BTreeMap<String, Field>,
+        scope: ElementRef,
+        path: &str,
+    ) -> Result<Map<String, Value>> {
+        let mut object = Map::new();
+
+        for (name, field) in fields {
+            let path = if path.is_empty() {
+                name.clone()
+            } else {
+                format!("{path}.{name}")
+            };
+            object.insert(name.clone(), self.field(field, scope, &path)?);
+        }
+
+        Ok(object)
+    }
+
+    fn field(&self, field: &Field, scope: ElementRef, path: &str) -> Result<Value> {
+        let elements = match &field.selector {
+            Some(selectors) => {
+                let selector =
+                    Selector::parse(selectors).map_err(|error| Error::CssSelectorParseError {
+                        path: path.to_owned(),
+                        selector: selectors.to_owned(),
+                        reason: format!("{error:?}"),
+                    })?;
+                scope.select(&selector).collect()
+            }
+            None => vec![scope],
+        };
+
+        if field.list {
+            let values = elements
+                .into_iter()
+                .enumerate()
+                .map(|(i, element)| self.value(field, element, &format!("{path}[{i}]")))
+                .collect::<Result<Vec<Value>>>()?;
+            return Ok(Value::Array(
+                values.into_iter().filter(|v| !v.is_null()).collect(),
+            ));
+        }
+
+        match elements.into_iter().next() {
+            Some(element) => self.value(field, element, path),
+            None if field.optional => Ok(Value::Null),
+            None => Err(Error::MissingElementError {
+                path: path.to_owned(),
+                selector: field.selector.clone().unwrap_or_default(),
+            }),
+        }
+    }
+
+    fn value(&self, field: &Field, element: ElementRef, path: &str) -> Result<Value> {
+        if let Some(fields) = &field.fields {
+            return Ok(Value::Object(self.fields(fields, element, path)?));
+        }
+
+        let raw = if let Some(attr) = &field.attr {
+            match element.value().attr(attr) {
+                Some(value) => value.to_owned(),
+                None if field.optional => return Ok(Value::Null),
+                None => {
+                    return Err(Error::MissingAttributeError {
+                        path: path.to_owned(),
+                        attr: attr.clone(),
+                    })
+                }
+            }
+        } else if field.html {
+            element.inner_html()
+        } else {
+            element.text().collect()
+        };
+
+        let mut value = Value::String(raw);
+        for transform in &field.transforms {
+            value = self
+                .transform(transform, &value)
+                .map_err(|reason| Error::TransformError {
+                    path: path.to_owned(),
+                    transform: transform.clone(),
+                    value: value.to_string(),
+                    reason,
+                })?;
+        }
+
+        Ok(value)
+    }
+
+    fn transform(
+        &self,
+        transform: &Transform,
+        value: &Value,
+    ) -> std::result::Result<Value, String> {
+        let s = value.as_str().ok_or("value is not a string")?;
+
+        Ok(match transform {
+            Transform::Trim => Value::String(s.trim().to_owned()),
+            Transform::NormalizeWhitespace => {
+                Value::String(s.split_whitespace().collect::<Vec<&str>>().join(" "))
+            }
+            Transform::Lowercase => Value::String(s.to_lowercase()),
+            Transform::Uppercase => Value::String(s.to_uppercase()),
+            Transform::Integer => {
+                let i: i64 = s.trim().parse().map_err(|error| format!("{error}"))?;
+                Value::Number(i.into())
+            }
+            Transform::Number => {
+                let f: f64 = s.trim().parse().map_err(|error| format!("{error}"))?;
+                Value::Number(Number::from_f64(f).ok_or("number is not finite")?)
+            }
+            Transform::Boolean => match s.trim().to_lowercase().as_str() {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => return Err("expected 'true' or 'false'".to_owned()),
+            },
+            Transform::AbsoluteUrl => {
+                let url = self
+                    .url
+                    .join(s.trim())
+                    .map_err(|error| format!("{error}"))?;
+                Value::String(url.to_string())
+            }
+            Transform::Regex(Pattern(regex)) => {
+                let captures = regex.captures(s).ok_or("regular expression didn't match")?;
+                let m = captures.get(1).or_else(|| captures.get(0)).unwrap();
+                Value::String(m.as_str().to_owned())
+            }
+            Transform::Replace(from, to) => Value::String(s.replace(from.as_str(), to)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Field, Pattern, Schema, Transform};
+    use crate::page::Page;
+    use reqwest::{header::HeaderMap, Method, StatusCode, Url};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    static PAGE_001: &str = r#"
+        <html>
+            <body>
+                <h1> Rust   Book </h1>
+                <div class="product" data-sku="A-1">
+                    <span class="name">Ferris plush</span>
+                    <span class="price">EUR 12.50</span>
+                    <a href="/ferris">details</a>
+                </div>
+                <div class="product" data-sku="B-2">
+                    <span class="name">Crab mug</span>
+                    <span class="price">EUR 8</span>
+                </div>
+            </body>
+        </html>
+    "#;
+
+    fn page() -> Page {
+        let url = Url::parse("https://shop.example/catalog/").unwrap();
+        Page::build(
+            Method::GET,
+            url,
+            StatusCode::OK,
+            HeaderMap::new(),
+            PAGE_001.to_owned(),
+        )
+    }
+
+    fn schema() -> Schema {
+        let product = Schema::new()
+            .field("sku", Field::element().with_attr("data-sku"))
+            .field("name", Field::text(".name"))
+            .field(
+                "price",
+                Field::text(".price")
+                    .transform(Transform::Regex(Pattern::new(r"(\d+(\.\d+)?)").unwrap()))
+                    .transform(Transform::Number),
+            )
+            .field(
+                "link",
+                Field::attr("a", "href")
+                    .optional()
+                    .transform(Transform::AbsoluteUrl),
+            );
+
+        Schema::new()
+            .field(
+                "title",
+                Field::text("h1").transform(Transform::NormalizeWhitespace),
+            )
+            .field("products", Field::object("div.product", product).list())
+    }
+
+    #[test]
+    fn extract_json() {
+        let value = schema().extract(&page()).unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "title": "Rust Book",
+                "products": [
+                    { "sku": "A-1", "name": "Ferris plush", "price": 12.5, "link": "https://shop.example/ferris" },
+                    { "sku": "B-2", "name": "Crab mug", "price": 8.0, "link": null },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn extract_into_struct() {
+        #[derive(Debug, Deserialize)]
+        struct Catalog {
+            title: String,
+            products: Vec<Product>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Product {
+            name: String,
+            price: f64,
+            link: Option<String>,
+        }
+
+        let catalog: Catalog = schema().extract_into(&page()).unwrap();
+        assert_eq!(catalog.title, "Rust Book");
+        assert_eq!(catalog.products.len(), 2);
+        assert_eq!(catalog.products[1].name, "Crab mug");
+        assert_eq!(catalog.products[1].price, 8.0);
+        assert_eq!(catalog.products[1].link, None);
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Wrong {
+            products: Vec<WrongProduct>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct WrongProduct {
+            name: u32,
+        }
+
+        match schema().extract_into::<Wrong>(&page()) {
+            Err(Error::DeserializeError { path, .. }) => assert_eq!(path, "products[0].name"),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn load_json_schema() {
+        let json = r#"{
+            "fields": {
+                "title": { "selector": "h1", "transforms": ["trim", "uppercase"] },
+                "skus": { "selector": "div.product", "attr": "data-sku", "list": true },
+                "missing": { "selector": "table", "optional": true },
+                "second": {
+                    "selector": "div.product:nth-of-type(2)",
+                    "fields": { "name": { "selector": ".name", "transforms": [{ "replace": ["Crab", "Ferris"] }] } }
+                }
+            }
+        }"#;
+        let value = Schema::from_json(json).unwrap().extract(&page()).unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "title": "RUST   BOOK",
+                "skus": ["A-1", "B-2"],
+                "missing": null,
+                "second": { "name": "Ferris mug" }
+            })
+        );
+
+        assert!(matches!(
+            Schema::from_json(r#"{ "fields": { "x": { "selektor": "h1" } } }"#),
+            Err(Error::SchemaParseError { .. })
+        ));
+    }
+
+    #[test]
+    fn reject_invalid_schema() {
+        let json = r#"{ "fields": { "price": { "selector": ".price", "transforms": [{ "regex": "(\\d+" }] } } }"#;
+        match Schema::from_json(json) {
+            Err(Error::SchemaParseError { reason }) => {
+                assert!(reason.contains("(\\d+"), "{reason}")
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert!(matches!(
+            Pattern::new("(x"),
+            Err(Error::RegexParseError { pattern, .. }) if pattern == "(x"
+        ));
+
+        let json = r#"{ "fields": { "item": { "selector": "div", "fields": { "x": { "selector": "a", "html": true, "attr": "href" } } } } }"#;
+        match Schema::from_json(json) {
+            Err(Error::SchemaParseError { reason }) => {
+                assert!(reason.contains("'item.x'"), "{reason}")
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+        assert_eq!(Field::html("a").with_attr("href"), Field::attr("a", "href"));
+
+        let json = r#"{ "fields": { "price": { "selector": ".price", "transforms": [{ "regex": "\\d+" }] } } }"#;
+        let schema = Schema::from_json(json).unwrap();
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap()["fields"]["price"]["transforms"],
+            json!([{ "regex": "\\d+" }])
+        );
+    }
+
+    #[test]
+    fn report_failing_field() {
+        let schema = Schema::new().field(
+            "products",
+            Field::object(
+                "div.product",
+                Schema::new().field("link", Field::attr("a", "href")),
+            )
+            .list(),
+        );
+        match schema.extract(&page()) {
+            Err(Error::MissingElementError { path, .. }) => assert_eq!(path, "products[1].link"),
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        let schema = Schema::new().field("title", Field::text("h1").transform(Transform::Integer));
+        match schema.extract(&page()) {
+            Err(Error::TransformError {
+                path, transform, ..
+            }) => {
+                assert_eq!(path, "title");
+                assert_eq!(transform, Transform::Integer);
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn load_yaml_schema() {
+        let yaml = r#"
+            fields:
+              title:
+                selector: h1
+                transforms: [normalize_whitespace, lowercase]
+              names:
+                selector: div.product .name
+                list: true
+        "#;
+        let value = Schema::from_yaml(yaml).unwrap().extract(&page()).unwrap();
+
+        assert_eq!(
+            value,
+            json!({ "title": "rust book", "names": ["Ferris plush", "Crab mug"] })
+        );
+    }
+}
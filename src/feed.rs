@@ -0,0 +1,461 @@
+//! Module containing the [`Feed`][Feed] parser for [RSS 2.0](https://www.rssboard.org/rss-specification),
+//! RSS 1.0 (RDF) and [Atom](https://www.rfc-editor.org/rfc/rfc4287) feeds.
+//!
+//! Both formats are mapped onto the same [`Feed`][Feed] and [`FeedEntry`][FeedEntry] types; `kind()` tells them
+//! apart. Links are resolved against the feed url (and `xml:base` is ignored). Dates are returned as found in the
+//! feed, i.e. RFC 2822 for RSS and RFC 3339 for Atom.
+//!
+//! ```
+//! use no_browser::{
+//!     feed::{Feed, FeedKind},
+//!     mock::{self, MockTransport},
+//!     Browser,
+//! };
+//! use reqwest::{Method, Url};
+//!
+//! let transport = MockTransport::new().respond(
+//!     Method::GET,
+//!     "/feed.xml",
+//!     mock::html(
+//!         r#"<feed xmlns="http://www.w3.org/2005/Atom">
+//!              <title>Blog</title>
+//!              <entry>
+//!                <id>urn:post:1</id>
+//!                <title>Hello</title>
+//!                <link href="/posts/hello"/>
+//!                <updated>2024-03-01T12:00:00Z</updated>
+//!              </entry>
+//!            </feed>"#,
+//!     ),
+//! );
+//! let browser = Browser::builder().transport(transport).finish()?;
+//!
+//! let feed = Feed::fetch(&browser, &Url::parse("https://blog.test/feed.xml")?)?;
+//! assert_eq!(feed.kind(), FeedKind::Atom);
+//! assert_eq!(feed.title(), Some("Blog"));
+//! assert_eq!(feed.entries()[0].link().unwrap().as_str(), "https://blog.test/posts/hello");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{
+    browser::{self, Browser},
+    xml::{child, child_text, elements, text},
+};
+use reqwest::{StatusCode, Url};
+use roxmltree::{Document, Node};
+use thiserror::Error;
+
+/// An error occurred while fetching or parsing a feed.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The feed could not be loaded by the [`Browser`][Browser].
+    #[error("Failed to fetch '{url}': {source}")]
+    BrowserError {
+        /// The `url` of the feed.
+        url: String,
+        /// The underlying error.
+        #[source]
+        source: Box<browser::Error>,
+    },
+
+    /// The server answered with a status other than success.
+    #[error("Failed to fetch '{url}', status: {status}")]
+    HttpStatusError {
+        /// The `url` of the feed.
+        url: String,
+        /// The http `status` of the response.
+        status: StatusCode,
+    },
+
+    /// The feed is not well-formed XML.
+    #[error("Failed to parse feed, reason: {reason}")]
+    XmlParseError {
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+
+    /// The XML document is neither an RSS nor an Atom feed.
+    #[error("Expected <rss>, <rdf:RDF> or <feed>, but found <{root}>!")]
+    InvalidFeedError {
+        /// The name of the root element.
+        root: String,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::feed::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The format of a [`Feed`][Feed].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedKind {
+    /// An RSS 2.0 or RSS 1.0 (RDF) feed.
+    Rss,
+    /// An Atom feed.
+    Atom,
+}
+
+/// A parsed RSS or Atom feed. See the [module documentation][crate::feed] for details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Feed {
+    kind: FeedKind,
+    title: Option<String>,
+    link: Option<Url>,
+    description: Option<String>,
+    updated: Option<String>,
+    entries: Vec<FeedEntry>,
+}
+
+/// An item of an RSS feed or an entry of an Atom feed.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct FeedEntry {
+    id: Option<String>,
+    title: Option<String>,
+    link: Option<Url>,
+    summary: Option<String>,
+    content: Option<String>,
+    published: Option<String>,
+    updated: Option<String>,
+    authors: Vec<String>,
+    categories: Vec<String>,
+}
+
+impl Feed {
+    /// Parses a feed from `bytes`. Relative links are resolved against `url`, the location of the feed.
+    pub fn parse(url: &Url, bytes: &[u8]) -> Result<Self> {
+        let text = String::from_utf8_lossy(bytes);
+        let document = Document::parse(&text).map_err(|error| Error::XmlParseError {
+            reason: error.to_string(),
+        })?;
+        let root = document.root_element();
+
+        match root.tag_name().name() {
+            "rss" => {
+                let channel = child(root, "channel");
+                Ok(Self::parse_rss(url, channel, channel))
+            }
+            "RDF" => Ok(Self::parse_rss(url, child(root, "channel"), Some(root))),
+            "feed" => Ok(Self::parse_atom(url, root)),
+            root => Err(Error::InvalidFeedError {
+                root: root.to_owned(),
+            }),
+        }
+    }
+
+    /// Fetches and parses the feed at `url` with `browser`.
+    pub fn fetch(browser: &Browser, url: &Url) -> Result<Self> {
+        let response = browser.fetch(url).map_err(|error| Error::BrowserError {
+            url: url.to_string(),
+            source: Box::new(error),
+        })?;
+        if !response.status().is_success() {
+            return Err(Error::HttpStatusError {
+                url: url.to_string(),
+                status: response.status(),
+            });
+        }
+
+        Self::parse(url, response.body())
+    }
+
+    /// Returns the format of the feed.
+    pub const fn kind(&self) -> FeedKind {
+        self.kind
+    }
+
+    /// Returns the title of the feed, if any.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the link to the website of the feed, if any.
+    pub const fn link(&self) -> Option<&Url> {
+        self.link.as_ref()
+    }
+
+    /// Returns the description (RSS) or subtitle (Atom) of the feed, if any.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the date of the last update (`lastBuildDate` for RSS, `updated` for Atom), if any.
+    pub fn updated(&self) -> Option<&str> {
+        self.updated.as_deref()
+    }
+
+    /// Returns the entries of the feed in document order.
+    pub fn entries(&self) -> &[FeedEntry] {
+        &self.entries
+    }
+
+    /// Parses an RSS feed. For RSS 2.0, items are children of the `channel`; for RSS 1.0 they are siblings of it,
+    /// hence the separate `items` parent.
+    fn parse_rss(url: &Url, channel: Option<Node<'_, '_>>, items: Option<Node<'_, '_>>) -> Self {
+        let entries = items
+            .into_iter()
+            .flat_map(|items| elements(items, "item"))
+            .map(|item| FeedEntry {
+                id: child_text(item, "guid").or_else(|| attribute(item, "about")),
+                title: child_text(item, "title"),
+                link: child_text(item, "link").and_then(|link| url.join(&link).ok()),
+                summary: child_text(item, "description"),
+                content: child_text(item, "encoded"),
+                published: child_text(item, "pubDate").or_else(|| child_text(item, "date")),
+                updated: None,
+                authors: elements(item, "author")
+                    .chain(elements(item, "creator"))
+                    .filter_map(text)
+                    .collect(),
+                categories: elements(item, "category")
+                    .chain(elements(item, "subject"))
+                    .filter_map(text)
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            kind: FeedKind::Rss,
+            title: channel.and_then(|channel| child_text(channel, "title")),
+            link: channel
+                .and_then(|channel| child_text(channel, "link"))
+                .and_then(|link| url.join(&link).ok()),
+            description: channel.and_then(|channel| child_text(channel, "description")),
+            updated: channel.and_then(|channel| {
+                child_text(channel, "lastBuildDate").or_else(|| child_text(channel, "date"))
+            }),
+            entries,
+        }
+    }
+
+    fn parse_atom(url: &Url, feed: Node<'_, '_>) -> Self {
+        let entries = elements(feed, "entry")
+            .map(|entry| FeedEntry {
+                id: child_text(entry, "id"),
+                title: child_text(entry, "title"),
+                link: atom_link(url, entry),
+                summary: child_text(entry, "summary"),
+                content: child_text(entry, "content"),
+                published: child_text(entry, "published"),
+                updated: child_text(entry, "updated"),
+                authors: elements(entry, "author")
+                    .filter_map(|author| child_text(author, "name"))
+                    .collect(),
+                categories: elements(entry, "category")
+                    .filter_map(|category| attribute(category, "term"))
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            kind: FeedKind::Atom,
+            title: child_text(feed, "title"),
+            link: atom_link(url, feed),
+            description: child_text(feed, "subtitle"),
+            updated: child_text(feed, "updated"),
+            entries,
+        }
+    }
+}
+
+impl FeedEntry {
+    /// Returns the unique id (`guid` for RSS, `id` for Atom), if any.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the title, if any.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the link to the full entry, if any. For Atom, this is the `alternate` link.
+    pub const fn link(&self) -> Option<&Url> {
+        self.link.as_ref()
+    }
+
+    /// Returns the summary (`description` for RSS, `summary` for Atom), if any.
+    pub fn summary(&self) -> Option<&str> {
+        self.summary.as_deref()
+    }
+
+    /// Returns the full content (`content:encoded` for RSS, `content` for Atom), if any.
+    pub fn content(&self) -> Option<&str> {
+        self.content.as_deref()
+    }
+
+    /// Returns the date of publication (`pubDate` for RSS, `published` for Atom), if any.
+    pub fn published(&self) -> Option<&str> {
+        self.published.as_deref()
+    }
+
+    /// Returns the date of the last update (Atom only), if any.
+    pub fn updated(&self) -> Option<&str> {
+        self.updated.as_deref()
+    }
+
+    /// Returns the names of the authors.
+    pub fn authors(&self) -> &[String] {
+        &self.authors
+    }
+
+    /// Returns the categories (`category` for RSS, the `term` of `category` for Atom).
+    pub fn categories(&self) -> &[String] {
+        &self.categories
+    }
+}
+
+/// Returns the `alternate` link of an Atom feed or entry, i.e. the first link without `rel` or with `rel="alternate"`.
+fn atom_link(url: &Url, node: Node<'_, '_>) -> Option<Url> {
+    elements(node, "link")
+        .find(|link| link.attribute("rel").is_none_or(|rel| rel == "alternate"))
+        .and_then(|link| link.attribute("href"))
+        .and_then(|href| url.join(href).ok())
+}
+
+/// Returns the value of the attribute with the local `name`, regardless of its namespace (e.g. `rdf:about`).
+fn attribute(node: Node<'_, '_>, name: &str) -> Option<String> {
+    node.attributes()
+        .find(|attribute| attribute.name() == name)
+        .map(|attribute| attribute.value().to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Feed, FeedKind};
+    use crate::{
+        browser::Browser,
+        mock::{self, MockTransport},
+    };
+    use reqwest::{Method, Url};
+
+    static RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/"
+     xmlns:atom="http://www.w3.org/2005/Atom">
+  <channel>
+    <title>News</title>
+    <atom:link href="https://news.test/feed.xml" rel="self" type="application/rss+xml"/>
+    <link>https://news.test/</link>
+    <description>All the news</description>
+    <lastBuildDate>Fri, 01 Mar 2024 12:00:00 GMT</lastBuildDate>
+    <item>
+      <title>First &amp; foremost</title>
+      <atom:link href="/articles/1/amp" rel="amphtml"/>
+      <link>/articles/1</link>
+      <guid isPermaLink="false">article-1</guid>
+      <description><![CDATA[<p>Teaser</p>]]></description>
+      <content:encoded><![CDATA[<p>Full text</p>]]></content:encoded>
+      <pubDate>Thu, 29 Feb 2024 08:00:00 GMT</pubDate>
+      <author>jane@news.test (Jane)</author>
+      <category>Politics</category>
+      <category>Local</category>
+    </item>
+    <item>
+      <title>Second</title>
+    </item>
+  </channel>
+</rss>"#;
+
+    static RDF: &str = r#"<?xml version="1.0"?>
+<rdf:RDF xmlns:rdf="http://www.w3.org/1999/02/22-rdf-syntax-ns#" xmlns="http://purl.org/rss/1.0/"
+         xmlns:dc="http://purl.org/dc/elements/1.1/">
+  <channel rdf:about="https://old.test/">
+    <title>Old school</title>
+    <link>https://old.test/</link>
+  </channel>
+  <item rdf:about="https://old.test/1">
+    <title>Item</title>
+    <link>https://old.test/1</link>
+    <dc:creator>Joe</dc:creator>
+    <dc:date>2024-03-01T12:00:00Z</dc:date>
+  </item>
+</rdf:RDF>"#;
+
+    static ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Blog</title>
+  <subtitle>Thoughts</subtitle>
+  <link rel="self" href="/feed.atom"/>
+  <link href="/"/>
+  <updated>2024-03-01T12:00:00Z</updated>
+  <entry>
+    <id>urn:post:1</id>
+    <title type="html">Hello</title>
+    <link rel="edit" href="/edit/1"/>
+    <link rel="alternate" href="https://blog.test/posts/hello"/>
+    <published>2024-02-28T10:00:00Z</published>
+    <updated>2024-03-01T12:00:00Z</updated>
+    <author><name>Ferris</name></author>
+    <category term="rust"/>
+    <summary>Short</summary>
+    <content type="xhtml"><div xmlns="http://www.w3.org/1999/xhtml"><p>Long</p></div></content>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn parse_feeds() {
+        let url = Url::parse("https://news.test/feed.xml").unwrap();
+
+        let rss = Feed::parse(&url, RSS.as_bytes()).unwrap();
+        assert_eq!(rss.kind(), FeedKind::Rss);
+        assert_eq!(rss.title(), Some("News"));
+        // the preceding <atom:link> elements without text are skipped
+        assert_eq!(rss.link().unwrap().as_str(), "https://news.test/");
+        assert_eq!(rss.description(), Some("All the news"));
+        assert_eq!(rss.updated(), Some("Fri, 01 Mar 2024 12:00:00 GMT"));
+        assert_eq!(rss.entries().len(), 2);
+        let item = &rss.entries()[0];
+        assert_eq!(item.id(), Some("article-1"));
+        assert_eq!(item.title(), Some("First & foremost"));
+        assert_eq!(
+            item.link().unwrap().as_str(),
+            "https://news.test/articles/1"
+        );
+        assert_eq!(item.summary(), Some("<p>Teaser</p>"));
+        assert_eq!(item.content(), Some("<p>Full text</p>"));
+        assert_eq!(item.published(), Some("Thu, 29 Feb 2024 08:00:00 GMT"));
+        assert_eq!(item.authors(), ["jane@news.test (Jane)"]);
+        assert_eq!(item.categories(), ["Politics", "Local"]);
+        assert_eq!(rss.entries()[1].link(), None);
+
+        let rdf = Feed::parse(&url, RDF.as_bytes()).unwrap();
+        assert_eq!(rdf.kind(), FeedKind::Rss);
+        assert_eq!(rdf.title(), Some("Old school"));
+        let item = &rdf.entries()[0];
+        assert_eq!(item.id(), Some("https://old.test/1"));
+        assert_eq!(item.authors(), ["Joe"]);
+        assert_eq!(item.published(), Some("2024-03-01T12:00:00Z"));
+
+        assert!(Feed::parse(&url, b"<html></html>").is_err());
+        assert!(Feed::parse(&url, b"<rss>").is_err());
+    }
+
+    #[test]
+    fn fetch_atom_feed() {
+        let transport = MockTransport::new().respond(Method::GET, "/feed.atom", mock::html(ATOM));
+        let browser = Browser::builder().transport(transport).finish().unwrap();
+
+        let feed = Feed::fetch(
+            &browser,
+            &Url::parse("https://blog.test/feed.atom").unwrap(),
+        )
+        .unwrap();
+        assert_eq!(feed.kind(), FeedKind::Atom);
+        assert_eq!(feed.title(), Some("Blog"));
+        assert_eq!(feed.description(), Some("Thoughts"));
+        assert_eq!(feed.link().unwrap().as_str(), "https://blog.test/");
+        let entry = &feed.entries()[0];
+        assert_eq!(entry.id(), Some("urn:post:1"));
+        assert_eq!(
+            entry.link().unwrap().as_str(),
+            "https://blog.test/posts/hello"
+        );
+        assert_eq!(entry.published(), Some("2024-02-28T10:00:00Z"));
+        assert_eq!(entry.updated(), Some("2024-03-01T12:00:00Z"));
+        assert_eq!(entry.authors(), ["Ferris"]);
+        assert_eq!(entry.categories(), ["rust"]);
+        assert_eq!(entry.summary(), Some("Short"));
+        assert_eq!(entry.content(), Some("Long"));
+
+        let missing = Url::parse("https://blog.test/missing.atom").unwrap();
+        assert!(Feed::fetch(&browser, &missing).is_err());
+    }
+}
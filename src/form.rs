@@ -1,9 +1,18 @@
 //! Module containing the [`Form`][Form] struct.
 
-use crate::input::{Input, InputType};
+use crate::{
+    input::{Input, InputType},
+    query::{self, TextMatch},
+};
+use ego_tree::NodeId;
 use reqwest::{Method, Url};
 use scraper::{ElementRef, Html, Selector};
-use std::str::FromStr;
+use std::{
+    collections::HashSet,
+    fmt::{self, Debug, Display, Formatter},
+    str::FromStr,
+    sync::Arc,
+};
 use thiserror::Error;
 
 /// An error occurred while working with the form.
@@ -17,6 +26,22 @@ pub enum Error {
         /// The type of the input to be fetched.
         input_type: InputType,
     },
+
+    /// No input field found for the given locator, e.g. label text, id, placeholder or CSS selector.
+    #[error("Form doesn't contain input with {locator}!")]
+    InputNotFoundError {
+        /// A description of the locator that matched no input.
+        locator: String,
+    },
+
+    /// The given [CSS selectors](https://developer.mozilla.org/en-US/docs/Web/CSS/CSS_Selectors) could not be parsed.
+    #[error("Failed to parse CSS selector '{selector}', reason: {reason}")]
+    CssSelectorParseError {
+        /// The given `selector` that could not be parsed.
+        selector: String,
+        /// The `reason` given by the parser.
+        reason: String,
+    },
 }
 
 /// Short-hand for `std::result::Result<T, no_browser::form::Error>`.
@@ -26,7 +51,11 @@ pub type Result<T> = std::result::Result<T, Error>;
 ///
 /// It gives access to:
 /// * this forms id (`id()`);
-/// * the individual input fields in this form (`input()`, `input_mut()`);
+/// * the individual input fields in this form identified by type and name (`input()`, `input_mut()`), by `id`
+///   (`input_by_id()`), by label text (`input_by_label()`), by placeholder (`input_by_placeholder()`) or by a CSS
+///   selector scoped to this form (`input_by_selector()`), each with a `_mut` variant;
+/// * all controls (`inputs()`, `inputs_mut()`): `<input>`, `<select>` and `<textarea>` elements in document order,
+///   followed by all buttons in document order;
 ///
 /// See the main docs of [crate `no_browser`][crate] for usage examples.
 #[derive(Debug)]
@@ -36,6 +65,40 @@ pub struct Form {
     action: String,
     id: Option<String>,
     inputs: Vec<Input>,
+    source: Source,
+    /// The node of the form element within the parsed `source`.
+    node: NodeId,
+    /// The node of each input within the parsed `source`, in the order of `inputs`.
+    nodes: Vec<NodeId>,
+}
+
+/// The html source of the page a form was parsed from, shared by all forms of the page. It is parsed again to match
+/// CSS selectors; parsing the same source yields the same [`NodeId`][NodeId]s.
+struct Source(Arc<str>);
+
+impl Debug for Source {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Source({} bytes)", self.0.len())
+    }
+}
+
+/// The different ways to identify an input besides its type and name.
+enum Locator<'a> {
+    Id(&'a str),
+    Label(TextMatch),
+    Placeholder(TextMatch),
+    Selector(&'a str),
+}
+
+impl Display for Locator<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "id '{id}'"),
+            Self::Label(label) => write!(f, "label text {label}"),
+            Self::Placeholder(placeholder) => write!(f, "placeholder text {placeholder}"),
+            Self::Selector(selector) => write!(f, "CSS selector '{selector}'"),
+        }
+    }
 }
 
 pub(crate) struct SubmitFormInfo {
@@ -84,6 +147,126 @@ impl Form {
         })
     }
 
+    /// Returns an iterator over all controls ([`Input`][Input]) within this form: `<input>`, `<select>` and
+    /// `<textarea>` elements in document order, followed by all buttons. When submitting, the value of the chosen
+    /// submit button comes first, followed by the values of the other controls in this order.
+    pub fn inputs(&self) -> impl Iterator<Item = &Input> {
+        self.inputs.iter()
+    }
+
+    /// Returns an iterator over mutable references to all controls ([`Input`][Input]) within this form, in the order
+    /// of `inputs()`.
+    pub fn inputs_mut(&mut self) -> impl Iterator<Item = &mut Input> {
+        self.inputs.iter_mut()
+    }
+
+    /// Returns a reference to the input field ([`Input`][Input]) with the given `id` attribute.
+    pub fn input_by_id(&self, id: &str) -> Result<&Input> {
+        let idx = self.locate(&Locator::Id(id))?;
+        Ok(&self.inputs[idx])
+    }
+
+    /// Returns a mutable reference to the input field ([`Input`][Input]) with the given `id` attribute.
+    pub fn input_by_id_mut(&mut self, id: &str) -> Result<&mut Input> {
+        let idx = self.locate(&Locator::Id(id))?;
+        Ok(&mut self.inputs[idx])
+    }
+
+    /// Returns a reference to the first input field ([`Input`][Input]) associated with the given label text. See
+    /// `Input::labels()` for the supported kinds of labels.
+    pub fn input_by_label(&self, label: impl Into<TextMatch>) -> Result<&Input> {
+        let idx = self.locate(&Locator::Label(label.into()))?;
+        Ok(&self.inputs[idx])
+    }
+
+    /// Returns a mutable reference to the first input field ([`Input`][Input]) associated with the given label text.
+    /// See `Input::labels()` for the supported kinds of labels.
+    pub fn input_by_label_mut(&mut self, label: impl Into<TextMatch>) -> Result<&mut Input> {
+        let idx = self.locate(&Locator::Label(label.into()))?;
+        Ok(&mut self.inputs[idx])
+    }
+
+    /// Returns a reference to the first input field ([`Input`][Input]) with the given `placeholder` attribute.
+    pub fn input_by_placeholder(&self, placeholder: impl Into<TextMatch>) -> Result<&Input> {
+        let idx = self.locate(&Locator::Placeholder(placeholder.into()))?;
+        Ok(&self.inputs[idx])
+    }
+
+    /// Returns a mutable reference to the first input field ([`Input`][Input]) with the given `placeholder`
+    /// attribute.
+    pub fn input_by_placeholder_mut(
+        &mut self,
+        placeholder: impl Into<TextMatch>,
+    ) -> Result<&mut Input> {
+        let idx = self.locate(&Locator::Placeholder(placeholder.into()))?;
+        Ok(&mut self.inputs[idx])
+    }
+
+    /// Returns a reference to the first input field ([`Input`][Input]) matching the given CSS selector group. The
+    /// selectors are evaluated against the page as loaded (which is parsed again for this), but only elements within
+    /// this form are considered. Attributes changed via `Input::set_attr()` or `Input::set_value()` are not taken
+    /// into account.
+    ///
+    /// ```no_run
+    /// # let page: Option<no_browser::page::Page> = None;
+    /// # let mut page = page.unwrap();
+    /// let form = page.form_mut(0)?;
+    /// form.input_by_selector_mut("fieldset.billing input[name$='[zip]']")?
+    ///     .set_value(Some("12345".to_owned()));
+    /// # Ok::<(), no_browser::browser::Error>(())
+    /// ```
+    pub fn input_by_selector(&self, selectors: &str) -> Result<&Input> {
+        let idx = self.locate(&Locator::Selector(selectors))?;
+        Ok(&self.inputs[idx])
+    }
+
+    /// Returns a mutable reference to the first input field ([`Input`][Input]) matching the given CSS selector group.
+    /// See `input_by_selector()` for details.
+    pub fn input_by_selector_mut(&mut self, selectors: &str) -> Result<&mut Input> {
+        let idx = self.locate(&Locator::Selector(selectors))?;
+        Ok(&mut self.inputs[idx])
+    }
+
+    fn locate(&self, locator: &Locator) -> Result<usize> {
+        let idx = match locator {
+            Locator::Id(id) => self.inputs.iter().position(|i| i.attr("id") == Some(id)),
+            Locator::Label(label) => self
+                .inputs
+                .iter()
+                .position(|i| i.labels().iter().any(|l| label.matches(l))),
+            Locator::Placeholder(placeholder) => self.inputs.iter().position(|i| {
+                i.attr("placeholder")
+                    .is_some_and(|p| placeholder.matches(p))
+            }),
+            Locator::Selector(selectors) => self.locate_by_selector(selectors)?,
+        };
+
+        idx.ok_or_else(|| Error::InputNotFoundError {
+            locator: locator.to_string(),
+        })
+    }
+
+    fn locate_by_selector(&self, selectors: &str) -> Result<Option<usize>> {
+        let selector =
+            Selector::parse(selectors).map_err(|error| Error::CssSelectorParseError {
+                selector: selectors.to_owned(),
+                reason: format!("{error:?}"),
+            })?;
+
+        let document = Html::parse_document(&self.source.0);
+        let form = document
+            .tree
+            .get(self.node)
+            .and_then(ElementRef::wrap)
+            .expect("form node is an element of its document");
+        let matched = form
+            .select(&selector)
+            .map(|element| element.id())
+            .collect::<HashSet<NodeId>>();
+
+        Ok(self.nodes.iter().position(|node| matched.contains(node)))
+    }
+
     pub(crate) fn submit(&self, submit_button_name: Option<&str>) -> Result<SubmitFormInfo> {
         let url = self.form_target_url();
         let method = self.method.clone();
@@ -109,10 +292,44 @@ impl Form {
             data.push((input.name().to_owned(), input.value().unwrap().to_owned()));
         }
 
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            form.id = self.id.as_deref(),
+            %method,
+            %url,
+            fields = %self.masked_fields(&data),
+            "form submitted"
+        );
+
         Ok(SubmitFormInfo { url, method, data })
     }
 
-    pub(crate) fn parse(form_ref: &ElementRef, page_url: Url) -> Self {
+    /// Returns the names of the password inputs, whose values are masked when logging.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn password_names(&self) -> Vec<String> {
+        self.inputs
+            .iter()
+            .filter(|input| input.t() == InputType::Password)
+            .map(|input| input.name().to_owned())
+            .collect()
+    }
+
+    /// Returns the submitted `data` as `name=value` list for logging, masking the values of password inputs.
+    #[cfg(feature = "tracing")]
+    fn masked_fields(&self, data: &[(String, String)]) -> String {
+        let passwords = self.password_names();
+
+        data.iter()
+            .map(|(name, value)| match passwords.contains(name) {
+                true => format!("{name}=********"),
+                false => format!("{name}={value}"),
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Parses the form `form_ref` of a document parsed with `Html::parse_document()` from `source`.
+    pub(crate) fn parse(source: &Arc<str>, form_ref: &ElementRef, page_url: Url) -> Self {
         let form = form_ref.value();
         let method_s = form.attr("method").unwrap_or("GET");
         let mut method = Method::from_str(&method_s.to_uppercase()).unwrap_or(Method::GET);
@@ -127,7 +344,7 @@ impl Form {
             .map(|s| s.to_owned())
             .unwrap();
         let id = form.attr("id").map(|s| s.to_owned());
-        let inputs = Self::parse_form_inputs(form_ref);
+        let (inputs, nodes) = Self::parse_form_inputs(form_ref);
 
         Self {
             page_url,
@@ -135,32 +352,29 @@ impl Form {
             action,
             id,
             inputs,
+            source: Source(Arc::clone(source)),
+            node: form_ref.id(),
+            nodes,
         }
     }
 
-    fn parse_form_inputs(form: &ElementRef) -> Vec<Input> {
-        let html = Html::parse_fragment(&form.inner_html());
+    fn parse_form_inputs(form: &ElementRef) -> (Vec<Input>, Vec<NodeId>) {
         let mut inputs = Vec::new();
-
-        let selector = Selector::parse("input").unwrap();
-        for input in html.select(&selector) {
-            let input = input.value();
-            if let Ok(input) = Input::parse(input) {
-                // Silently drop input parse errors
-                inputs.push(input)
+        let mut nodes = Vec::new();
+
+        // buttons go last; the value of the chosen submit button is submitted first, the other buttons are skipped
+        for selector in ["input, select, textarea", "button"] {
+            let selector = Selector::parse(selector).unwrap();
+            for control in form.select(&selector) {
+                if let Ok(input) = Input::parse(&control) {
+                    // Silently drop input parse errors
+                    inputs.push(input.with_labels(query::labels_of(&control)));
+                    nodes.push(control.id());
+                }
             }
         }
 
-        let selector = Selector::parse("button").unwrap();
-        for button in html.select(&selector) {
-            let button = button.value();
-            if let Ok(button) = Input::parse(button) {
-                // Silently drop input parse errors
-                inputs.push(button)
-            }
-        }
-
-        inputs
+        (inputs, nodes)
     }
 
     fn form_target_url(&self) -> String {
@@ -187,7 +401,7 @@ impl Form {
         if !self.action.starts_with('/') {
             // action relative to the current path; so add current path
             if self.page_url.path().ends_with('/') {
-                url.push_str(&self.page_url.path());
+                url.push_str(self.page_url.path());
             } else {
                 // discard last page / file segment
                 let mut path_parts: Vec<&str> = self.page_url.path().split('/').collect();
@@ -207,10 +421,23 @@ impl Form {
 mod tests {
     use reqwest::{Method, Url};
     use scraper::{Html, Selector};
+    use std::sync::Arc;
 
-    use crate::input::InputType;
+    use crate::{input::InputType, query::TextMatch};
+
+    use super::{Error, Form, Result};
+
+    fn parse(source: &str) -> Form {
+        let html = Html::parse_document(source);
+        let selector = Selector::parse("form").unwrap();
+        let form = html.select(&selector).next().unwrap();
 
-    use super::{Form, Result};
+        Form::parse(
+            &Arc::from(source),
+            &form,
+            Url::parse("https://wikipedia.org/").unwrap(),
+        )
+    }
 
     static FORM_001: &str = r#"
     <html>
@@ -226,11 +453,7 @@ mod tests {
 
     #[test]
     fn parse_form() -> Result<()> {
-        let html = Html::parse_fragment(FORM_001);
-        let selector = Selector::parse("form").unwrap();
-        let form = html.select(&selector).next().unwrap();
-
-        let form = Form::parse(&form, Url::parse("https://wikipedia.org/").unwrap());
+        let form = parse(FORM_001);
 
         assert_eq!(form.page_url, Url::parse("https://wikipedia.org/").unwrap());
         assert_eq!(form.method, Method::GET);
@@ -247,11 +470,7 @@ mod tests {
 
     #[test]
     fn submit_checkboxes() -> Result<()> {
-        let html = Html::parse_fragment(FORM_001);
-        let selector = Selector::parse("form").unwrap();
-        let form = html.select(&selector).next().unwrap();
-
-        let mut form = Form::parse(&form, Url::parse("https://wikipedia.org/").unwrap());
+        let mut form = parse(FORM_001);
 
         let info = form.submit(Some("ok"))?;
         assert_eq!(info.method, Method::GET);
@@ -301,4 +520,127 @@ mod tests {
 
         Ok(())
     }
+
+    static FORM_002: &str = r#"
+    <html>
+        <body>
+            <label for="mail">E-Mail address</label>
+            <form id="form_02" method="POST" action="/signup">
+                <input id="mail" name="user[8f3a]" type="email" placeholder="you@example.com">
+                <label>Password <input name="user[c1d2]" type="password"></label>
+                <fieldset class="extra">
+                    <input name="user[e4f5]" type="text" aria-label="Nickname">
+                </fieldset>
+                <input type="text" placeholder="unnamed and therefore ignored">
+                <button name="ok" type="submit" value="ok">OK</button>
+            </form>
+        </body>
+    </html>"#;
+
+    #[test]
+    fn locate_inputs() -> Result<()> {
+        let mut form = parse(FORM_002);
+
+        let names: Vec<&str> = form.inputs().map(|i| i.name()).collect();
+        assert_eq!(names, vec!["user[8f3a]", "user[c1d2]", "user[e4f5]", "ok"]);
+
+        assert_eq!(form.input_by_id("mail")?.name(), "user[8f3a]");
+        assert_eq!(form.input_by_label("E-Mail address")?.name(), "user[8f3a]");
+        assert_eq!(form.input_by_label("Password")?.name(), "user[c1d2]");
+        assert_eq!(form.input_by_label("Nickname")?.name(), "user[e4f5]");
+        assert_eq!(
+            form.input_by_placeholder("you@example.com")?.name(),
+            "user[8f3a]"
+        );
+        assert_eq!(
+            form.input_by_selector("fieldset.extra > input")?.name(),
+            "user[e4f5]"
+        );
+        assert_eq!(form.input_by_selector("[type=submit]")?.name(), "ok");
+
+        form.input_by_label_mut(TextMatch::contains("e-mail"))?
+            .set_value(Some("ferris@example.com".to_owned()));
+        assert_eq!(
+            form.input_by_id("mail")?.value(),
+            Some("ferris@example.com")
+        );
+
+        assert!(matches!(
+            form.input_by_label("Username"),
+            Err(Error::InputNotFoundError { .. })
+        ));
+        assert!(matches!(
+            form.input_by_selector("input[[["),
+            Err(Error::CssSelectorParseError { .. })
+        ));
+
+        Ok(())
+    }
+
+    static FORM_003: &str = r#"
+    <html>
+        <body>
+            <form id="form_03" method="POST" action="/order">
+                <button name="go" type="submit" value="go">Go</button>
+                <select name="size"><option value="s">S</option><option selected>M</option></select>
+                <select name="crust"><option value="thin">Thin</option></select>
+                <textarea name="note" class="note">Extra cheese</textarea>
+                <input name="qty" type="number" value="2">
+            </form>
+        </body>
+    </html>"#;
+
+    #[test]
+    fn parse_all_controls() -> Result<()> {
+        let mut form = parse(FORM_003);
+
+        // buttons come after all other controls
+        let controls: Vec<(InputType, &str)> = form.inputs().map(|i| (i.t(), i.name())).collect();
+        assert_eq!(
+            controls,
+            vec![
+                (InputType::Select, "size"),
+                (InputType::Select, "crust"),
+                (InputType::TextArea, "note"),
+                (InputType::Number, "qty"),
+                (InputType::Submit, "go"),
+            ]
+        );
+        assert_eq!(form.input(InputType::Select, "size")?.value(), Some("M"));
+        assert_eq!(form.input(InputType::Select, "size")?.options(), ["s", "M"]);
+        assert_eq!(
+            form.input(InputType::Select, "crust")?.value(),
+            Some("thin")
+        );
+
+        // selectors are matched against the document, not against replayed attributes
+        form.input_by_selector_mut("textarea.note")?
+            .set_attr("class", None);
+        assert_eq!(form.input_by_selector("form .note")?.name(), "note");
+        assert_eq!(form.input_by_selector("body button")?.name(), "go");
+
+        // the value of the chosen submit button comes first
+        let info = form.submit(Some("go"))?;
+        let data: Vec<(&str, &str)> = info
+            .data
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        assert_eq!(
+            data,
+            vec![
+                ("go", "go"),
+                ("size", "M"),
+                ("crust", "thin"),
+                ("note", "Extra cheese"),
+                ("qty", "2"),
+            ]
+        );
+
+        // forms own their data and can be moved to other threads
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+        assert_send_sync(&form);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,513 @@
+//! Module containing the [`HarRecorder`][HarRecorder] struct to record [`Browser`][crate::Browser] traffic.
+//!
+//! The recorder captures every request and response sent by a browser, including each hop of a redirect chain, and
+//! writes them as [HTTP Archive (HAR) 1.2](http://www.softwareishard.com/blog/har-12-spec/) files, which can be
+//! inspected with the developer tools of common web browsers or other standard tools.
+//!
+//! ```no_run
+//! use no_browser::{har::HarRecorder, Browser};
+//!
+//! let recorder = HarRecorder::new();
+//! let browser = Browser::builder().har_recorder(recorder.clone()).finish()?;
+//!
+//! let page = browser.navigate_to("https://example.com/", None)?;
+//! recorder.save("target/example.har")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{
+    headers::{SetCookie, TypedHeaders},
+    transport,
+};
+use base64::Engine;
+use reqwest::{
+    header::{HeaderMap, HeaderName, CONTENT_TYPE, COOKIE, LOCATION},
+    Url, Version,
+};
+use serde::Serialize;
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use thiserror::Error;
+
+/// An error occurred while writing a HAR file.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The HAR file could not be written.
+    #[error("Failed to write HAR file '{path}'!")]
+    IoError {
+        /// The `path` of the file.
+        path: String,
+        /// The underlying error.
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::har::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Records the http traffic of a [`Browser`][crate::Browser] in HAR format. Attach it with
+/// `BrowserBuilder::har_recorder()`.
+///
+/// Clones of a recorder share the same recorded entries, so keep a clone to save the archive later on, e.g. when a
+/// test fails. Recorders are thread-safe.
+#[derive(Debug, Clone, Default)]
+pub struct HarRecorder {
+    entries: Arc<Mutex<Vec<Entry>>>,
+}
+
+impl HarRecorder {
+    /// Returns a new, empty [`HarRecorder`][HarRecorder].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of recorded entries, i.e. request-response pairs.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns `true` if no entries have been recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Removes all recorded entries.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Returns the recorded entries as HAR 1.2 JSON document.
+    pub fn to_json(&self) -> String {
+        let entries = self.entries.lock().unwrap();
+        let har = Har {
+            log: Log {
+                version: "1.2",
+                creator: Creator {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                },
+                entries: &entries,
+            },
+        };
+
+        serde_json::to_string_pretty(&har).expect("HAR is serializable")
+    }
+
+    /// Writes the recorded entries as HAR 1.2 file to `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+
+        std::fs::write(path, self.to_json()).map_err(|error| Error::IoError {
+            path: path.display().to_string(),
+            source: error,
+        })
+    }
+
+    pub(crate) fn record(&self, entry: Entry) {
+        self.entries.lock().unwrap().push(entry);
+    }
+}
+
+#[derive(Serialize)]
+struct Har<'a> {
+    log: Log<'a>,
+}
+
+#[derive(Serialize)]
+struct Log<'a> {
+    version: &'static str,
+    creator: Creator,
+    entries: &'a [Entry],
+}
+
+#[derive(Serialize)]
+struct Creator {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Entry {
+    started_date_time: String,
+    time: f64,
+    request: Request,
+    response: Response,
+    cache: Cache,
+    timings: Timings,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Request {
+    method: String,
+    url: String,
+    http_version: String,
+    cookies: Vec<Cookie>,
+    headers: Vec<NameValue>,
+    query_string: Vec<NameValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    post_data: Option<PostData>,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PostData {
+    mime_type: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    params: Vec<NameValue>,
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    cookies: Vec<Cookie>,
+    headers: Vec<NameValue>,
+    content: Content,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Content {
+    size: usize,
+    mime_type: String,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Cookie {
+    name: String,
+    value: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    domain: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secure: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct NameValue {
+    name: String,
+    value: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Cache {}
+
+#[derive(Debug, Serialize)]
+struct Timings {
+    blocked: f64,
+    dns: f64,
+    connect: f64,
+    send: f64,
+    wait: f64,
+    receive: f64,
+    ssl: f64,
+}
+
+/// The request half of an [`Entry`][Entry], captured before the request is sent.
+#[derive(Debug)]
+pub(crate) struct PendingEntry {
+    started: SystemTime,
+    request: Request,
+}
+
+impl PendingEntry {
+    /// Captures `request`, including the cookies added by the browser.
+    pub(crate) fn new(request: &transport::Request) -> Self {
+        let cookies = request
+            .headers()
+            .get_all(COOKIE)
+            .iter()
+            .flat_map(|cookie| cookie.to_str().unwrap_or_default().split(';'))
+            .filter_map(|pair| pair.trim().split_once('='))
+            .map(|(name, value)| Cookie {
+                name: name.to_owned(),
+                value: value.to_owned(),
+                path: None,
+                domain: None,
+                expires: None,
+                http_only: None,
+                secure: None,
+            })
+            .collect();
+
+        let body = request.body().unwrap_or_default();
+        let post_data = (!body.is_empty()).then(|| {
+            let mime_type = header(request.headers(), CONTENT_TYPE).unwrap_or_default();
+            let params = if mime_type.starts_with("application/x-www-form-urlencoded") {
+                form_pairs(body)
+            } else {
+                Vec::new()
+            };
+            PostData {
+                mime_type,
+                params,
+                text: String::from_utf8_lossy(body).into_owned(),
+            }
+        });
+
+        Self {
+            started: SystemTime::now(),
+            request: Request {
+                method: request.method().to_string(),
+                url: request.url().to_string(),
+                http_version: version(Version::HTTP_11),
+                cookies,
+                headers: name_values(request.headers()),
+                query_string: query_pairs(request.url()),
+                post_data,
+                headers_size: -1,
+                body_size: body.len() as i64,
+            },
+        }
+    }
+
+    /// Completes the entry with the received `response`; `wait` is the time the transport took to return it.
+    pub(crate) fn finish(self, response: &transport::Response, wait: Duration) -> Entry {
+        let status = response.status();
+        let headers = response.headers();
+        let body = response.body();
+        let mime_type = header(headers, CONTENT_TYPE).unwrap_or_default();
+        let (text, encoding) = match std::str::from_utf8(body) {
+            Ok(text) => (text.to_owned(), None),
+            Err(_) => (
+                base64::engine::general_purpose::STANDARD.encode(body),
+                Some("base64"),
+            ),
+        };
+        let redirect_url = header(headers, LOCATION)
+            .and_then(|location| Url::parse(&self.request.url).ok()?.join(&location).ok())
+            .map(|url| url.to_string())
+            .unwrap_or_default();
+
+        let wait = millis(wait);
+
+        Entry {
+            started_date_time: iso8601(self.started),
+            time: wait,
+            request: self.request,
+            response: Response {
+                status: status.as_u16(),
+                status_text: status.canonical_reason().unwrap_or_default().to_owned(),
+                http_version: version(response.version()),
+                cookies: TypedHeaders::new(headers)
+                    .set_cookies()
+                    .into_iter()
+                    .map(cookie)
+                    .collect(),
+                headers: name_values(headers),
+                content: Content {
+                    size: body.len(),
+                    mime_type,
+                    text,
+                    encoding,
+                },
+                redirect_url,
+                headers_size: -1,
+                body_size: body.len() as i64,
+            },
+            cache: Cache {},
+            timings: Timings {
+                blocked: -1.0,
+                dns: -1.0,
+                connect: -1.0,
+                send: 0.0,
+                wait,
+                receive: 0.0,
+                ssl: -1.0,
+            },
+        }
+    }
+}
+
+fn query_pairs(url: &Url) -> Vec<NameValue> {
+    url.query_pairs()
+        .map(|(name, value)| NameValue {
+            name: name.into_owned(),
+            value: value.into_owned(),
+        })
+        .collect()
+}
+
+fn form_pairs(body: &[u8]) -> Vec<NameValue> {
+    let mut url = Url::parse("http://localhost/").unwrap();
+    url.set_query(Some(&String::from_utf8_lossy(body)));
+
+    query_pairs(&url)
+}
+
+fn header(headers: &HeaderMap, name: HeaderName) -> Option<String> {
+    headers
+        .get(name)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+}
+
+fn name_values(headers: &HeaderMap) -> Vec<NameValue> {
+    headers
+        .iter()
+        .map(|(name, value)| NameValue {
+            name: name.to_string(),
+            value: String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        })
+        .collect()
+}
+
+fn cookie(cookie: SetCookie) -> Cookie {
+    Cookie {
+        name: cookie.name().to_owned(),
+        value: cookie.value().to_owned(),
+        path: cookie.path().map(str::to_owned),
+        domain: cookie.domain().map(str::to_owned),
+        expires: cookie.expires().map(iso8601),
+        http_only: cookie.http_only().then_some(true),
+        secure: cookie.secure().then_some(true),
+    }
+}
+
+fn version(version: Version) -> String {
+    format!("{version:?}")
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Formats `time` as ISO 8601 date-time in UTC with millisecond precision, e.g. `2024-05-01T12:34:56.789Z`.
+fn iso8601(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{iso8601, HarRecorder};
+    use crate::{browser::Browser, input::InputType};
+    use serde_json::Value;
+    use std::{
+        thread,
+        time::{Duration, UNIX_EPOCH},
+    };
+    use tiny_http::{Header, Response, Server};
+
+    #[test]
+    fn format_timestamps() {
+        assert_eq!(iso8601(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            iso8601(UNIX_EPOCH + Duration::from_millis(1_709_210_096_789)),
+            "2024-02-29T12:34:56.789Z"
+        );
+    }
+
+    #[test]
+    fn record_redirects_cookies_and_forms() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        thread::spawn(move || {
+            for request in server.incoming_requests().take(3) {
+                let response = match request.url() {
+                    "/login" => Response::from_string("").with_status_code(303).with_header(
+                        Header::from_bytes("Location", "/home").unwrap(),
+                    ).with_header(
+                        Header::from_bytes("Set-Cookie", "SESSION=abc; Path=/; Expires=Wed, 21 Oct 2099 07:28:00 GMT; HttpOnly").unwrap(),
+                    ),
+                    _ => Response::from_string(
+                        "<form action='/login' method='post'><input type='text' name='user'></form>",
+                    ),
+                };
+                request.respond(response).unwrap();
+            }
+        });
+
+        let recorder = HarRecorder::new();
+        let browser = Browser::builder()
+            .har_recorder(recorder.clone())
+            .finish()
+            .unwrap();
+
+        let mut page = browser
+            .navigate_to(&format!("http://{addr}/start"), Some(&vec![("a", "1")]))
+            .unwrap();
+        let form = page.form_mut(0).unwrap();
+        form.input_mut(InputType::Text, "user")
+            .unwrap()
+            .set_value(Some("ferris".to_owned()));
+        let page = browser.submit_form(form, None).unwrap();
+        assert_eq!(page.url().path(), "/home");
+        assert_eq!(recorder.len(), 3);
+
+        let har: Value = serde_json::from_str(&recorder.to_json()).unwrap();
+        let entries = har["log"]["entries"].as_array().unwrap();
+        assert_eq!(har["log"]["version"], "1.2");
+
+        assert_eq!(entries[0]["request"]["queryString"][0]["value"], "1");
+
+        let login = &entries[1];
+        assert_eq!(login["request"]["method"], "POST");
+        assert_eq!(login["request"]["postData"]["params"][0]["name"], "user");
+        assert_eq!(login["request"]["postData"]["params"][0]["value"], "ferris");
+        assert_eq!(login["response"]["status"], 303);
+        assert_eq!(
+            login["response"]["redirectURL"],
+            format!("http://{addr}/home")
+        );
+        assert_eq!(login["response"]["cookies"][0]["name"], "SESSION");
+        assert_eq!(login["response"]["cookies"][0]["httpOnly"], true);
+        assert_eq!(
+            login["response"]["cookies"][0]["expires"],
+            "2099-10-21T07:28:00.000Z"
+        );
+
+        let home = &entries[2];
+        assert_eq!(home["request"]["method"], "GET");
+        assert_eq!(home["request"]["cookies"][0]["value"], "abc");
+        assert!(home["time"].as_f64().unwrap() >= 0.0);
+
+        let dir = tempfile::tempdir().unwrap();
+        recorder.save(dir.path().join("test.har")).unwrap();
+        recorder.clear();
+        assert!(recorder.is_empty());
+    }
+}
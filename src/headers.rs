@@ -0,0 +1,727 @@
+//! Module containing [`TypedHeaders`][TypedHeaders], a typed view on common response headers.
+//!
+//! Use `Page::typed_headers()` for the headers of a page, or `TypedHeaders::new()` for any [`HeaderMap`]. Parsing is
+//! lenient: missing or malformed headers are returned as `None` (or empty lists) rather than as errors, and unknown
+//! directives and parameters are kept, so that checks can look for them by name.
+//!
+//! ```
+//! use no_browser::{headers::SameSite, page::Page};
+//! use reqwest::header::{HeaderValue, CONTENT_TYPE, SET_COOKIE, STRICT_TRANSPORT_SECURITY};
+//! use std::time::Duration;
+//!
+//! let page = Page::builder("https://example.com/")
+//!     .header(CONTENT_TYPE, HeaderValue::from_static("text/html; charset=UTF-8"))
+//!     .header(STRICT_TRANSPORT_SECURITY, HeaderValue::from_static("max-age=31536000; includeSubDomains"))
+//!     .header(SET_COOKIE, HeaderValue::from_static("sid=42; Path=/; Secure; HttpOnly; SameSite=Lax"))
+//!     .build_from_text("<h1>Hello</h1>")?;
+//! let headers = page.typed_headers();
+//!
+//! let content_type = headers.content_type().unwrap();
+//! assert_eq!(content_type.essence(), "text/html");
+//! assert_eq!(content_type.charset(), Some("utf-8"));
+//!
+//! let hsts = headers.strict_transport_security().unwrap();
+//! assert_eq!(hsts.max_age(), Duration::from_secs(31_536_000));
+//! assert!(hsts.include_subdomains());
+//!
+//! let cookie = &headers.set_cookies()[0];
+//! assert_eq!((cookie.name(), cookie.value()), ("sid", "42"));
+//! assert!(cookie.secure() && cookie.http_only());
+//! assert_eq!(cookie.same_site(), Some(SameSite::Lax));
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use reqwest::header::{
+    HeaderMap, HeaderName, CACHE_CONTROL, CONTENT_LENGTH, CONTENT_SECURITY_POLICY,
+    CONTENT_SECURITY_POLICY_REPORT_ONLY, CONTENT_TYPE, LAST_MODIFIED, LINK, SET_COOKIE,
+    STRICT_TRANSPORT_SECURITY,
+};
+use std::time::{Duration, SystemTime};
+
+/// A typed view on the headers of a response. See the [module documentation][crate::headers] for details.
+#[derive(Debug, Clone, Copy)]
+pub struct TypedHeaders<'a> {
+    headers: &'a HeaderMap,
+}
+
+impl<'a> TypedHeaders<'a> {
+    /// Returns a typed view on `headers`.
+    pub const fn new(headers: &'a HeaderMap) -> Self {
+        Self { headers }
+    }
+
+    /// Returns the first value of the header `name` as string, or `None` if it is missing or not visible ASCII.
+    pub fn get<K: AsRef<str>>(&self, name: K) -> Option<&'a str> {
+        self.headers.get(name.as_ref())?.to_str().ok()
+    }
+
+    /// Returns all values of the header `name` that are visible ASCII.
+    pub fn get_all(&self, name: &HeaderName) -> Vec<&'a str> {
+        self.headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .collect()
+    }
+
+    /// Returns the parsed `Content-Type` header.
+    pub fn content_type(&self) -> Option<MediaType> {
+        MediaType::parse(self.get(CONTENT_TYPE)?)
+    }
+
+    /// Returns the `Content-Length` header in bytes.
+    pub fn content_length(&self) -> Option<u64> {
+        self.get(CONTENT_LENGTH)?.trim().parse().ok()
+    }
+
+    /// Returns the `Last-Modified` header.
+    pub fn last_modified(&self) -> Option<SystemTime> {
+        httpdate::parse_http_date(self.get(LAST_MODIFIED)?.trim()).ok()
+    }
+
+    /// Returns the directives of all `Cache-Control` headers. Without any header, the directives are empty.
+    pub fn cache_control(&self) -> CacheControl {
+        CacheControl {
+            directives: self
+                .get_all(&CACHE_CONTROL)
+                .into_iter()
+                .flat_map(|value| split_unquoted(value, ','))
+                .filter_map(|directive| {
+                    let (name, argument) = match directive.split_once('=') {
+                        Some((name, argument)) => (name, Some(unquote(argument))),
+                        None => (directive, None),
+                    };
+                    let name = name.trim().to_ascii_lowercase();
+                    (!name.is_empty()).then_some((name, argument))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the enforced content security policies, one per policy in the `Content-Security-Policy` headers. All
+    /// of them apply at the same time.
+    pub fn content_security_policy(&self) -> Vec<ContentSecurityPolicy> {
+        self.policies(&CONTENT_SECURITY_POLICY)
+    }
+
+    /// Returns the monitored content security policies of the `Content-Security-Policy-Report-Only` headers.
+    pub fn content_security_policy_report_only(&self) -> Vec<ContentSecurityPolicy> {
+        self.policies(&CONTENT_SECURITY_POLICY_REPORT_ONLY)
+    }
+
+    /// Returns the parsed `Strict-Transport-Security` header, or `None` if it is missing or has no valid `max-age`.
+    pub fn strict_transport_security(&self) -> Option<StrictTransportSecurity> {
+        let mut hsts = StrictTransportSecurity {
+            max_age: None,
+            include_subdomains: false,
+            preload: false,
+        };
+
+        for directive in split_unquoted(self.get(STRICT_TRANSPORT_SECURITY)?, ';') {
+            let (name, value) = directive.split_once('=').unwrap_or((directive, ""));
+            match name.trim().to_ascii_lowercase().as_str() {
+                "max-age" => hsts.max_age = unquote(value).parse().ok().map(Duration::from_secs),
+                "includesubdomains" => hsts.include_subdomains = true,
+                "preload" => hsts.preload = true,
+                _ => {}
+            }
+        }
+
+        hsts.max_age.is_some().then_some(hsts)
+    }
+
+    /// Returns the cookies set by the `Set-Cookie` headers. Headers without a cookie name are skipped.
+    pub fn set_cookies(&self) -> Vec<SetCookie> {
+        self.get_all(&SET_COOKIE)
+            .into_iter()
+            .filter_map(SetCookie::parse)
+            .collect()
+    }
+
+    /// Returns the links of the `Link` headers, e.g. `preload` or `canonical` relations.
+    pub fn links(&self) -> Vec<Link> {
+        self.get_all(&LINK)
+            .into_iter()
+            .flat_map(|value| split_unquoted(value, ','))
+            .filter_map(Link::parse)
+            .collect()
+    }
+
+    fn policies(&self, name: &HeaderName) -> Vec<ContentSecurityPolicy> {
+        self.get_all(name)
+            .into_iter()
+            .flat_map(|value| value.split(','))
+            .map(ContentSecurityPolicy::parse)
+            .filter(|policy| !policy.directives.is_empty())
+            .collect()
+    }
+}
+
+/// A media type, like `text/html; charset=utf-8`, as found in the `Content-Type` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaType {
+    essence: String,
+    params: Vec<(String, String)>,
+}
+
+impl MediaType {
+    /// Parses a media type, returning `None` if it has no `type/subtype`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = split_unquoted(value, ';').into_iter();
+        let essence = parts.next()?.trim().to_ascii_lowercase();
+        let (kind, subtype) = essence.split_once('/')?;
+        if kind.is_empty() || subtype.is_empty() {
+            return None;
+        }
+
+        let params = parts
+            .filter_map(|param| param.split_once('='))
+            .map(|(name, value)| {
+                let name = name.trim().to_ascii_lowercase();
+                let value = match name.as_str() {
+                    "charset" => unquote(value).to_ascii_lowercase(),
+                    _ => unquote(value),
+                };
+                (name, value)
+            })
+            .collect();
+
+        Some(Self { essence, params })
+    }
+
+    /// Returns the lowercase `type/subtype`, without parameters, e.g. `text/html`.
+    pub fn essence(&self) -> &str {
+        &self.essence
+    }
+
+    /// Returns the type, e.g. `text` for `text/html`.
+    pub fn type_(&self) -> &str {
+        self.essence.split_once('/').unwrap_or_default().0
+    }
+
+    /// Returns the subtype, e.g. `html` for `text/html`.
+    pub fn subtype(&self) -> &str {
+        self.essence.split_once('/').unwrap_or_default().1
+    }
+
+    /// Returns the value of the parameter `name` (case-insensitive), if any.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the lowercase `charset` parameter, if any.
+    pub fn charset(&self) -> Option<&str> {
+        self.param("charset")
+    }
+
+    /// Returns `true` for html and xhtml documents.
+    pub fn is_html(&self) -> bool {
+        matches!(self.essence(), "text/html" | "application/xhtml+xml")
+    }
+}
+
+/// The directives of the `Cache-Control` header.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    directives: Vec<(String, Option<String>)>,
+}
+
+impl CacheControl {
+    /// Returns all directives in order, with lowercase names and unquoted arguments.
+    pub fn directives(&self) -> &[(String, Option<String>)] {
+        &self.directives
+    }
+
+    /// Returns `true` if the directive `name` (case-insensitive) is present.
+    pub fn contains(&self, name: &str) -> bool {
+        self.directives
+            .iter()
+            .any(|(directive, _)| directive.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns the argument of the directive `name` (case-insensitive), if it is present and has an argument.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.directives
+            .iter()
+            .find(|(directive, _)| directive.eq_ignore_ascii_case(name))
+            .and_then(|(_, argument)| argument.as_deref())
+    }
+
+    /// Returns the `max-age` directive.
+    pub fn max_age(&self) -> Option<Duration> {
+        self.seconds("max-age")
+    }
+
+    /// Returns the `s-maxage` directive for shared caches.
+    pub fn s_maxage(&self) -> Option<Duration> {
+        self.seconds("s-maxage")
+    }
+
+    /// Returns `true` if the `no-store` directive is present.
+    pub fn no_store(&self) -> bool {
+        self.contains("no-store")
+    }
+
+    /// Returns `true` if the `no-cache` directive is present.
+    pub fn no_cache(&self) -> bool {
+        self.contains("no-cache")
+    }
+
+    /// Returns `true` if the `private` directive is present.
+    pub fn private(&self) -> bool {
+        self.contains("private")
+    }
+
+    /// Returns `true` if the `public` directive is present.
+    pub fn public(&self) -> bool {
+        self.contains("public")
+    }
+
+    fn seconds(&self, name: &str) -> Option<Duration> {
+        self.get(name)?.parse().ok().map(Duration::from_secs)
+    }
+}
+
+/// A [content security policy](https://www.w3.org/TR/CSP3/), i.e. a list of directives with their source lists.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentSecurityPolicy {
+    directives: Vec<(String, Vec<String>)>,
+}
+
+impl ContentSecurityPolicy {
+    /// Parses a single serialized policy. Repeated directives are ignored, like browsers do.
+    pub fn parse(policy: &str) -> Self {
+        let mut directives: Vec<(String, Vec<String>)> = Vec::new();
+
+        for directive in policy.split(';') {
+            let mut tokens = directive.split_ascii_whitespace();
+            let Some(name) = tokens.next().map(str::to_ascii_lowercase) else {
+                continue;
+            };
+            if directives.iter().all(|(existing, _)| *existing != name) {
+                directives.push((name, tokens.map(str::to_owned).collect()));
+            }
+        }
+
+        Self { directives }
+    }
+
+    /// Returns all directives in order, with lowercase names.
+    pub fn directives(&self) -> &[(String, Vec<String>)] {
+        &self.directives
+    }
+
+    /// Returns the values of the directive `name` (case-insensitive), e.g. the sources of `script-src`, or `None` if
+    /// the directive is missing.
+    pub fn directive(&self, name: &str) -> Option<&[String]> {
+        self.directives
+            .iter()
+            .find(|(directive, _)| directive.eq_ignore_ascii_case(name))
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// Returns the values of the directive `name` or, for fetch directives like `script-src` or `img-src`, of
+    /// `default-src` if `name` is missing.
+    pub fn effective_directive(&self, name: &str) -> Option<&[String]> {
+        self.directive(name).or_else(|| {
+            name.ends_with("-src")
+                .then(|| self.directive("default-src"))
+                .flatten()
+        })
+    }
+}
+
+/// The parsed `Strict-Transport-Security` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StrictTransportSecurity {
+    max_age: Option<Duration>,
+    include_subdomains: bool,
+    preload: bool,
+}
+
+impl StrictTransportSecurity {
+    /// Returns how long the host is to be accessed via https only. A `max-age` of zero removes the policy.
+    pub fn max_age(&self) -> Duration {
+        self.max_age.unwrap_or_default()
+    }
+
+    /// Returns `true` if the `includeSubDomains` directive is present.
+    pub const fn include_subdomains(&self) -> bool {
+        self.include_subdomains
+    }
+
+    /// Returns `true` if the `preload` directive is present.
+    pub const fn preload(&self) -> bool {
+        self.preload
+    }
+}
+
+/// The `SameSite` attribute of a cookie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SameSite {
+    /// The cookie is only sent with requests from the same site.
+    Strict,
+    /// The cookie is also sent when navigating to the site from other sites.
+    Lax,
+    /// The cookie is sent with all requests, including cross-site ones.
+    None,
+}
+
+/// A cookie set by a `Set-Cookie` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetCookie {
+    name: String,
+    value: String,
+    expires: Option<SystemTime>,
+    max_age: Option<Duration>,
+    domain: Option<String>,
+    path: Option<String>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+impl SetCookie {
+    /// Parses the value of a `Set-Cookie` header, returning `None` if it has no cookie name.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Self {
+            name: name.to_owned(),
+            value: unquote(value),
+            expires: None,
+            max_age: None,
+            domain: None,
+            path: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        };
+
+        for attribute in parts {
+            let (name, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "expires" => cookie.expires = httpdate::parse_http_date(value).ok(),
+                "max-age" => {
+                    // zero or negative values expire the cookie immediately
+                    cookie.max_age = value
+                        .parse::<i64>()
+                        .ok()
+                        .map(|seconds| Duration::from_secs(seconds.max(0).unsigned_abs()))
+                }
+                "domain" if !value.is_empty() => {
+                    cookie.domain = Some(value.trim_start_matches('.').to_ascii_lowercase())
+                }
+                "path" if value.starts_with('/') => cookie.path = Some(value.to_owned()),
+                "secure" => cookie.secure = true,
+                "httponly" => cookie.http_only = true,
+                "samesite" => {
+                    cookie.same_site = match value.to_ascii_lowercase().as_str() {
+                        "strict" => Some(SameSite::Strict),
+                        "lax" => Some(SameSite::Lax),
+                        "none" => Some(SameSite::None),
+                        _ => None,
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(cookie)
+    }
+
+    /// Returns the name of the cookie.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the value of the cookie.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the `Expires` attribute, if any and valid.
+    pub const fn expires(&self) -> Option<SystemTime> {
+        self.expires
+    }
+
+    /// Returns the `Max-Age` attribute, if any and valid. Negative values are returned as zero.
+    pub const fn max_age(&self) -> Option<Duration> {
+        self.max_age
+    }
+
+    /// Returns the lowercase `Domain` attribute without leading dot, if any.
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    /// Returns the `Path` attribute, if any.
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// Returns `true` if the `Secure` attribute is present.
+    pub const fn secure(&self) -> bool {
+        self.secure
+    }
+
+    /// Returns `true` if the `HttpOnly` attribute is present.
+    pub const fn http_only(&self) -> bool {
+        self.http_only
+    }
+
+    /// Returns the `SameSite` attribute, if any and valid.
+    pub const fn same_site(&self) -> Option<SameSite> {
+        self.same_site
+    }
+
+    /// Returns `true` if the cookie expires with the browser session, i.e. has neither `Expires` nor `Max-Age`.
+    pub const fn is_session(&self) -> bool {
+        self.expires.is_none() && self.max_age.is_none()
+    }
+}
+
+/// A link of the `Link` header, like `<https://example.com/style.css>; rel=preload; as=style`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    target: String,
+    rels: Vec<String>,
+    params: Vec<(String, String)>,
+}
+
+impl Link {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (target, params) = value.strip_prefix('<')?.split_once('>')?;
+
+        let params = split_unquoted(params, ';')
+            .into_iter()
+            .filter_map(|param| {
+                let (name, value) = param.split_once('=').unwrap_or((param, ""));
+                let name = name.trim().to_ascii_lowercase();
+                (!name.is_empty()).then(|| (name, unquote(value)))
+            })
+            .collect::<Vec<(String, String)>>();
+        let rels = params
+            .iter()
+            .find(|(name, _)| name == "rel")
+            .map(|(_, rels)| {
+                rels.split_whitespace()
+                    .map(str::to_ascii_lowercase)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(Self {
+            target: target.trim().to_owned(),
+            rels,
+            params,
+        })
+    }
+
+    /// Returns the target uri as given, i.e. possibly relative to the url of the response.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns the lowercase relation types of the `rel` parameter.
+    pub fn rels(&self) -> &[String] {
+        &self.rels
+    }
+
+    /// Returns `true` if the link has the relation type `rel` (case-insensitive).
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.rels
+            .iter()
+            .any(|other| other.eq_ignore_ascii_case(rel))
+    }
+
+    /// Returns the unquoted value of the parameter `name` (case-insensitive), e.g. `as` or `type`, if any.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Splits `value` at `separator`, except within double quotes and angle brackets.
+fn split_unquoted(value: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted, mut bracketed, mut escaped) = (0, false, false, false);
+
+    for (idx, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' if !bracketed => quoted = !quoted,
+            '<' if !quoted => bracketed = true,
+            '>' if !quoted => bracketed = false,
+            c if c == separator && !quoted && !bracketed => {
+                parts.push(&value[start..idx]);
+                start = idx + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&value[start..]);
+
+    parts
+}
+
+/// Returns the trimmed `value` without surrounding double quotes and escapes.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    match value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        Some(quoted) => {
+            let mut unquoted = String::with_capacity(quoted.len());
+            let mut chars = quoted.chars();
+            while let Some(c) = chars.next() {
+                unquoted.extend(match c {
+                    '\\' => chars.next(),
+                    c => Some(c),
+                });
+            }
+            unquoted
+        }
+        None => value.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_unquoted, unquote, SameSite, SetCookie, TypedHeaders};
+    use reqwest::header::{
+        HeaderMap, HeaderValue, CACHE_CONTROL, CONTENT_LENGTH, CONTENT_SECURITY_POLICY,
+        CONTENT_TYPE, LAST_MODIFIED, LINK, SET_COOKIE,
+    };
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn headers(pairs: &[(reqwest::header::HeaderName, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.append(name, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn parse_typed_headers() {
+        let headers = headers(&[
+            (CONTENT_TYPE, "Text/HTML; Charset=\"ISO-8859-1\""),
+            (CONTENT_LENGTH, "1234"),
+            (LAST_MODIFIED, "Thu, 01 Jan 1970 00:01:40 GMT"),
+            (CACHE_CONTROL, "private, max-age=60"),
+            (CACHE_CONTROL, "no-cache=\"Set-Cookie, X-Foo\""),
+            (
+                CONTENT_SECURITY_POLICY,
+                "default-src 'self'; script-src 'self' https://cdn.test; script-src *, frame-ancestors 'none'",
+            ),
+            (
+                LINK,
+                "</style.css>; rel=preload; as=style, <https://example.com/page>; rel=\"canonical alternate\"",
+            ),
+        ]);
+        let headers = TypedHeaders::new(&headers);
+
+        let content_type = headers.content_type().unwrap();
+        assert_eq!(content_type.essence(), "text/html");
+        assert_eq!(
+            (content_type.type_(), content_type.subtype()),
+            ("text", "html")
+        );
+        assert_eq!(content_type.charset(), Some("iso-8859-1"));
+        assert!(content_type.is_html());
+        assert_eq!(headers.content_length(), Some(1234));
+        assert_eq!(
+            headers.last_modified(),
+            Some(UNIX_EPOCH + Duration::from_secs(100))
+        );
+
+        let cache_control = headers.cache_control();
+        assert!(cache_control.private() && cache_control.no_cache());
+        assert!(!cache_control.no_store());
+        assert_eq!(cache_control.max_age(), Some(Duration::from_secs(60)));
+        assert_eq!(cache_control.get("no-cache"), Some("Set-Cookie, X-Foo"));
+
+        let policies = headers.content_security_policy();
+        assert_eq!(policies.len(), 2);
+        assert_eq!(
+            policies[0].directive("script-src").unwrap(),
+            ["'self'", "https://cdn.test"]
+        );
+        assert_eq!(
+            policies[0].effective_directive("img-src").unwrap(),
+            ["'self'"]
+        );
+        assert_eq!(policies[0].effective_directive("frame-ancestors"), None);
+        assert_eq!(
+            policies[1].directive("frame-ancestors").unwrap(),
+            ["'none'"]
+        );
+        assert!(headers.content_security_policy_report_only().is_empty());
+
+        let links = headers.links();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].target(), "/style.css");
+        assert!(links[0].has_rel("preload"));
+        assert_eq!(links[0].param("as"), Some("style"));
+        assert_eq!(links[1].rels(), ["canonical", "alternate"]);
+
+        assert!(headers.strict_transport_security().is_none());
+        assert!(headers.set_cookies().is_empty());
+    }
+
+    #[test]
+    fn parse_set_cookie() {
+        let headers = headers(&[
+            (
+                SET_COOKIE,
+                "id=a3fWa; Expires=Thu, 21 Oct 2021 07:28:00 GMT; Domain=.Example.com; Path=/app; Secure; HttpOnly",
+            ),
+            (SET_COOKIE, "theme=\"dark\"; Max-Age=-1; SameSite=None"),
+            (SET_COOKIE, "=nameless"),
+            (SET_COOKIE, "session=1; samesite=strict"),
+        ]);
+        let cookies = TypedHeaders::new(&headers).set_cookies();
+        assert_eq!(cookies.len(), 3);
+
+        assert_eq!(cookies[0].name(), "id");
+        assert_eq!(cookies[0].value(), "a3fWa");
+        assert_eq!(
+            cookies[0].expires(),
+            httpdate::parse_http_date("Thu, 21 Oct 2021 07:28:00 GMT").ok()
+        );
+        assert_eq!(cookies[0].domain(), Some("example.com"));
+        assert_eq!(cookies[0].path(), Some("/app"));
+        assert!(cookies[0].secure() && cookies[0].http_only());
+        assert_eq!(cookies[0].same_site(), None);
+
+        assert_eq!(cookies[1].value(), "dark");
+        assert_eq!(cookies[1].max_age(), Some(Duration::ZERO));
+        assert_eq!(cookies[1].same_site(), Some(SameSite::None));
+        assert!(!cookies[1].is_session());
+
+        assert!(cookies[2].is_session());
+        assert_eq!(cookies[2].same_site(), Some(SameSite::Strict));
+        assert!(SetCookie::parse("no-equals-sign").is_none());
+
+        assert_eq!(
+            split_unquoted(r#"a="x,\"y", b"#, ','),
+            [r#"a="x,\"y""#, " b"]
+        );
+        assert_eq!(unquote(r#" "x\"y" "#), r#"x"y"#);
+    }
+}
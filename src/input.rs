@@ -1,7 +1,7 @@
 //! Module containing the [`Input`][Input] struct.
 
 use lazy_static::lazy_static;
-use scraper::node::Element;
+use scraper::{node::Element, ElementRef, Selector};
 use std::collections::HashMap;
 use thiserror::Error;
 
@@ -12,7 +12,7 @@ pub enum Error {
     #[error("Unnamed inputs are not supported!")]
     UnnamedInputError {},
 
-    /// Only `<input>` and `<button>` elements can be parsed.
+    /// Only `<input>`, `<button>`, `<select>` and `<textarea>` elements can be parsed.
     #[error("Html tag '{element_tag}' cannot be parsed to struct Input!")]
     UnsupportedElementTagError {
         /// The actual unparsable element tag.
@@ -91,6 +91,10 @@ pub enum InputType {
     Url,
     /// See <https://developer.mozilla.org/en-US/docs/Web/HTML/Element/input/week>
     Week,
+    /// See <https://developer.mozilla.org/en-US/docs/Web/HTML/Element/select>
+    Select,
+    /// See <https://developer.mozilla.org/en-US/docs/Web/HTML/Element/textarea>
+    TextArea,
 }
 
 lazy_static! {
@@ -122,13 +126,16 @@ lazy_static! {
     };
 }
 
-/// Struct [`Input`][Input] represents a parsed html form input element.
+/// Struct [`Input`][Input] represents a parsed html form control: an `<input>`, `<button>`, `<select>` or `<textarea>`
+/// element.
 ///
 /// It gives access to:
 /// * this input's type (`t()`);
 /// * this input's name (`name()`);
 /// * this input's value (`value()` / `set_value()`);
 /// * this input's other attributes (`attr()` / `set_attr()`);
+/// * the values of the options of a `<select>` element (`options()`);
+/// * the texts of the labels associated with this input (`labels()`);
 ///
 /// See the main docs of [crate `no_browser`][crate] for usage examples.
 #[derive(Debug)]
@@ -137,6 +144,8 @@ pub struct Input {
     name: String,
     value: Option<String>,
     attr: HashMap<String, String>,
+    options: Vec<String>,
+    labels: Vec<String>,
 }
 
 impl Input {
@@ -180,12 +189,32 @@ impl Input {
         prev
     }
 
-    pub(crate) fn parse(element: &Element) -> Result<Self> {
+    /// Returns the values of all options of a `<select>` element in document order, or an empty slice for other
+    /// controls. Use `set_value()` to select an option.
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    /// Returns the texts of all labels associated with this input element, i.e. `<label>` elements referencing this
+    /// input via `for` or wrapping it, as well as `aria-label` and `aria-labelledby` attributes.
+    pub fn labels(&self) -> &[String] {
+        &self.labels
+    }
+
+    pub(crate) fn with_labels(mut self, labels: Vec<String>) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    pub(crate) fn parse(element_ref: &ElementRef) -> Result<Self> {
+        let element = element_ref.value();
         let tag_name = element.name().to_lowercase();
 
         match tag_name.as_str() {
             "input" => Self::parse_input(element),
             "button" => Self::parse_button(element),
+            "select" => Self::parse_select(element_ref),
+            "textarea" => Self::parse_textarea(element_ref),
             _ => Err(Error::UnsupportedElementTagError {
                 element_tag: tag_name,
             }),
@@ -223,6 +252,37 @@ impl Input {
         Self::parse_element(element, t)
     }
 
+    /// Parses a `<select>` element, using the value of its first selected option (or its first option, like web
+    /// browsers) as value.
+    fn parse_select(element_ref: &ElementRef) -> Result<Self> {
+        let mut input = Self::parse_element(element_ref.value(), InputType::Select)?;
+
+        let option_value = |option: &ElementRef| match option.value().attr("value") {
+            Some(value) => value.to_owned(),
+            None => option.text().collect::<String>().trim().to_owned(),
+        };
+        let options = element_ref
+            .select(&Selector::parse("option").unwrap())
+            .collect::<Vec<ElementRef>>();
+        let selected = options
+            .iter()
+            .find(|option| option.value().attr("selected").is_some())
+            .or(options.first());
+
+        input.value = selected.map(option_value);
+        input.options = options.iter().map(option_value).collect();
+
+        Ok(input)
+    }
+
+    /// Parses a `<textarea>` element, using its text content as value.
+    fn parse_textarea(element_ref: &ElementRef) -> Result<Self> {
+        let mut input = Self::parse_element(element_ref.value(), InputType::TextArea)?;
+        input.value = Some(element_ref.text().collect());
+
+        Ok(input)
+    }
+
     fn parse_element(element: &Element, t: InputType) -> Result<Self> {
         let name = element
             .attr("name")
@@ -240,6 +300,8 @@ impl Input {
             name,
             value,
             attr,
+            options: Vec::new(),
+            labels: Vec::new(),
         })
     }
 }
@@ -285,7 +347,7 @@ mod tests {
         let selector = Selector::parse("input").unwrap();
         let element = html.select(&selector).next().unwrap();
 
-        let mut input = Input::parse(element.value())?;
+        let mut input = Input::parse(&element)?;
 
         assert_eq!(input.t(), expected_type);
         assert_eq!(input.name(), format!("the_{input_type}"));
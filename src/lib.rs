@@ -82,9 +82,36 @@
 //! ```
 
 pub mod browser;
+pub mod cache;
+pub mod cassette;
+pub mod crawler;
+pub mod extract;
+pub mod feed;
 pub mod form;
+pub mod har;
+pub mod headers;
 pub mod input;
+pub mod link_checker;
+pub mod metadata;
+pub mod middleware;
+pub mod mock;
 pub mod page;
+pub mod query;
+pub mod rate_limit;
+pub mod retry;
+pub mod robots;
+pub mod security;
+pub mod sitemap;
+pub mod snapshot;
+pub mod status;
+pub mod structured_data;
+pub mod table;
+#[cfg(feature = "test-server")]
+pub mod test_server;
+pub mod transport;
+pub mod xpath;
+
+mod xml;
 
 pub use browser::Browser;
 pub use browser::Result;
@@ -0,0 +1,627 @@
+//! Module containing the [`LinkChecker`][LinkChecker] to find broken references on pages loaded by a
+//! [`Browser`][Browser].
+//!
+//! A link checker collects all http(s) references of a [`Page`][Page]: links (`<a href>`, `<area href>`), images
+//! (`<img src>`, `srcset` of `<img>` and `<source>`), `<link href>` (stylesheets, icons, ...), scripts and iframes.
+//! Each distinct target is requested with `HEAD` first, falling back to `GET` if the server rejects `HEAD` or answers
+//! with an error. Links with a `#fragment` are additionally checked for a matching `id` (or `<a name>`) on the target
+//! page, which is then loaded with `GET`; in-page fragments are checked without any request.
+//!
+//! The results of every target are remembered, so checking a whole site page by page, e.g. with a
+//! [`Crawler`][crate::crawler::Crawler], requests every target only once.
+//!
+//! ```no_run
+//! use no_browser::{crawler::Crawler, link_checker::LinkChecker, Browser};
+//!
+//! let browser = Browser::builder().finish()?;
+//! let checker = LinkChecker::new(&browser).concurrency(8);
+//!
+//! for page in Crawler::builder(&browser).seed("https://example.com/").finish()? {
+//!     let report = checker.check_page(&page?);
+//!     for broken in report.broken() {
+//!         println!(
+//!             "{}: {} {:?} {:?}",
+//!             report.url(),
+//!             broken.reference().url(),
+//!             broken.status(),
+//!             broken.error()
+//!         );
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{
+    browser::Browser,
+    page::{self, Page},
+    transport::Request,
+};
+use percent_encoding::percent_decode_str;
+use reqwest::{header::CONTENT_TYPE, Method, StatusCode, Url};
+use scraper::{Html, Selector};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    thread,
+};
+
+/// The kind of element a [`Reference`][Reference] was found on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReferenceKind {
+    /// A hyperlink, `<a href>` or `<area href>`.
+    Anchor,
+    /// An image, `<img src>` or a candidate of the `srcset` of `<img>` or `<source>`.
+    Image,
+    /// A linked resource, `<link href>`, e.g. a stylesheet or an icon.
+    Link,
+    /// A script, `<script src>`.
+    Script,
+    /// An embedded document, `<iframe src>`.
+    Frame,
+}
+
+/// A reference from a page to another resource.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    kind: ReferenceKind,
+    element: String,
+    attribute: &'static str,
+    url: Url,
+}
+
+impl Reference {
+    /// Returns the kind of element the reference was found on.
+    pub const fn kind(&self) -> ReferenceKind {
+        self.kind
+    }
+
+    /// Returns the name of the element the reference was found on, e.g. `img`.
+    pub fn element(&self) -> &str {
+        &self.element
+    }
+
+    /// Returns the name of the attribute the reference was found in, e.g. `srcset`.
+    pub const fn attribute(&self) -> &'static str {
+        self.attribute
+    }
+
+    /// Returns the absolute url of the reference, resolved against the page url or its `<base href>`.
+    pub const fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+/// A redirect followed while checking a [`Reference`][Reference].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Redirect {
+    url: Url,
+    status: StatusCode,
+}
+
+impl Redirect {
+    /// Returns the url that answered with the redirect.
+    pub const fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the redirect status, e.g. `301 Moved Permanently`.
+    pub const fn status(&self) -> StatusCode {
+        self.status
+    }
+}
+
+/// The result of checking a single [`Reference`][Reference].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkResult {
+    reference: Reference,
+    status: Option<StatusCode>,
+    redirects: Vec<Redirect>,
+    final_url: Url,
+    fragment_found: Option<bool>,
+    error: Option<String>,
+}
+
+impl LinkResult {
+    /// Returns the checked reference.
+    pub const fn reference(&self) -> &Reference {
+        &self.reference
+    }
+
+    /// Returns the final status after following all redirects, or `None` if the request failed.
+    pub const fn status(&self) -> Option<StatusCode> {
+        self.status
+    }
+
+    /// Returns the redirects followed, in order. Empty if the reference was not redirected.
+    pub fn redirects(&self) -> &[Redirect] {
+        &self.redirects
+    }
+
+    /// Returns the url that answered after following all redirects.
+    pub const fn final_url(&self) -> &Url {
+        &self.final_url
+    }
+
+    /// Returns whether the `#fragment` of the reference exists on the target page, or `None` if the reference has no
+    /// fragment or fragments were not checked.
+    pub const fn fragment_found(&self) -> Option<bool> {
+        self.fragment_found
+    }
+
+    /// Returns the reason the request failed, e.g. a connection error, if any.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Returns `true` if the request failed, did not end with a success status or the fragment was not found.
+    pub fn is_broken(&self) -> bool {
+        !self.status.is_some_and(|status| status.is_success()) || self.fragment_found == Some(false)
+    }
+}
+
+/// The results of checking all references of a page with `LinkChecker::check_page()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkReport {
+    url: Url,
+    results: Vec<LinkResult>,
+}
+
+impl LinkReport {
+    /// Returns the url of the checked page.
+    pub const fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the results of all references in document order.
+    pub fn results(&self) -> &[LinkResult] {
+        &self.results
+    }
+
+    /// Returns the results of all broken references in document order.
+    pub fn broken(&self) -> impl Iterator<Item = &LinkResult> {
+        self.results.iter().filter(|result| result.is_broken())
+    }
+
+    /// Returns `true` if no reference is broken.
+    pub fn is_ok(&self) -> bool {
+        self.broken().next().is_none()
+    }
+}
+
+/// The outcome of requesting a target url, shared by all references to it regardless of their fragment.
+#[derive(Debug, Clone)]
+struct Target {
+    status: Option<StatusCode>,
+    redirects: Vec<Redirect>,
+    final_url: Url,
+    error: Option<String>,
+    /// The ids and anchor names of the target, if it was loaded with `GET` and is an html page.
+    anchors: Option<HashSet<String>>,
+}
+
+/// A checker for the references on pages. See the [module documentation][crate::link_checker] for details.
+#[derive(Debug)]
+pub struct LinkChecker<'a> {
+    browser: &'a Browser,
+    concurrency: usize,
+    check_fragments: bool,
+    targets: Mutex<HashMap<Url, Target>>,
+}
+
+impl<'a> LinkChecker<'a> {
+    /// Returns a link checker sending its requests with the given `browser`.
+    pub fn new(browser: &'a Browser) -> Self {
+        Self {
+            browser,
+            concurrency: 4,
+            check_fragments: true,
+            targets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the maximum number of concurrent requests. Defaults to 4.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Sets whether the `#fragment` of links is checked against the target page. Defaults to `true`.
+    pub const fn check_fragments(mut self, check_fragments: bool) -> Self {
+        self.check_fragments = check_fragments;
+        self
+    }
+
+    /// Returns all http(s) references of the html `page` in document order, resolved against its `<base href>`, if
+    /// any. `<link>` elements merely hinting at origins (`rel="preconnect"` or `rel="dns-prefetch"`) are skipped.
+    pub fn references(page: &Page) -> Vec<Reference> {
+        let selector = Selector::parse(
+            "a[href], area[href], img[src], img[srcset], source[srcset], link[href], script[src], iframe[src]",
+        )
+        .unwrap();
+        let base = page.base_url();
+        let mut references = Vec::new();
+
+        for element in page.html().select(&selector) {
+            let element = element.value();
+            let (kind, attributes): (ReferenceKind, &[&'static str]) = match element.name() {
+                "a" | "area" => (ReferenceKind::Anchor, &["href"]),
+                "img" => (ReferenceKind::Image, &["src", "srcset"]),
+                "source" => (ReferenceKind::Image, &["srcset"]),
+                "link" => (ReferenceKind::Link, &["href"]),
+                "script" => (ReferenceKind::Script, &["src"]),
+                _ => (ReferenceKind::Frame, &["src"]),
+            };
+
+            let hint = element.attr("rel").is_some_and(|rel| {
+                rel.split_whitespace().any(|rel| {
+                    rel.eq_ignore_ascii_case("preconnect")
+                        || rel.eq_ignore_ascii_case("dns-prefetch")
+                })
+            });
+            if hint {
+                continue;
+            }
+
+            for &attribute in attributes {
+                let Some(value) = element.attr(attribute) else {
+                    continue;
+                };
+                references.extend(
+                    page::attribute_urls(attribute, value)
+                        .into_iter()
+                        .filter_map(|value| base.join(value).ok())
+                        .filter(|url| matches!(url.scheme(), "http" | "https"))
+                        .map(|url| Reference {
+                            kind,
+                            element: element.name().to_owned(),
+                            attribute,
+                            url,
+                        }),
+                );
+            }
+        }
+
+        references
+    }
+
+    /// Checks all references of the html `page`. Failing requests are reported per reference, so checking never
+    /// fails as a whole.
+    pub fn check_page(&self, page: &Page) -> LinkReport {
+        let references = Self::references(page);
+        let own_url = without_fragment(page.url());
+        let own = Target {
+            status: Some(*page.status()),
+            redirects: Vec::new(),
+            final_url: own_url.clone(),
+            error: None,
+            anchors: Some(anchors(page.html())),
+        };
+
+        // request each missing target once, with GET if its anchors are needed
+        let mut wanted = HashMap::<Url, bool>::new();
+        for reference in &references {
+            let url = without_fragment(&reference.url);
+            if url != own_url {
+                *wanted.entry(url).or_default() |= self.needs_anchors(reference);
+            }
+        }
+        let wanted = {
+            let targets = self.targets.lock().unwrap();
+            let mut wanted = wanted
+                .into_iter()
+                .filter(|(url, needs_anchors)| {
+                    targets.get(url).is_none_or(|target| {
+                        *needs_anchors && target.anchors.is_none() && target.error.is_none()
+                    })
+                })
+                .collect::<Vec<(Url, bool)>>();
+            wanted.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+            wanted
+        };
+
+        for batch in wanted.chunks(self.concurrency) {
+            let targets = thread::scope(|scope| {
+                let handles = batch
+                    .iter()
+                    .map(|(url, needs_anchors)| scope.spawn(|| self.probe(url, *needs_anchors)))
+                    .collect::<Vec<_>>();
+
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .collect::<Vec<_>>()
+            });
+
+            let mut cache = self.targets.lock().unwrap();
+            for ((url, _), target) in batch.iter().zip(targets) {
+                cache.insert(url.clone(), target);
+            }
+        }
+
+        let targets = self.targets.lock().unwrap();
+        let results = references
+            .into_iter()
+            .map(|reference| {
+                let url = without_fragment(&reference.url);
+                let target = match url == own_url {
+                    true => &own,
+                    false => &targets[&url],
+                };
+
+                let fragment_found = match self.needs_anchors(&reference) {
+                    true => target.anchors.as_ref().map(|anchors| {
+                        // fragments are percent-encoded in urls, ids are not
+                        let fragment = reference.url.fragment().unwrap_or_default();
+                        let decoded = percent_decode_str(fragment).decode_utf8_lossy();
+                        fragment.eq_ignore_ascii_case("top")
+                            || anchors.contains(fragment)
+                            || anchors.contains(decoded.as_ref())
+                    }),
+                    false => None,
+                };
+
+                LinkResult {
+                    reference,
+                    status: target.status,
+                    redirects: target.redirects.clone(),
+                    final_url: target.final_url.clone(),
+                    fragment_found,
+                    error: target.error.clone(),
+                }
+            })
+            .collect();
+
+        LinkReport {
+            url: page.url().clone(),
+            results,
+        }
+    }
+
+    /// Returns `true` if the fragment of `reference` is to be checked against the anchors of its target.
+    fn needs_anchors(&self, reference: &Reference) -> bool {
+        self.check_fragments
+            && reference.kind == ReferenceKind::Anchor
+            && reference
+                .url
+                .fragment()
+                .is_some_and(|fragment| !fragment.is_empty())
+    }
+
+    /// Requests `url` with `HEAD`, falling back to `GET` if that fails or if the anchors of the target are needed.
+    fn probe(&self, url: &Url, needs_anchors: bool) -> Target {
+        if !needs_anchors {
+            let target = self.request(Method::HEAD, url);
+            if target.status.is_some_and(|status| status.is_success()) {
+                return target;
+            }
+        }
+
+        self.request(Method::GET, url)
+    }
+
+    fn request(&self, method: Method, url: &Url) -> Target {
+        let mut redirects = Vec::new();
+        let result = self
+            .browser
+            .send_with_redirects(Request::new(method.clone(), url.clone()), &mut redirects);
+        let redirects = redirects
+            .into_iter()
+            .map(|(url, status)| Redirect { url, status })
+            .collect();
+
+        match result {
+            Ok((request, response, _)) => {
+                let is_html = response
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|content_type| content_type.to_str().ok())
+                    .is_none_or(|content_type| content_type.contains("html"));
+
+                Target {
+                    status: Some(response.status()),
+                    redirects,
+                    final_url: request.url().clone(),
+                    error: None,
+                    anchors: (method == Method::GET && is_html).then(|| {
+                        anchors(&Html::parse_document(&String::from_utf8_lossy(
+                            response.body(),
+                        )))
+                    }),
+                }
+            }
+            Err(error) => Target {
+                status: None,
+                redirects,
+                final_url: url.clone(),
+                error: Some(error.to_string()),
+                anchors: None,
+            },
+        }
+    }
+}
+
+fn without_fragment(url: &Url) -> Url {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url
+}
+
+/// Returns the fragment targets of `html`: all element ids and the names of `<a name>` elements.
+fn anchors(html: &Html) -> HashSet<String> {
+    html.select(&Selector::parse("[id], a[name]").unwrap())
+        .flat_map(|element| [element.value().id(), element.value().attr("name")])
+        .flatten()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LinkChecker;
+    use crate::{
+        browser::Browser,
+        mock::{self, MockTransport, RequestLog},
+        page::Page,
+        transport::Response,
+    };
+    use reqwest::{Method, StatusCode};
+
+    static HTML: &str = r##"
+        <html>
+        <head>
+            <link rel="stylesheet" href="/style.css">
+            <link rel="preconnect" href="https://fonts.test">
+            <script src="/app.js"></script>
+        </head>
+        <body>
+            <h1 id="top-heading">Links</h1>
+            <a href="#top-heading">up</a>
+            <a href="#nowhere">nowhere</a>
+            <a href="/docs#install">install</a>
+            <a href="/docs#missing">missing section</a>
+            <a href="/old">moved</a>
+            <a href="/gone">gone</a>
+            <a href="mailto:info@site.test">mail</a>
+            <img src="/logo.png" srcset="/logo.png 1x, /logo@2x.png 2x">
+            <iframe src="/widget"></iframe>
+        </body>
+        </html>"##;
+
+    fn browser() -> (Browser, RequestLog) {
+        let transport = MockTransport::new()
+            .respond(Method::HEAD, "/style.css", Response::new(StatusCode::OK))
+            .respond(Method::HEAD, "/app.js", Response::new(StatusCode::OK))
+            .respond(
+                Method::GET,
+                "/docs",
+                mock::html("<h2 id='install'>Install</h2>"),
+            )
+            .respond(Method::HEAD, "/docs", Response::new(StatusCode::OK))
+            .respond(Method::HEAD, "/old", mock::redirect("/docs"))
+            .respond(
+                Method::HEAD,
+                "/logo.png",
+                Response::new(StatusCode::METHOD_NOT_ALLOWED),
+            )
+            .respond(Method::GET, "/logo.png", Response::new(StatusCode::OK))
+            .respond(Method::HEAD, "/logo@2x.png", Response::new(StatusCode::OK))
+            .respond(Method::HEAD, "/widget", Response::new(StatusCode::OK));
+        let log = transport.request_log();
+
+        (
+            Browser::builder().transport(transport).finish().unwrap(),
+            log,
+        )
+    }
+
+    #[test]
+    fn collect_references() {
+        let page = Page::from_html("https://site.test/page", HTML).unwrap();
+
+        let references = LinkChecker::references(&page)
+            .into_iter()
+            .map(|reference| {
+                format!(
+                    "{:?} {}[{}] {}",
+                    reference.kind(),
+                    reference.element(),
+                    reference.attribute(),
+                    reference.url().path()
+                )
+            })
+            .collect::<Vec<String>>();
+
+        assert_eq!(
+            references,
+            [
+                "Link link[href] /style.css",
+                "Script script[src] /app.js",
+                "Anchor a[href] /page",
+                "Anchor a[href] /page",
+                "Anchor a[href] /docs",
+                "Anchor a[href] /docs",
+                "Anchor a[href] /old",
+                "Anchor a[href] /gone",
+                "Image img[src] /logo.png",
+                "Image img[srcset] /logo.png",
+                "Image img[srcset] /logo@2x.png",
+                "Frame iframe[src] /widget",
+            ]
+        );
+    }
+
+    #[test]
+    fn check_page_references() {
+        let (browser, log) = browser();
+        let checker = LinkChecker::new(&browser).concurrency(2);
+        let page = Page::from_html("https://site.test/page", HTML).unwrap();
+
+        let report = checker.check_page(&page);
+        assert_eq!(report.url().as_str(), "https://site.test/page");
+        assert_eq!(report.results().len(), 12);
+
+        let broken = report
+            .broken()
+            .map(|result| result.reference().url().to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            broken,
+            [
+                "https://site.test/page#nowhere",
+                "https://site.test/docs#missing",
+                "https://site.test/gone"
+            ]
+        );
+        assert!(!report.is_ok());
+
+        let results = report.results();
+        assert_eq!(results[2].fragment_found(), Some(true));
+        assert_eq!(results[3].fragment_found(), Some(false));
+        assert_eq!(results[5].status(), Some(StatusCode::OK));
+        assert_eq!(results[5].fragment_found(), Some(false));
+        assert_eq!(results[6].redirects().len(), 1);
+        assert_eq!(results[6].redirects()[0].url().path(), "/old");
+        assert_eq!(results[6].redirects()[0].status(), StatusCode::SEE_OTHER);
+        assert_eq!(results[6].final_url().path(), "/docs");
+        assert_eq!(results[7].status(), Some(StatusCode::NOT_FOUND));
+        assert_eq!(results[8].status(), Some(StatusCode::OK));
+        assert_eq!(results[0].fragment_found(), None);
+
+        // every target is requested once, retrying /logo.png and /gone with GET
+        let mut requests = log
+            .requests()
+            .iter()
+            .map(|request| format!("{} {}", request.method(), request.url().path()))
+            .collect::<Vec<String>>();
+        requests.sort();
+        assert_eq!(
+            requests,
+            [
+                "GET /docs",
+                "GET /gone",
+                "GET /logo.png",
+                "HEAD /app.js",
+                "HEAD /docs",
+                "HEAD /gone",
+                "HEAD /logo.png",
+                "HEAD /logo@2x.png",
+                "HEAD /old",
+                "HEAD /style.css",
+                "HEAD /widget",
+            ]
+        );
+
+        checker.check_page(&page);
+        assert_eq!(log.len(), 11);
+
+        // fragments are matched percent-decoded
+        let page = Page::from_html(
+            "https://site.test/menu",
+            "<h2 id='café'>Café</h2><a href='#caf%C3%A9'>encoded</a><a href='#café'>raw</a>",
+        )
+        .unwrap();
+        let report = checker.check_page(&page);
+        assert_eq!(report.results().len(), 2);
+        assert!(report.is_ok());
+    }
+}
@@ -0,0 +1,388 @@
+//! Module containing the [`Metadata`][Metadata] struct.
+
+use crate::query;
+use reqwest::Url;
+use scraper::{Html, Selector};
+use std::collections::BTreeMap;
+
+/// Struct [`Link`][Link] represents a `<link>` element of a page, e.g. an alternate language version, a favicon or a
+/// feed. The `href` is resolved against the page url (or `<base href>`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    rel: String,
+    href: Url,
+    hreflang: Option<String>,
+    media_type: Option<String>,
+    title: Option<String>,
+    sizes: Option<String>,
+}
+
+impl Link {
+    /// Returns the `rel` attribute, lower-cased.
+    pub fn rel(&self) -> &str {
+        &self.rel
+    }
+
+    /// Returns the resolved `href` attribute.
+    pub const fn href(&self) -> &Url {
+        &self.href
+    }
+
+    /// Returns the `hreflang` attribute, if any.
+    pub fn hreflang(&self) -> Option<&str> {
+        self.hreflang.as_deref()
+    }
+
+    /// Returns the `type` attribute, if any.
+    pub fn media_type(&self) -> Option<&str> {
+        self.media_type.as_deref()
+    }
+
+    /// Returns the `title` attribute, if any.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the `sizes` attribute, if any.
+    pub fn sizes(&self) -> Option<&str> {
+        self.sizes.as_deref()
+    }
+}
+
+/// Struct [`Metadata`][Metadata] is a typed view on the metadata of a [`Page`][crate::page::Page], as commonly checked
+/// for SEO. All urls are resolved against the page url, respecting `<base href>`.
+///
+/// It gives access to:
+/// * the document `<title>` (`title()`) and language (`language()`);
+/// * the meta tags `description` (`description()`), `keywords` (`keywords()`) and `robots` (`robots()`);
+/// * the canonical url (`canonical()`) and alternate language versions (`hreflang_links()`);
+/// * [OpenGraph](https://ogp.me/) properties (`open_graph()`, `open_graph_all()`, `open_graph_properties()`);
+/// * [Twitter card](https://developer.x.com/en/docs/x-for-websites/cards/overview/markup) properties (`twitter()`,
+///   `twitter_properties()`);
+/// * favicons (`icons()`) and RSS / Atom feeds (`feeds()`).
+///
+/// ```no_run
+/// # let page: Option<no_browser::page::Page> = None;
+/// # let page = page.unwrap();
+/// let metadata = page.metadata();
+/// assert!(metadata.title().is_some());
+/// assert!(!metadata.robots().iter().any(|directive| directive == "noindex"));
+/// assert_eq!(metadata.canonical(), Some(page.url()));
+/// assert_eq!(metadata.open_graph("type"), Some("article"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    title: Option<String>,
+    language: Option<String>,
+    description: Option<String>,
+    keywords: Vec<String>,
+    robots: Vec<String>,
+    canonical: Option<Url>,
+    hreflang_links: Vec<Link>,
+    open_graph: BTreeMap<String, Vec<String>>,
+    twitter: BTreeMap<String, Vec<String>>,
+    icons: Vec<Link>,
+    feeds: Vec<Link>,
+}
+
+const URL_PROPERTIES: [&str; 10] = [
+    "og:url",
+    "og:image",
+    "og:image:url",
+    "og:image:secure_url",
+    "og:video",
+    "og:video:url",
+    "og:audio",
+    "twitter:image",
+    "twitter:image:src",
+    "twitter:player",
+];
+
+const FEED_TYPES: [&str; 4] = [
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/feed+json",
+    "application/json",
+];
+
+impl Metadata {
+    pub(crate) fn parse(html: &Html, url: &Url) -> Self {
+        let select = |selectors: &str| {
+            let selector = Selector::parse(selectors).unwrap();
+            html.select(&selector).collect::<Vec<_>>()
+        };
+
+        let base = select("base[href]")
+            .first()
+            .and_then(|base| url.join(base.value().attr("href").unwrap().trim()).ok())
+            .unwrap_or_else(|| url.clone());
+        let resolve = |href: &str| base.join(href.trim()).ok();
+
+        let mut metadata = Self {
+            title: select("title")
+                .first()
+                .map(|title| query::normalize(&query::text_content(title))),
+            language: html
+                .root_element()
+                .value()
+                .attr("lang")
+                .map(|lang| lang.trim().to_owned())
+                .filter(|lang| !lang.is_empty()),
+            ..Self::default()
+        };
+
+        for meta in select("meta[content]") {
+            let e = meta.value();
+            let content = e.attr("content").unwrap().trim();
+            let Some(key) = e.attr("property").or_else(|| e.attr("name")) else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+
+            match key.as_str() {
+                "description" if metadata.description.is_none() => {
+                    metadata.description = Some(content.to_owned())
+                }
+                "keywords" => metadata.keywords.extend(split_list(content)),
+                "robots" => metadata
+                    .robots
+                    .extend(split_list(content).map(|directive| directive.to_ascii_lowercase())),
+                _ => {
+                    let (map, name) = if let Some(name) = key.strip_prefix("og:") {
+                        (&mut metadata.open_graph, name)
+                    } else if let Some(name) = key.strip_prefix("twitter:") {
+                        (&mut metadata.twitter, name)
+                    } else {
+                        continue;
+                    };
+                    let value = if URL_PROPERTIES.contains(&key.as_str()) {
+                        match resolve(content) {
+                            Some(url) => url.to_string(),
+                            None => content.to_owned(),
+                        }
+                    } else {
+                        content.to_owned()
+                    };
+                    map.entry(name.to_owned()).or_default().push(value);
+                }
+            }
+        }
+
+        for link in select("link[rel][href]") {
+            let e = link.value();
+            let Some(href) = resolve(e.attr("href").unwrap()) else {
+                continue;
+            };
+            let attr = |name: &str| e.attr(name).map(|v| v.trim().to_owned());
+            let rels = e.attr("rel").unwrap().to_ascii_lowercase();
+            let rels = rels.split_ascii_whitespace().collect::<Vec<_>>();
+            let link = Link {
+                rel: rels.join(" "),
+                href,
+                hreflang: attr("hreflang"),
+                media_type: attr("type").map(|t| t.to_ascii_lowercase()),
+                title: attr("title"),
+                sizes: attr("sizes"),
+            };
+
+            if rels.contains(&"canonical") && metadata.canonical.is_none() {
+                metadata.canonical = Some(link.href.clone());
+            }
+            if rels.contains(&"icon") || rels.contains(&"apple-touch-icon") {
+                metadata.icons.push(link.clone());
+            }
+            if rels.contains(&"alternate") {
+                if link.hreflang.is_some() {
+                    metadata.hreflang_links.push(link);
+                } else if FEED_TYPES.contains(&link.media_type().unwrap_or_default()) {
+                    metadata.feeds.push(link);
+                }
+            }
+        }
+
+        metadata
+    }
+
+    /// Returns the whitespace-normalized text of the `<title>` element, if any.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the `lang` attribute of the `<html>` element, if any.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// Returns the content of `<meta name="description">`, if any.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Returns the comma-separated entries of `<meta name="keywords">`.
+    pub fn keywords(&self) -> &[String] {
+        &self.keywords
+    }
+
+    /// Returns the lower-cased, comma-separated directives of `<meta name="robots">`, e.g. `noindex`.
+    pub fn robots(&self) -> &[String] {
+        &self.robots
+    }
+
+    /// Returns the url of `<link rel="canonical">`, if any.
+    pub const fn canonical(&self) -> Option<&Url> {
+        self.canonical.as_ref()
+    }
+
+    /// Returns all `<link rel="alternate" hreflang="...">` elements.
+    pub fn hreflang_links(&self) -> &[Link] {
+        &self.hreflang_links
+    }
+
+    /// Returns the first value of the given OpenGraph property, given without the `og:` prefix, e.g. `image`.
+    pub fn open_graph(&self, property: &str) -> Option<&str> {
+        Self::first(&self.open_graph, property)
+    }
+
+    /// Returns all values of the given OpenGraph property, given without the `og:` prefix, e.g. `image`.
+    pub fn open_graph_all(&self, property: &str) -> Vec<&str> {
+        Self::all(&self.open_graph, property)
+    }
+
+    /// Returns all OpenGraph properties, keyed by name without the `og:` prefix.
+    pub const fn open_graph_properties(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.open_graph
+    }
+
+    /// Returns the first value of the given Twitter card property, given without the `twitter:` prefix, e.g. `card`.
+    pub fn twitter(&self, property: &str) -> Option<&str> {
+        Self::first(&self.twitter, property)
+    }
+
+    /// Returns all Twitter card properties, keyed by name without the `twitter:` prefix.
+    pub const fn twitter_properties(&self) -> &BTreeMap<String, Vec<String>> {
+        &self.twitter
+    }
+
+    /// Returns all `<link rel="icon">` and `<link rel="apple-touch-icon">` elements.
+    pub fn icons(&self) -> &[Link] {
+        &self.icons
+    }
+
+    /// Returns all `<link rel="alternate">` elements pointing to RSS, Atom or JSON feeds.
+    pub fn feeds(&self) -> &[Link] {
+        &self.feeds
+    }
+
+    fn first<'a>(map: &'a BTreeMap<String, Vec<String>>, property: &str) -> Option<&'a str> {
+        map.get(&property.to_ascii_lowercase())
+            .and_then(|values| values.first())
+            .map(String::as_str)
+    }
+
+    fn all<'a>(map: &'a BTreeMap<String, Vec<String>>, property: &str) -> Vec<&'a str> {
+        map.get(&property.to_ascii_lowercase())
+            .map(|values| values.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+}
+
+fn split_list(content: &str) -> impl Iterator<Item = String> + '_ {
+    content
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Metadata;
+    use reqwest::Url;
+    use scraper::Html;
+
+    static PAGE_001: &str = r#"
+        <html lang="en">
+            <head>
+                <base href="/blog/">
+                <title>  Ferris   goes  West </title>
+                <meta name="description" content="A crab's journey.">
+                <meta name="keywords" content="rust, crab, , travel">
+                <meta name="robots" content="NoIndex, follow">
+                <meta property="og:title" content="Ferris goes West">
+                <meta property="og:image" content="img/ferris.png">
+                <meta property="og:image" content="https://cdn.example/ferris-2.png">
+                <meta name="twitter:card" content="summary_large_image">
+                <meta name="twitter:image" content="//cdn.example/card.png">
+                <link rel="canonical" href="ferris-goes-west">
+                <link rel="alternate" hreflang="de" href="/de/blog/ferris-geht-nach-westen">
+                <link rel="alternate" type="application/rss+xml" title="Blog" href="feed.xml">
+                <link rel="Shortcut Icon" href="/favicon.ico">
+                <link rel="apple-touch-icon" sizes="180x180" href="/apple-touch-icon.png">
+                <link rel="stylesheet" href="style.css">
+            </head>
+            <body></body>
+        </html>
+    "#;
+
+    #[test]
+    fn parse_metadata() {
+        let url = Url::parse("https://example.com/blog/2024/ferris?ref=home").unwrap();
+        let metadata = Metadata::parse(&Html::parse_document(PAGE_001), &url);
+
+        assert_eq!(metadata.title(), Some("Ferris goes West"));
+        assert_eq!(metadata.language(), Some("en"));
+        assert_eq!(metadata.description(), Some("A crab's journey."));
+        assert_eq!(metadata.keywords(), ["rust", "crab", "travel"]);
+        assert_eq!(metadata.robots(), ["noindex", "follow"]);
+        assert_eq!(
+            metadata.canonical().map(Url::as_str),
+            Some("https://example.com/blog/ferris-goes-west")
+        );
+
+        assert_eq!(metadata.hreflang_links().len(), 1);
+        assert_eq!(metadata.hreflang_links()[0].hreflang(), Some("de"));
+        assert_eq!(
+            metadata.hreflang_links()[0].href().as_str(),
+            "https://example.com/de/blog/ferris-geht-nach-westen"
+        );
+
+        assert_eq!(metadata.open_graph("title"), Some("Ferris goes West"));
+        assert_eq!(
+            metadata.open_graph_all("image"),
+            [
+                "https://example.com/blog/img/ferris.png",
+                "https://cdn.example/ferris-2.png"
+            ]
+        );
+        assert_eq!(metadata.twitter("card"), Some("summary_large_image"));
+        assert_eq!(
+            metadata.twitter("image"),
+            Some("https://cdn.example/card.png")
+        );
+
+        let icons: Vec<&str> = metadata.icons().iter().map(|i| i.href().as_str()).collect();
+        assert_eq!(
+            icons,
+            [
+                "https://example.com/favicon.ico",
+                "https://example.com/apple-touch-icon.png"
+            ]
+        );
+        assert_eq!(metadata.icons()[1].sizes(), Some("180x180"));
+
+        assert_eq!(metadata.feeds().len(), 1);
+        assert_eq!(metadata.feeds()[0].title(), Some("Blog"));
+        assert_eq!(
+            metadata.feeds()[0].href().as_str(),
+            "https://example.com/blog/feed.xml"
+        );
+    }
+
+    #[test]
+    fn parse_empty_metadata() {
+        let url = Url::parse("https://example.com/").unwrap();
+        let metadata = Metadata::parse(&Html::parse_document("<p>hi</p>"), &url);
+
+        assert_eq!(metadata, Metadata::default());
+    }
+}
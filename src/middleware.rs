@@ -0,0 +1,210 @@
+//! Module containing the [`Middleware`][Middleware] trait to intercept the requests and responses of a
+//! [`Browser`][crate::Browser].
+//!
+//! Middlewares form a chain attached with `BrowserBuilder::middleware()`, the first one added being the outermost. Each
+//! middleware receives every outgoing request, including the ones following redirects, after cookies have been added.
+//! It may modify the request before passing it on with [`Next::run()`][Next::run], modify the returned response before
+//! it is handled by the browser (cookies, redirects, `Page` building), or short-circuit the chain by returning a
+//! synthetic response without calling `next` at all.
+//!
+//! ```
+//! use no_browser::{
+//!     middleware,
+//!     mock::{self, MockTransport},
+//!     Browser,
+//! };
+//! use reqwest::{header::HeaderValue, Method};
+//!
+//! let transport = MockTransport::new().route(Method::GET, "/", |request| {
+//!     mock::html(request.headers()["x-csrf-token"].to_str().unwrap())
+//! });
+//!
+//! let browser = Browser::builder()
+//!     .transport(transport)
+//!     .middleware(middleware::map_request(|request| {
+//!         request
+//!             .headers_mut()
+//!             .insert("x-csrf-token", HeaderValue::from_static("secret"));
+//!     }))
+//!     .middleware(middleware::from_fn(|request, next| {
+//!         if request.url().path() == "/maintenance" {
+//!             return Ok(mock::html("Down for maintenance"));
+//!         }
+//!         next.run(request)
+//!     }))
+//!     .finish()?;
+//!
+//! assert_eq!(browser.navigate_to("http://app.test/", None)?.text(), "secret");
+//! assert_eq!(
+//!     browser.navigate_to("http://app.test/maintenance", None)?.text(),
+//!     "Down for maintenance"
+//! );
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::transport::{Request, Response, Result};
+use std::fmt::{self, Debug, Formatter};
+
+/// An interceptor for the requests and responses of a [`Browser`][crate::Browser]. See the
+/// [module documentation][crate::middleware] for details.
+pub trait Middleware: Debug + Send + Sync {
+    /// Handles the `request`, usually by passing it (modified or not) to `next` and returning (a possibly modified
+    /// version of) its response.
+    fn handle(&self, request: Request, next: Next<'_>) -> Result<Response>;
+}
+
+/// The remainder of a middleware chain, ending with the [`Transport`][crate::transport::Transport] of the browser.
+pub struct Next<'a> {
+    middlewares: &'a [Box<dyn Middleware>],
+    endpoint: &'a dyn Fn(Request) -> Result<Response>,
+}
+
+impl Debug for Next<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Next")
+            .field("middlewares", &self.middlewares)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Next<'a> {
+    pub(crate) fn new(
+        middlewares: &'a [Box<dyn Middleware>],
+        endpoint: &'a dyn Fn(Request) -> Result<Response>,
+    ) -> Self {
+        Self {
+            middlewares,
+            endpoint,
+        }
+    }
+
+    /// Passes the `request` to the next middleware in the chain, or sends it if this is the end of the chain.
+    pub fn run(self, request: Request) -> Result<Response> {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => middleware.handle(request, Next::new(rest, self.endpoint)),
+            None => (self.endpoint)(request),
+        }
+    }
+}
+
+/// A [`Middleware`][Middleware] created from a closure with `from_fn()`.
+pub struct FromFn<F> {
+    f: F,
+}
+
+impl<F> Debug for FromFn<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FromFn").finish_non_exhaustive()
+    }
+}
+
+impl<F> Middleware for FromFn<F>
+where
+    F: Fn(Request, Next<'_>) -> Result<Response> + Send + Sync,
+{
+    fn handle(&self, request: Request, next: Next<'_>) -> Result<Response> {
+        (self.f)(request, next)
+    }
+}
+
+/// Returns a [`Middleware`][Middleware] handling requests with the closure `f`, which gets the request and the
+/// remainder of the chain.
+pub const fn from_fn<F>(f: F) -> FromFn<F>
+where
+    F: Fn(Request, Next<'_>) -> Result<Response> + Send + Sync,
+{
+    FromFn { f }
+}
+
+/// Returns a [`Middleware`][Middleware] modifying every outgoing request with the closure `f`, e.g. to add headers or
+/// to rewrite the host.
+pub fn map_request<F>(f: F) -> FromFn<impl Fn(Request, Next<'_>) -> Result<Response> + Send + Sync>
+where
+    F: Fn(&mut Request) + Send + Sync,
+{
+    from_fn(move |mut request, next: Next<'_>| {
+        f(&mut request);
+        next.run(request)
+    })
+}
+
+/// Returns a [`Middleware`][Middleware] modifying every incoming response with the closure `f`, which also gets the
+/// request the response answers.
+pub fn map_response<F>(f: F) -> FromFn<impl Fn(Request, Next<'_>) -> Result<Response> + Send + Sync>
+where
+    F: Fn(&Request, &mut Response) + Send + Sync,
+{
+    from_fn(move |request: Request, next: Next<'_>| {
+        let mut response = next.run(request.clone())?;
+        f(&request, &mut response);
+        Ok(response)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_fn, map_request, map_response};
+    use crate::{
+        browser::Browser,
+        mock::{self, MockTransport},
+    };
+    use reqwest::{
+        header::{HeaderValue, SET_COOKIE},
+        Method,
+    };
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn run_chain_in_order() {
+        let transport = MockTransport::new()
+            .route(Method::GET, "/", |request| {
+                mock::html(&format!(
+                    "{} {:?}",
+                    request.url(),
+                    request.headers().get("cookie")
+                ))
+            })
+            .respond(Method::GET, "/login", mock::redirect("/"));
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let outer_log = log.clone();
+        let inner_log = log.clone();
+        let browser = Browser::builder()
+            .transport(transport)
+            .middleware(from_fn(move |request, next| {
+                outer_log
+                    .lock()
+                    .unwrap()
+                    .push(format!("outer {}", request.url().path()));
+                next.run(request)
+            }))
+            .middleware(map_request(|request| {
+                request.url_mut().set_host(Some("staging.test")).unwrap();
+            }))
+            .middleware(map_response(move |request, response| {
+                inner_log
+                    .lock()
+                    .unwrap()
+                    .push(format!("inner {}", request.url().host_str().unwrap()));
+                response
+                    .headers_mut()
+                    .append(SET_COOKIE, HeaderValue::from_static("injected=1"));
+            }))
+            .finish()
+            .unwrap();
+
+        let page = browser.navigate_to("http://app.test/login", None).unwrap();
+
+        assert_eq!(page.url().as_str(), "http://app.test/");
+        assert_eq!(page.text(), "http://staging.test/ Some(\"injected=1\")");
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "outer /login",
+                "inner staging.test",
+                "outer /",
+                "inner staging.test"
+            ]
+        );
+    }
+}
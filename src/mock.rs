@@ -0,0 +1,262 @@
+//! Module containing the [`MockTransport`][MockTransport] to test navigation and form submissions in-process, without
+//! any sockets.
+//!
+//! A mock transport answers requests either with a closure or a route table mapping http method and url path to
+//! responses. As redirects and cookies are handled by the [`Browser`][crate::Browser] itself, both work with mocked
+//! responses just like with real servers.
+//!
+//! ```
+//! use no_browser::{
+//!     mock::{self, MockTransport},
+//!     Browser, InputType,
+//! };
+//! use reqwest::Method;
+//!
+//! let transport = MockTransport::new()
+//!     .route(Method::GET, "/login", |_| {
+//!         mock::html("<form action='/login' method='post'><input type='text' name='user'></form>")
+//!     })
+//!     .route(Method::POST, "/login", |request| {
+//!         let user = &request.form_data()[0].1;
+//!         mock::redirect("/home").with_header(
+//!             reqwest::header::SET_COOKIE,
+//!             format!("user={user}").parse().unwrap(),
+//!         )
+//!     })
+//!     .route(Method::GET, "/home", |request| {
+//!         let cookie = request.headers()["cookie"].to_str().unwrap();
+//!         mock::html(&format!("<h1>Welcome, {cookie}</h1>"))
+//!     });
+//! let log = transport.request_log();
+//!
+//! let browser = Browser::builder().transport(transport).finish()?;
+//! let mut page = browser.navigate_to("http://app.test/login", None)?;
+//!
+//! let form = page.form_mut(0)?;
+//! form.input_mut(InputType::Text, "user")?.set_value(Some("ferris".to_owned()));
+//! let page = browser.submit_form(form, None)?;
+//!
+//! assert_eq!(page.url().path(), "/home");
+//! assert_eq!(page.select_first("h1")?.inner_html(), "Welcome, user=ferris");
+//! assert_eq!(log.len(), 3);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::transport::{self, Request, Response, Transport};
+use reqwest::{
+    header::{HeaderValue, CONTENT_TYPE, LOCATION},
+    Method, StatusCode,
+};
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, Mutex},
+};
+
+type Handler = Box<dyn Fn(&Request) -> Response + Send + Sync>;
+
+struct Route {
+    method: Method,
+    path: String,
+    handler: Handler,
+}
+
+/// A [`Transport`][Transport] answering requests in-process, either with a closure (`from_fn()`) or a route table
+/// (`route()`, `respond()`). Requests not matched by any route receive a `404 Not Found` response, unless a
+/// `fallback()` is set. See the [module documentation][crate::mock] for an example.
+pub struct MockTransport {
+    routes: Vec<Route>,
+    fallback: Option<Handler>,
+    log: RequestLog,
+}
+
+impl Debug for MockTransport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockTransport")
+            .field(
+                "routes",
+                &self
+                    .routes
+                    .iter()
+                    .map(|route| format!("{} {}", route.method, route.path))
+                    .collect::<Vec<String>>(),
+            )
+            .field("fallback", &self.fallback.is_some())
+            .field("log", &self.log)
+            .finish()
+    }
+}
+
+impl Default for MockTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockTransport {
+    /// Returns a [`MockTransport`][MockTransport] with an empty route table.
+    pub fn new() -> Self {
+        Self {
+            routes: Vec::new(),
+            fallback: None,
+            log: RequestLog::default(),
+        }
+    }
+
+    /// Returns a [`MockTransport`][MockTransport] answering all requests with the given `handler`.
+    pub fn from_fn<F>(handler: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        Self::new().fallback(handler)
+    }
+
+    /// Adds a route answering requests with the given http `method` and url `path` (without query string) with the
+    /// given `handler`. Routes are matched in the order they were added.
+    pub fn route<F>(mut self, method: Method, path: &str, handler: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        self.routes.push(Route {
+            method,
+            path: path.to_owned(),
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    /// Adds a route answering requests with the given http `method` and url `path` with (a clone of) `response`.
+    pub fn respond(self, method: Method, path: &str, response: Response) -> Self {
+        self.route(method, path, move |_| response.clone())
+    }
+
+    /// Sets the `handler` answering all requests not matched by any route.
+    pub fn fallback<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        self.fallback = Some(Box::new(handler));
+        self
+    }
+
+    /// Returns a handle to the log of all requests received by this transport. Keep it to inspect the requests after
+    /// the transport has been moved into a [`Browser`][crate::Browser].
+    pub fn request_log(&self) -> RequestLog {
+        self.log.clone()
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(&self, request: &Request) -> transport::Result<Response> {
+        self.log.push(request.clone());
+
+        let route = self
+            .routes
+            .iter()
+            .find(|route| route.method == request.method() && route.path == request.url().path());
+
+        Ok(match (route, &self.fallback) {
+            (Some(route), _) => (route.handler)(request),
+            (None, Some(fallback)) => fallback(request),
+            (None, None) => Response::new(StatusCode::NOT_FOUND),
+        })
+    }
+}
+
+/// A shared, thread-safe log of the requests received by a [`MockTransport`][MockTransport].
+#[derive(Debug, Clone, Default)]
+pub struct RequestLog {
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl RequestLog {
+    pub(crate) fn push(&self, request: Request) {
+        self.requests.lock().unwrap().push(request);
+    }
+
+    /// Returns (clones of) all requests received so far, in order.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the number of requests received so far.
+    pub fn len(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// Returns `true` if no requests have been received yet.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Returns a `200 OK` response with the given html `body`.
+pub fn html(body: &str) -> Response {
+    Response::new(StatusCode::OK)
+        .with_header(
+            CONTENT_TYPE,
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        )
+        .with_body(body)
+}
+
+/// Returns a `303 See Other` response redirecting to `location`.
+///
+/// # Panics
+///
+/// Panics if `location` is not a valid header value, e.g. if it contains line breaks.
+pub fn redirect(location: &str) -> Response {
+    let location = HeaderValue::from_str(location).expect("location must be a valid header value");
+
+    Response::new(StatusCode::SEE_OTHER).with_header(LOCATION, location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{html, redirect, MockTransport};
+    use crate::{browser::Browser, transport::Response};
+    use reqwest::{Method, StatusCode};
+
+    #[test]
+    fn route_requests() {
+        let transport = MockTransport::new()
+            .respond(Method::GET, "/", html("<a href='/next'>next</a>"))
+            .route(Method::GET, "/next", |request| {
+                html(&format!(
+                    "query: {}",
+                    request.url().query().unwrap_or_default()
+                ))
+            })
+            .respond(Method::GET, "/loop", redirect("/loop"));
+        let log = transport.request_log();
+        let browser = Browser::builder().transport(transport).finish().unwrap();
+
+        let page = browser.navigate_to("http://mock.test/", None).unwrap();
+        assert_eq!(page.select_first("a").unwrap().inner_html(), "next");
+
+        let page = browser
+            .navigate_to("http://mock.test/next", Some(&vec![("a", "b c")]))
+            .unwrap();
+        assert_eq!(page.text(), "query: a=b+c");
+
+        let page = browser
+            .navigate_to("http://mock.test/missing", None)
+            .unwrap();
+        assert_eq!(*page.status(), StatusCode::NOT_FOUND);
+
+        assert!(browser.navigate_to("http://mock.test/loop", None).is_err());
+        assert_eq!(log.len(), 3 + 11);
+        assert_eq!(log.requests()[1].url().path(), "/next");
+    }
+
+    #[test]
+    fn handle_with_closure() {
+        let transport = MockTransport::from_fn(|request| {
+            Response::new(StatusCode::IM_A_TEAPOT).with_body(request.method().as_str())
+        });
+        let browser = Browser::builder().transport(transport).finish().unwrap();
+
+        let page = browser.navigate_to("http://mock.test/any", None).unwrap();
+        assert_eq!(*page.status(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(page.text(), "GET");
+    }
+}
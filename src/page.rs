@@ -1,8 +1,24 @@
 //! Module containing the [`Page`][Page] struct.
 
-use crate::form::Form;
-use reqwest::{header::HeaderMap, Method, StatusCode, Url};
+use crate::{
+    browser,
+    cache::CacheStatus,
+    form::Form,
+    headers::TypedHeaders,
+    metadata::Metadata,
+    query::{self, By},
+    security::SecurityReport,
+    status::{ExpectedStatus, UnexpectedResponse},
+    structured_data::StructuredData,
+    table::Table,
+    xpath::{self, XPath, XPathValue},
+};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method, StatusCode, Url,
+};
 use scraper::{ElementRef, Html, Selector};
+use std::{path::Path, sync::Arc};
 use thiserror::Error;
 
 /// An error occurred while working with the page.
@@ -33,6 +49,48 @@ pub enum Error {
         selector: String,
     },
 
+    /// The given [`By`][By] query matched nothing on this page.
+    #[error("Found no element by {query}!{}", query::format_near_misses(.near_misses))]
+    QueryResultEmptyError {
+        /// The query that had no matches.
+        query: String,
+        /// Similar candidates found on this page, most similar first.
+        near_misses: Vec<String>,
+    },
+
+    /// The given [`By`][By] query matched more than one element, but at most one was expected.
+    #[error("Found {count} elements by {query}, but expected at most one!")]
+    QueryMultipleResultsError {
+        /// The query that had multiple matches.
+        query: String,
+        /// The number of matches.
+        count: usize,
+    },
+
+    /// The given XPath expression could not be parsed or evaluated.
+    #[error("{source}")]
+    XPathError {
+        /// The underlying error.
+        #[from]
+        source: xpath::Error,
+    },
+
+    /// The given XPath expression selected no elements on this page.
+    #[error("XPath expression '{expression}' selected no elements.")]
+    XPathResultEmptyError {
+        /// The given `expression` that had no matches.
+        expression: String,
+    },
+
+    /// The element matching the given CSS selector is not a `<table>`.
+    #[error("CSS selector '{selector}' matched a <{tag}> element, but expected a <table>.")]
+    NotATableError {
+        /// The given `selector`.
+        selector: String,
+        /// The tag name of the matched element.
+        tag: String,
+    },
+
     /// The given form index is out of bounds.
     #[error("This page contains {num_forms} forms; index {idx} is out of bounds!")]
     FormIndexOutOfBoundsError {
@@ -48,6 +106,25 @@ pub enum Error {
         /// The `id` that matched no form.
         id: String,
     },
+
+    /// The url given to [`PageBuilder`][PageBuilder] could not be parsed.
+    #[error("Failed to parse url '{url}', reason: {reason}")]
+    UrlParseError {
+        /// The given `url` that could not be parsed.
+        url: String,
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+
+    /// The file given to [`PageBuilder`][PageBuilder] could not be read.
+    #[error("Failed to read file '{path}'!")]
+    FileReadError {
+        /// The `path` of the file.
+        path: String,
+        /// The underlying error.
+        #[source]
+        source: std::io::Error,
+    },
 }
 
 /// Short-hand for `std::result::Result<T, no_browser::page::Error>`.
@@ -58,12 +135,27 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// It gives access to:
 /// * response meta data, like http method (`method()`) used to access the page url (`url()`), the http response status
 ///   (`status()`) and response headers (`headers()`);
+/// * typed response headers, like content type, cache directives, security policies and cookies (`typed_headers()`);
 /// * the unprocessed reponse body (`text()`);
+/// * whether the response came from the [`HttpCache`][crate::cache::HttpCache] of the browser (`cache_status()`);
 /// * individual query parameters form the page's url (`query()`);
+/// * SEO metadata, like title, description, canonical url, OpenGraph and Twitter card properties (`metadata()`);
+/// * a security audit of the response headers, cookies and mixed content references (`security_report()`);
+/// * embedded structured data from JSON-LD, Microdata and RDFa Lite (`structured_data()`);
 /// * parsed html elements via [CSS selectors](https://developer.mozilla.org/en-US/docs/Web/CSS/CSS_Selectors) either
 ///   by returning all matches (`select()`) or returning the first match only (`select_first()`);
+/// * parsed html elements via user-facing [`By`][By] queries, like ARIA role or label text, either expecting exactly
+///   one match (`get_by()`), at most one match (`query_by()`), at least one match (`get_all_by()`) or any number of
+///   matches (`query_all_by()`);
+/// * parsed html nodes, strings and numbers via [XPath 1.0](https://www.w3.org/TR/1999/REC-xpath-19991116/)
+///   expressions (`xpath()`, `xpath_elements()`, `xpath_first()`, `xpath_strings()`, `xpath_string()`,
+///   `xpath_number()`);
+/// * parsed html tables, either all tables (`tables()`) or the first table matching a CSS selector (`table()`);
 /// * parsed html forms identified either by index (`form()`) or by id (`form_by_id()`);
 ///
+/// Pages are usually returned by a [`Browser`][crate::Browser], but can also be built offline from html strings, bytes
+/// or files using `Page::builder()` or `Page::from_html()`, e.g. for fixtures in unit tests.
+///
 /// See the main docs of [crate `no_browser`][crate] for usage examples.
 #[derive(Debug)]
 pub struct Page {
@@ -74,6 +166,7 @@ pub struct Page {
     text: String,
     html: Html,
     forms: Vec<Form>,
+    cache_status: Option<CacheStatus>,
 }
 
 impl Page {
@@ -85,7 +178,7 @@ impl Page {
         text: String,
     ) -> Self {
         let html = Html::parse_document(&text);
-        let forms = Self::parse_forms(&html, &url);
+        let forms = Self::parse_forms(&html, &text, &url);
 
         Self {
             method,
@@ -95,9 +188,38 @@ impl Page {
             text,
             html,
             forms,
+            cache_status: None,
         }
     }
 
+    pub(crate) const fn with_cache_status(mut self, cache_status: Option<CacheStatus>) -> Self {
+        self.cache_status = cache_status;
+        self
+    }
+
+    /// Returns a [`PageBuilder`][PageBuilder] to construct a page for the given `url` without any http request.
+    ///
+    /// ```
+    /// use no_browser::page::Page;
+    /// use reqwest::StatusCode;
+    ///
+    /// let page = Page::builder("https://example.com/login")
+    ///     .status(StatusCode::UNAUTHORIZED)
+    ///     .build_from_text("<form id='login'><input type='text' name='user'></form>")?;
+    ///
+    /// assert_eq!(page.form_by_id("login")?.inputs().count(), 1);
+    /// # Ok::<(), no_browser::page::Error>(())
+    /// ```
+    pub fn builder(url: &str) -> PageBuilder {
+        PageBuilder::new(url)
+    }
+
+    /// Constructs a page with the given `html` content for the given `url` without any http request, using method
+    /// `GET`, status `200 OK` and no headers. Use `Page::builder()` for more control.
+    pub fn from_html(url: &str, html: &str) -> Result<Self> {
+        Self::builder(url).build_from_text(html)
+    }
+
     /// Returns the http method used to fetch this page.
     pub const fn method(&self) -> &Method {
         &self.method
@@ -123,6 +245,62 @@ impl Page {
         &self.text
     }
 
+    /// Fails with an `HttpStatusError` carrying this page, unless it was loaded with a success status (`2xx`).
+    pub fn error_for_status(self) -> browser::Result<Self> {
+        self.expect_status(ExpectedStatus::Success)
+    }
+
+    /// Fails with an `HttpStatusError` carrying this page, unless it was loaded with the `expected` status.
+    ///
+    /// ```
+    /// use no_browser::page::Page;
+    /// use reqwest::StatusCode;
+    ///
+    /// let page = Page::builder("https://example.com/admin")
+    ///     .status(StatusCode::FORBIDDEN)
+    ///     .build_from_text("<h1>Forbidden</h1>")?;
+    ///
+    /// let page = page.expect_status(StatusCode::FORBIDDEN)?;
+    /// assert!(page.expect_status(StatusCode::OK).is_err());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn expect_status<E: Into<ExpectedStatus>>(self, expected: E) -> browser::Result<Self> {
+        let expected = expected.into();
+        if expected.matches(self.status) {
+            return Ok(self);
+        }
+
+        Err(browser::Error::HttpStatusError {
+            url: self.url.to_string(),
+            status: self.status,
+            expected,
+            response: Box::new(UnexpectedResponse::new(&self)),
+        })
+    }
+
+    /// Returns how the [`HttpCache`][crate::cache::HttpCache] of the browser was involved in loading this page, or
+    /// `None` if the browser has no cache or the request was not cacheable, e.g. a `POST` request.
+    pub const fn cache_status(&self) -> Option<CacheStatus> {
+        self.cache_status
+    }
+
+    pub(crate) const fn html(&self) -> &Html {
+        &self.html
+    }
+
+    /// Returns the url relative links on this page are resolved against: the `<base href>`, if any, or the page url.
+    pub(crate) fn base_url(&self) -> Url {
+        self.html
+            .select(&Selector::parse("base[href]").unwrap())
+            .next()
+            .and_then(|base| {
+                self.url
+                    .join(base.value().attr("href").unwrap().trim())
+                    .ok()
+            })
+            .unwrap_or_else(|| self.url.clone())
+    }
+
     /// Returns a reference to the form at index `idx` from the list of forms on this page.
     pub fn form(&self, idx: usize) -> Result<&Form> {
         self.forms.get(idx).ok_or(Error::FormIndexOutOfBoundsError {
@@ -181,7 +359,7 @@ impl Page {
     /// let title = title_element.inner_html();
     /// # Ok::<(), no_browser::page::Error>(())
     /// ```
-    pub fn select_first(&self, selectors: &str) -> Result<ElementRef> {
+    pub fn select_first(&self, selectors: &str) -> Result<ElementRef<'_>> {
         let s = self.parse_selectors(selectors)?;
 
         self.html
@@ -202,12 +380,180 @@ impl Page {
     /// let last_content = elements.first().unwrap().inner_html();
     /// # Ok::<(), no_browser::page::Error>(())
     /// ```
-    pub fn select(&self, selectors: &str) -> Result<Vec<ElementRef>> {
+    pub fn select(&self, selectors: &str) -> Result<Vec<ElementRef<'_>>> {
         let selectors = self.parse_selectors(selectors)?;
 
         Ok(self.html.select(&selectors).collect::<Vec<ElementRef>>())
     }
 
+    /// Returns a typed view on the response headers of this page, like `Content-Type`, `Cache-Control` or
+    /// `Set-Cookie`. See [`TypedHeaders`][TypedHeaders].
+    pub const fn typed_headers(&self) -> TypedHeaders<'_> {
+        TypedHeaders::new(&self.headers)
+    }
+
+    /// Returns a typed view on the metadata of this page, like `<title>`, meta tags, canonical and alternate links,
+    /// OpenGraph and Twitter card properties. See [`Metadata`][Metadata].
+    pub fn metadata(&self) -> Metadata {
+        Metadata::parse(&self.html, &self.url)
+    }
+
+    /// Returns the security audit of this page, covering security headers, cookie flags and mixed content. See
+    /// [`SecurityReport`][SecurityReport].
+    pub fn security_report(&self) -> SecurityReport {
+        SecurityReport::audit(self)
+    }
+
+    /// Returns the structured data (e.g. [schema.org](https://schema.org)) embedded into this page as JSON-LD,
+    /// Microdata or RDFa Lite. See [`StructuredData`][StructuredData].
+    pub fn structured_data(&self) -> StructuredData {
+        StructuredData::parse(&self.html, &self.url)
+    }
+
+    /// Returns all tables on this page, including nested tables, in document order.
+    pub fn tables(&self) -> Vec<Table> {
+        let selector = Selector::parse("table").unwrap();
+
+        self.html
+            .select(&selector)
+            .filter_map(|e| Table::from_element(&e))
+            .collect()
+    }
+
+    /// Returns the first table matching the given CSS selector group. Fails if there is no match or the first match is
+    /// not a `<table>` element.
+    ///
+    /// ```no_run
+    /// # let page: Option<no_browser::page::Page> = None;
+    /// # let page = page.unwrap();
+    /// let table = page.table("table#prices")?;
+    /// let prices = table.column("Price").unwrap_or_default();
+    /// # Ok::<(), no_browser::page::Error>(())
+    /// ```
+    pub fn table(&self, selectors: &str) -> Result<Table> {
+        let element = self.select_first(selectors)?;
+
+        Table::from_element(&element).ok_or_else(|| Error::NotATableError {
+            selector: selectors.to_owned(),
+            tag: element.value().name().to_owned(),
+        })
+    }
+
+    /// Evaluates the given [XPath 1.0](https://www.w3.org/TR/1999/REC-xpath-19991116/) expression against this page.
+    /// The result may be a node-set, a boolean, a number or a string. See [`XPath`][XPath] for the supported features.
+    ///
+    /// ```no_run
+    /// # let page: Option<no_browser::page::Page> = None;
+    /// # let page = page.unwrap();
+    /// let value = page.xpath("count(//table[@id = 'prices']//tr[td])")?;
+    /// let rows = value.number();
+    /// # Ok::<(), no_browser::page::Error>(())
+    /// ```
+    pub fn xpath(&self, expression: &str) -> Result<XPathValue<'_>> {
+        Ok(XPath::compile(expression)?.evaluate(&self.html)?)
+    }
+
+    /// Returns all elements selected by the given XPath expression. Other nodes, like text nodes or attributes, are
+    /// skipped. Fails if the expression doesn't evaluate to a node-set.
+    ///
+    /// ```no_run
+    /// # let page: Option<no_browser::page::Page> = None;
+    /// # let page = page.unwrap();
+    /// let cells = page.xpath_elements("//td[normalize-space() = 'Total']/following-sibling::td")?;
+    /// # Ok::<(), no_browser::page::Error>(())
+    /// ```
+    pub fn xpath_elements(&self, expression: &str) -> Result<Vec<ElementRef<'_>>> {
+        let nodes = XPath::compile(expression)?.select(&self.html)?;
+
+        Ok(nodes.iter().filter_map(|n| n.as_element()).collect())
+    }
+
+    /// Returns the first element selected by the given XPath expression. Fails if the expression doesn't evaluate to a
+    /// node-set or selects no elements.
+    pub fn xpath_first(&self, expression: &str) -> Result<ElementRef<'_>> {
+        self.xpath_elements(expression)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::XPathResultEmptyError {
+                expression: expression.to_owned(),
+            })
+    }
+
+    /// Returns the string-values of all nodes selected by the given XPath expression, e.g. the attribute values for
+    /// `//a/@href`. Fails if the expression doesn't evaluate to a node-set.
+    pub fn xpath_strings(&self, expression: &str) -> Result<Vec<String>> {
+        let nodes = XPath::compile(expression)?.select(&self.html)?;
+
+        Ok(nodes.iter().map(|n| n.string_value()).collect())
+    }
+
+    /// Evaluates the given XPath expression and converts the result to a string, like the XPath `string()` function.
+    pub fn xpath_string(&self, expression: &str) -> Result<String> {
+        Ok(self.xpath(expression)?.string())
+    }
+
+    /// Evaluates the given XPath expression and converts the result to a number, like the XPath `number()` function.
+    pub fn xpath_number(&self, expression: &str) -> Result<f64> {
+        Ok(self.xpath(expression)?.number())
+    }
+
+    /// Returns the single element matching the given [`By`][By] query. Fails if there is no match or more than one.
+    ///
+    /// ```no_run
+    /// # let page: Option<no_browser::page::Page> = None;
+    /// # let page = page.unwrap();
+    /// use no_browser::query::By;
+    ///
+    /// let heading = page.get_by(By::role("heading").name("Welcome"))?;
+    /// # Ok::<(), no_browser::page::Error>(())
+    /// ```
+    pub fn get_by(&self, by: By) -> Result<ElementRef<'_>> {
+        let mut elements = self.get_all_by(by.clone())?;
+
+        if elements.len() > 1 {
+            return Err(Error::QueryMultipleResultsError {
+                query: by.to_string(),
+                count: elements.len(),
+            });
+        }
+
+        Ok(elements.remove(0))
+    }
+
+    /// Returns the element matching the given [`By`][By] query, or `None` if there is no match. Fails if there is more
+    /// than one match.
+    pub fn query_by(&self, by: By) -> Result<Option<ElementRef<'_>>> {
+        let mut elements = self.query_all_by(by.clone());
+
+        if elements.len() > 1 {
+            return Err(Error::QueryMultipleResultsError {
+                query: by.to_string(),
+                count: elements.len(),
+            });
+        }
+
+        Ok(elements.pop())
+    }
+
+    /// Returns all elements matching the given [`By`][By] query. Fails if there is no match.
+    pub fn get_all_by(&self, by: By) -> Result<Vec<ElementRef<'_>>> {
+        let elements = self.query_all_by(by.clone());
+
+        if elements.is_empty() {
+            return Err(Error::QueryResultEmptyError {
+                near_misses: query::near_misses(&self.html, &by),
+                query: by.to_string(),
+            });
+        }
+
+        Ok(elements)
+    }
+
+    /// Returns all elements matching the given [`By`][By] query, which may be none at all.
+    pub fn query_all_by(&self, by: By) -> Vec<ElementRef<'_>> {
+        query::find_all(&self.html, &by)
+    }
+
     /// Returns the value of the query parameter associated with the given name. _Note_: If there are multiple values
     /// associated, only the first hit will be returned!
     pub fn query(&self, name: &str) -> Result<String> {
@@ -223,25 +569,123 @@ impl Page {
         })
     }
 
-    fn parse_forms(html: &Html, url: &Url) -> Vec<Form> {
+    fn parse_forms(html: &Html, text: &str, url: &Url) -> Vec<Form> {
         let mut forms = Vec::new();
 
         let selector = Selector::parse("form").unwrap();
+        let mut source: Option<Arc<str>> = None;
 
         for form_ref in html.select(&selector) {
-            forms.push(Form::parse(&form_ref, url.clone()));
+            let source = source.get_or_insert_with(|| Arc::from(text));
+            forms.push(Form::parse(source, &form_ref, url.clone()));
         }
 
         forms
     }
 }
 
+/// Returns the urls in the value of `attribute`: the image candidates of a `srcset` (comma-separated urls, each
+/// optionally followed by a descriptor) or the trimmed value of any other attribute, e.g. `src` or `href`.
+pub(crate) fn attribute_urls<'v>(attribute: &str, value: &'v str) -> Vec<&'v str> {
+    match attribute {
+        "srcset" => value
+            .split(',')
+            .filter_map(|candidate| candidate.split_whitespace().next())
+            .collect(),
+        _ => vec![value.trim()],
+    }
+}
+
+/// A builder to construct a [`Page`][Page] offline, i.e. without sending an http request.
+///
+/// Refer to the documentation of the public methods to learn about the available settings and their defaults. Use
+/// one of `build_from_text()`, `build_from_bytes()` or `build_from_file()` to get the configured [`Page`][Page].
+#[derive(Debug)]
+pub struct PageBuilder {
+    url: String,
+    method: Method,
+    status: StatusCode,
+    headers: HeaderMap,
+}
+
+impl PageBuilder {
+    fn new(url: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+            method: Method::GET,
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+        }
+    }
+
+    /// Set the http method used to "fetch" the page. Defaults to `GET`.
+    pub fn method(mut self, method: Method) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Set the http response status of the page. Defaults to `200 OK`.
+    pub const fn status(mut self, status: StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Replaces all response headers of the page. Defaults to no headers.
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Appends a single response header to the page.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Completes configuration and returns the [`Page`][Page] with the given html `text` as content.
+    pub fn build_from_text(self, text: &str) -> Result<Page> {
+        let url = Url::parse(&self.url).map_err(|error| Error::UrlParseError {
+            url: self.url.clone(),
+            reason: error.to_string(),
+        })?;
+
+        Ok(Page::build(
+            self.method,
+            url,
+            self.status,
+            self.headers,
+            text.to_owned(),
+        ))
+    }
+
+    /// Completes configuration and returns the [`Page`][Page] with the given `bytes` as content. Invalid UTF-8
+    /// sequences are replaced with `U+FFFD`.
+    pub fn build_from_bytes(self, bytes: &[u8]) -> Result<Page> {
+        self.build_from_text(&String::from_utf8_lossy(bytes))
+    }
+
+    /// Completes configuration and returns the [`Page`][Page] with the content of the file at `path`. Invalid UTF-8
+    /// sequences are replaced with `U+FFFD`.
+    pub fn build_from_file<P: AsRef<Path>>(self, path: P) -> Result<Page> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|error| Error::FileReadError {
+            path: path.display().to_string(),
+            source: error,
+        })?;
+
+        self.build_from_bytes(&bytes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::input::InputType;
+    use crate::{input::InputType, query::By};
 
-    use super::Page;
-    use reqwest::{header::HeaderMap, Method, StatusCode, Url};
+    use super::{Error, Page};
+    use reqwest::{
+        header::{HeaderMap, HeaderValue, CONTENT_TYPE},
+        Method, StatusCode, Url,
+    };
 
     static PAGE_001: &str = r#"
         <html>
@@ -289,4 +733,129 @@ mod tests {
         assert_eq!(hidden.name(), "hidden");
         assert_eq!(hidden.value(), Some("hidden"));
     }
+
+    #[test]
+    fn query_page() {
+        let method = Method::GET;
+        let url = Url::parse("https://wikipedia.org/").unwrap();
+        let page = Page::build(
+            method,
+            url,
+            StatusCode::OK,
+            HeaderMap::new(),
+            PAGE_001.to_owned(),
+        );
+
+        assert_eq!(
+            page.get_by(By::role("heading")).unwrap().inner_html(),
+            "Test"
+        );
+        assert!(page.query_by(By::text("Missing")).unwrap().is_none());
+        assert_eq!(
+            page.get_all_by(By::role("button").name("Submit"))
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(page.query_all_by(By::role("form")).len(), 0);
+
+        match page.get_by(By::role("button")) {
+            Err(Error::QueryMultipleResultsError { count, .. }) => assert_eq!(count, 3),
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        let error = page.get_by(By::text("Tset")).unwrap_err();
+        assert!(matches!(error, Error::QueryResultEmptyError { .. }));
+        assert!(error.to_string().contains("Did you mean:\n  * text 'Test'"));
+    }
+
+    #[test]
+    fn xpath_page() {
+        let method = Method::GET;
+        let url = Url::parse("https://wikipedia.org/").unwrap();
+        let page = Page::build(
+            method,
+            url,
+            StatusCode::OK,
+            HeaderMap::new(),
+            PAGE_001.to_owned(),
+        );
+
+        assert_eq!(page.xpath_elements("//form[input]").unwrap().len(), 3);
+        assert_eq!(
+            page.xpath_first("//form[last()]").unwrap().value().id(),
+            Some("id_03")
+        );
+        assert_eq!(
+            page.xpath_strings("//form[position() < 3]/@id").unwrap(),
+            vec!["id_01", "id_02"]
+        );
+        assert_eq!(page.xpath_string("string(//h1)").unwrap(), "Test");
+        assert_eq!(page.xpath_number("count(//button)").unwrap(), 3.0);
+
+        assert!(matches!(
+            page.xpath_first("//table"),
+            Err(Error::XPathResultEmptyError { .. })
+        ));
+        assert!(matches!(
+            page.xpath_elements("count(//form)"),
+            Err(Error::XPathError { .. })
+        ));
+        assert!(matches!(
+            page.xpath("//form["),
+            Err(Error::XPathError { .. })
+        ));
+    }
+
+    #[test]
+    fn table_page() {
+        let method = Method::GET;
+        let url = Url::parse("https://wikipedia.org/").unwrap();
+        let page = Page::build(
+            method,
+            url,
+            StatusCode::OK,
+            HeaderMap::new(),
+            "<table id='t'><tr><th>A</th></tr><tr><td>1</td></tr></table><p>no table</p>"
+                .to_owned(),
+        );
+
+        assert_eq!(page.tables().len(), 1);
+        assert_eq!(page.table("#t").unwrap().column("A"), Some(vec!["1"]));
+        assert!(matches!(
+            page.table("p"),
+            Err(Error::NotATableError { tag, .. }) if tag == "p"
+        ));
+    }
+
+    #[test]
+    fn build_page_offline() {
+        let page = Page::builder("https://example.com/search?q=rust")
+            .method(Method::POST)
+            .status(StatusCode::NOT_FOUND)
+            .header(CONTENT_TYPE, HeaderValue::from_static("text/html"))
+            .build_from_bytes(b"<h1>Not \xF0 found</h1>")
+            .unwrap();
+
+        assert_eq!(page.method(), Method::POST);
+        assert_eq!(*page.status(), StatusCode::NOT_FOUND);
+        assert_eq!(page.headers()[CONTENT_TYPE], "text/html");
+        assert_eq!(page.query("q").unwrap(), "rust");
+        assert_eq!(
+            page.select_first("h1").unwrap().inner_html(),
+            "Not \u{FFFD} found"
+        );
+
+        let page = Page::from_html("https://example.com/", PAGE_001).unwrap();
+        assert_eq!(page.forms.len(), 3);
+
+        assert!(matches!(
+            Page::from_html("no url", ""),
+            Err(Error::UrlParseError { .. })
+        ));
+        assert!(matches!(
+            Page::builder("https://example.com/").build_from_file("/does/not/exist.html"),
+            Err(Error::FileReadError { .. })
+        ));
+    }
 }
@@ -0,0 +1,768 @@
+//! Module containing the [`By`][By] queries to find elements the way a user would perceive them.
+//!
+//! The queries are modelled on [DOM Testing Library](https://testing-library.com/docs/queries/about): instead of
+//! coupling tests to the markup via CSS selectors, elements are looked up by their ARIA role and accessible name, the
+//! text of their label, their placeholder, their visible text, their current value or a dedicated `data-testid`
+//! attribute. Use them through the `get_by()`, `query_by()`, `get_all_by()` and `query_all_by()` methods of
+//! [`Page`][crate::page::Page].
+
+use scraper::{node::Element, ElementRef, Html, Node};
+use std::fmt::Display;
+
+/// Describes how the text of an element is compared to the expected text. Whitespace is always normalized, i.e.
+/// leading and trailing whitespace is removed and inner whitespace is collapsed into single spaces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextMatch {
+    /// The text must match exactly (case-sensitive).
+    Exact(String),
+    /// The text must contain the given string (case-insensitive).
+    Contains(String),
+}
+
+impl TextMatch {
+    /// Returns a [`TextMatch`][TextMatch] matching the given text exactly.
+    pub fn exact(text: &str) -> Self {
+        Self::Exact(normalize(text))
+    }
+
+    /// Returns a [`TextMatch`][TextMatch] matching any text containing the given text, ignoring case.
+    pub fn contains(text: &str) -> Self {
+        Self::Contains(normalize(text).to_lowercase())
+    }
+
+    /// Returns `true` if the given `text` satisfies this [`TextMatch`][TextMatch].
+    pub fn matches(&self, text: &str) -> bool {
+        let text = normalize(text);
+        match self {
+            Self::Exact(expected) => text == *expected,
+            Self::Contains(expected) => text.to_lowercase().contains(expected.as_str()),
+        }
+    }
+
+    fn expected(&self) -> &str {
+        match self {
+            Self::Exact(expected) | Self::Contains(expected) => expected,
+        }
+    }
+}
+
+impl From<&str> for TextMatch {
+    fn from(text: &str) -> Self {
+        Self::exact(text)
+    }
+}
+
+impl From<String> for TextMatch {
+    fn from(text: String) -> Self {
+        Self::exact(&text)
+    }
+}
+
+impl Display for TextMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Exact(expected) => write!(f, "'{expected}'"),
+            Self::Contains(expected) => write!(f, "containing '{expected}'"),
+        }
+    }
+}
+
+/// A query describing how to find elements on a [`Page`][crate::page::Page].
+///
+/// ```no_run
+/// # let page: Option<no_browser::page::Page> = None;
+/// # let page = page.unwrap();
+/// use no_browser::query::{By, TextMatch};
+///
+/// let button = page.get_by(By::role("button").name("Sign in"))?;
+/// let email = page.get_by(By::label_text("E-Mail"))?;
+/// let banner = page.query_by(By::text(TextMatch::contains("welcome")))?;
+/// # Ok::<(), no_browser::page::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum By {
+    /// Elements with the given [ARIA role](https://www.w3.org/TR/wai-aria-1.2/#role_definitions), either explicitly
+    /// set via the `role` attribute or implied by the html tag, optionally filtered by their accessible name.
+    Role {
+        /// The expected role, e.g. `button`, `link`, `heading` or `textbox`.
+        role: String,
+        /// The expected accessible name, if any.
+        name: Option<TextMatch>,
+    },
+    /// Form controls associated with a `<label>` (via `for` or by nesting), `aria-label` or `aria-labelledby`.
+    LabelText(TextMatch),
+    /// Elements with a matching `placeholder` attribute.
+    PlaceholderText(TextMatch),
+    /// Elements whose own text content matches; `<script>` and `<style>` elements are ignored.
+    Text(TextMatch),
+    /// `<input>`, `<select>` and `<textarea>` elements with a matching current value.
+    DisplayValue(TextMatch),
+    /// Elements with the given `data-testid` attribute.
+    TestId(String),
+    /// Elements matching the query `by` that also have the given accessible name, see `By::name()`.
+    Named {
+        /// The query the elements must match.
+        by: Box<By>,
+        /// The expected accessible name.
+        name: TextMatch,
+    },
+}
+
+impl By {
+    /// Returns a query for elements with the given ARIA `role`. Use `name()` to filter by accessible name.
+    pub fn role(role: &str) -> Self {
+        Self::Role {
+            role: role.to_lowercase(),
+            name: None,
+        }
+    }
+
+    /// Restricts the query to elements with the given accessible `name`, replacing any name set before.
+    pub fn name(self, name: impl Into<TextMatch>) -> Self {
+        match self {
+            Self::Role { role, .. } => Self::Role {
+                role,
+                name: Some(name.into()),
+            },
+            Self::Named { by, .. } => Self::Named {
+                by,
+                name: name.into(),
+            },
+            by => Self::Named {
+                by: Box::new(by),
+                name: name.into(),
+            },
+        }
+    }
+
+    /// Returns a query for form controls labelled by the given text.
+    pub fn label_text(text: impl Into<TextMatch>) -> Self {
+        Self::LabelText(text.into())
+    }
+
+    /// Returns a query for elements with the given placeholder text.
+    pub fn placeholder_text(text: impl Into<TextMatch>) -> Self {
+        Self::PlaceholderText(text.into())
+    }
+
+    /// Returns a query for elements containing the given text.
+    pub fn text(text: impl Into<TextMatch>) -> Self {
+        Self::Text(text.into())
+    }
+
+    /// Returns a query for form controls with the given current value.
+    pub fn display_value(value: impl Into<TextMatch>) -> Self {
+        Self::DisplayValue(value.into())
+    }
+
+    /// Returns a query for elements with the given `data-testid` attribute.
+    pub fn test_id(test_id: &str) -> Self {
+        Self::TestId(test_id.to_owned())
+    }
+}
+
+impl Display for By {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Role { role, name: None } => write!(f, "role '{role}'"),
+            Self::Role {
+                role,
+                name: Some(name),
+            } => write!(f, "role '{role}' and name {name}"),
+            Self::LabelText(text) => write!(f, "label text {text}"),
+            Self::PlaceholderText(text) => write!(f, "placeholder text {text}"),
+            Self::Text(text) => write!(f, "text {text}"),
+            Self::DisplayValue(value) => write!(f, "display value {value}"),
+            Self::TestId(test_id) => write!(f, "test id '{test_id}'"),
+            Self::Named { by, name } => write!(f, "{by} and name {name}"),
+        }
+    }
+}
+
+const MAX_NEAR_MISSES: usize = 5;
+
+/// Returns all elements matching the given query in document order.
+pub(crate) fn find_all<'a>(html: &'a Html, by: &By) -> Vec<ElementRef<'a>> {
+    elements(html)
+        .filter(|element| is_match(element, by))
+        .collect()
+}
+
+/// Returns `true` if the element matches the given query.
+fn is_match(element: &ElementRef, by: &By) -> bool {
+    match by {
+        By::Role { role, name } => {
+            !is_hidden(element)
+                && role_of(element).as_deref() == Some(role.as_str())
+                && name
+                    .as_ref()
+                    .is_none_or(|name| name.matches(&accessible_name(element)))
+        }
+        By::LabelText(text) => labels_of(element).iter().any(|l| text.matches(l)),
+        By::PlaceholderText(text) => element
+            .value()
+            .attr("placeholder")
+            .is_some_and(|p| text.matches(p)),
+        By::Text(text) => own_text(element).is_some_and(|t| text.matches(&t)),
+        By::DisplayValue(value) => display_value(element).is_some_and(|v| value.matches(&v)),
+        By::TestId(test_id) => element.value().attr("data-testid") == Some(test_id.as_str()),
+        By::Named { by, name } => is_match(element, by) && name.matches(&accessible_name(element)),
+    }
+}
+
+/// Returns a short list of candidates of the same kind as the given query, most similar first. Used to give helpful
+/// error messages when a query matched nothing.
+pub(crate) fn near_misses(html: &Html, by: &By) -> Vec<String> {
+    let mut candidates = Vec::new();
+    let expected = match by {
+        By::Role { role, name } => {
+            for element in elements(html).filter(|e| !is_hidden(e)) {
+                if let Some(r) = role_of(&element) {
+                    let name = accessible_name(&element);
+                    candidates.push(format!("role '{r}' and name '{name}'"));
+                }
+            }
+            match name {
+                Some(name) => format!("role '{role}' and name '{}'", name.expected()),
+                None => format!("role '{role}' and name ''"),
+            }
+        }
+        By::LabelText(text) => {
+            for element in elements(html) {
+                for label in labels_of(&element) {
+                    candidates.push(format!("label text '{label}'"));
+                }
+            }
+            format!("label text '{}'", text.expected())
+        }
+        By::PlaceholderText(text) => {
+            for element in elements(html) {
+                if let Some(p) = element.value().attr("placeholder") {
+                    candidates.push(format!("placeholder text '{}'", normalize(p)));
+                }
+            }
+            format!("placeholder text '{}'", text.expected())
+        }
+        By::Text(text) => {
+            for element in elements(html) {
+                if let Some(t) = own_text(&element) {
+                    candidates.push(format!("text '{}'", normalize(&t)));
+                }
+            }
+            format!("text '{}'", text.expected())
+        }
+        By::DisplayValue(value) => {
+            for element in elements(html) {
+                if let Some(v) = display_value(&element) {
+                    candidates.push(format!("display value '{}'", normalize(&v)));
+                }
+            }
+            format!("display value '{}'", value.expected())
+        }
+        By::TestId(test_id) => {
+            for element in elements(html) {
+                if let Some(t) = element.value().attr("data-testid") {
+                    candidates.push(format!("test id '{t}'"));
+                }
+            }
+            format!("test id '{test_id}'")
+        }
+        By::Named { by, name } => {
+            for element in find_all(html, by) {
+                candidates.push(format!("{by} and name '{}'", accessible_name(&element)));
+            }
+            format!("{by} and name '{}'", name.expected())
+        }
+    };
+
+    candidates.sort();
+    candidates.dedup();
+
+    let expected = expected.to_lowercase();
+    candidates.sort_by_cached_key(|c| levenshtein(&c.to_lowercase(), &expected));
+    candidates.truncate(MAX_NEAR_MISSES);
+    candidates
+}
+
+/// Formats the near misses of a failed query for use in error messages.
+pub(crate) fn format_near_misses(near_misses: &[String]) -> String {
+    if near_misses.is_empty() {
+        return String::new();
+    }
+
+    let mut s = String::from(" Did you mean:");
+    for near_miss in near_misses {
+        s.push_str("\n  * ");
+        s.push_str(near_miss);
+    }
+    s
+}
+
+/// Returns the ARIA role of an element, preferring an explicit `role` attribute over the implicit role of the tag.
+pub(crate) fn role_of(element: &ElementRef) -> Option<String> {
+    let e = element.value();
+
+    if let Some(role) = e.attr("role").and_then(|r| r.split_whitespace().next()) {
+        return Some(role.to_lowercase());
+    }
+
+    let role = match e.name() {
+        "a" | "area" if e.attr("href").is_some() => "link",
+        "article" => "article",
+        "aside" => "complementary",
+        "button" => "button",
+        "datalist" => "listbox",
+        "details" | "fieldset" => "group",
+        "dialog" => "dialog",
+        "figure" => "figure",
+        "footer" => "contentinfo",
+        "form" if has_explicit_name(e) => "form",
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => "heading",
+        "header" => "banner",
+        "hr" => "separator",
+        "img" if e.attr("alt") == Some("") => "presentation",
+        "img" => "img",
+        "input" => match e.attr("type").unwrap_or("text").to_lowercase().as_str() {
+            "button" | "image" | "reset" | "submit" => "button",
+            "checkbox" => "checkbox",
+            "radio" => "radio",
+            "range" => "slider",
+            "number" => "spinbutton",
+            "search" => "searchbox",
+            "email" | "tel" | "text" | "url" => "textbox",
+            _ => return None,
+        },
+        "li" => "listitem",
+        "main" => "main",
+        "menu" | "ol" | "ul" => "list",
+        "nav" => "navigation",
+        "option" => "option",
+        "output" => "status",
+        "progress" => "progressbar",
+        "section" if has_explicit_name(e) => "region",
+        "select" if e.attr("multiple").is_some() => "listbox",
+        "select" => "combobox",
+        "table" => "table",
+        "tbody" | "tfoot" | "thead" => "rowgroup",
+        "td" => "cell",
+        "textarea" => "textbox",
+        "th" => "columnheader",
+        "tr" => "row",
+        _ => return None,
+    };
+
+    Some(role.to_owned())
+}
+
+/// Computes a simplified version of the [accessible name](https://www.w3.org/TR/accname-1.2/) of an element.
+pub(crate) fn accessible_name(element: &ElementRef) -> String {
+    let e = element.value();
+
+    if let Some(ids) = e.attr("aria-labelledby") {
+        let name = ids
+            .split_whitespace()
+            .filter_map(|id| element_by_id(element, id))
+            .map(|labelled_by| text_content(&labelled_by))
+            .collect::<Vec<String>>()
+            .join(" ");
+        if !name.is_empty() {
+            return normalize(&name);
+        }
+    }
+
+    if let Some(label) = e.attr("aria-label").filter(|l| !l.trim().is_empty()) {
+        return normalize(label);
+    }
+
+    if is_labelable(element) {
+        let labels = html_labels_for(element);
+        if !labels.is_empty() {
+            return labels.join(" ");
+        }
+    }
+
+    let name = match e.name() {
+        "input" => match e.attr("type").unwrap_or("text").to_lowercase().as_str() {
+            "submit" => Some(e.attr("value").unwrap_or("Submit").to_owned()),
+            "reset" => Some(e.attr("value").unwrap_or("Reset").to_owned()),
+            "button" => e.attr("value").map(|v| v.to_owned()),
+            "image" => e.attr("alt").map(|v| v.to_owned()),
+            _ => None,
+        },
+        "img" | "area" => e.attr("alt").map(|v| v.to_owned()),
+        "fieldset" => first_child_text(element, "legend"),
+        "figure" => first_child_text(element, "figcaption"),
+        "table" => first_child_text(element, "caption"),
+        _ => None,
+    };
+    if let Some(name) = name.filter(|n| !n.trim().is_empty()) {
+        return normalize(&name);
+    }
+
+    let name_from_content = matches!(
+        role_of(element).as_deref(),
+        Some(
+            "button"
+                | "cell"
+                | "checkbox"
+                | "columnheader"
+                | "heading"
+                | "link"
+                | "listitem"
+                | "menuitem"
+                | "option"
+                | "radio"
+                | "row"
+                | "rowheader"
+                | "switch"
+                | "tab"
+                | "tooltip"
+                | "treeitem"
+        )
+    );
+    if name_from_content {
+        let text = text_content(element);
+        if !text.is_empty() {
+            return text;
+        }
+    }
+
+    e.attr("title")
+        .or_else(|| e.attr("placeholder"))
+        .map(normalize)
+        .unwrap_or_default()
+}
+
+/// Returns all texts labelling the given element via `aria-labelledby`, `aria-label` or `<label>` elements.
+pub(crate) fn labels_of(element: &ElementRef) -> Vec<String> {
+    let e = element.value();
+    let mut labels = Vec::new();
+
+    if let Some(ids) = e.attr("aria-labelledby") {
+        for labelled_by in ids
+            .split_whitespace()
+            .filter_map(|id| element_by_id(element, id))
+        {
+            labels.push(text_content(&labelled_by));
+        }
+    }
+
+    if let Some(label) = e.attr("aria-label") {
+        labels.push(normalize(label));
+    }
+
+    if is_labelable(element) {
+        labels.extend(html_labels_for(element));
+    }
+
+    labels
+}
+
+/// Returns the texts of all `<label>` elements pointing to the given element via `for` or by nesting it.
+fn html_labels_for(element: &ElementRef) -> Vec<String> {
+    let mut labels = Vec::new();
+
+    if let Some(id) = element.value().attr("id") {
+        for label in document_elements(element).filter(|l| l.value().name() == "label") {
+            if label.value().attr("for") == Some(id) {
+                labels.push(text_content(&label));
+            }
+        }
+    }
+
+    for label in element.ancestors().filter_map(ElementRef::wrap) {
+        if label.value().name() != "label" || label.value().attr("for").is_some() {
+            continue;
+        }
+        let first_labelable = label
+            .descendants()
+            .filter_map(ElementRef::wrap)
+            .find(is_labelable);
+        if first_labelable.map(|l| l.id()) == Some(element.id()) {
+            labels.push(text_content(&label));
+        }
+    }
+
+    labels
+}
+
+/// Returns the value of form controls as displayed to the user.
+fn display_value(element: &ElementRef) -> Option<String> {
+    let e = element.value();
+
+    match e.name() {
+        "input" => {
+            let t = e.attr("type").unwrap_or("text").to_lowercase();
+            if [
+                "checkbox", "radio", "hidden", "submit", "reset", "button", "image",
+            ]
+            .contains(&t.as_str())
+            {
+                return None;
+            }
+            Some(e.attr("value").unwrap_or("").to_owned())
+        }
+        "textarea" => Some(element.text().collect()),
+        "select" => {
+            let options: Vec<ElementRef> = element
+                .descendants()
+                .filter_map(ElementRef::wrap)
+                .filter(|o| o.value().name() == "option")
+                .collect();
+            options
+                .iter()
+                .find(|o| o.value().attr("selected").is_some())
+                .or(options.first())
+                .map(text_content)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the text made up of the element's direct text nodes, i.e. excluding the text of child elements. Input
+/// buttons expose their `value` instead.
+fn own_text(element: &ElementRef) -> Option<String> {
+    let e = element.value();
+
+    if ["script", "style", "head", "title"].contains(&e.name()) {
+        return None;
+    }
+
+    if e.name() == "input" {
+        let t = e.attr("type").unwrap_or("text").to_lowercase();
+        if ["submit", "reset", "button"].contains(&t.as_str()) {
+            return e.attr("value").map(|v| v.to_owned());
+        }
+        return None;
+    }
+
+    let text: String = element
+        .children()
+        .filter_map(|child| match child.value() {
+            Node::Text(text) => Some(&**text),
+            _ => None,
+        })
+        .collect();
+
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// Landmarks like `<form>` and `<section>` only have an implicit role if they are explicitly named.
+fn has_explicit_name(e: &Element) -> bool {
+    ["aria-label", "aria-labelledby", "title"]
+        .iter()
+        .any(|attr| e.attr(attr).is_some())
+}
+
+fn is_labelable(element: &ElementRef) -> bool {
+    let e = element.value();
+    match e.name() {
+        "input" => e.attr("type").map(|t| t.to_lowercase()).as_deref() != Some("hidden"),
+        "button" | "meter" | "output" | "progress" | "select" | "textarea" => true,
+        _ => false,
+    }
+}
+
+/// An element is hidden from the accessibility tree if it or any ancestor is marked `hidden` or `aria-hidden`.
+fn is_hidden(element: &ElementRef) -> bool {
+    let hidden = |e: &ElementRef| {
+        let v = e.value();
+        v.attr("hidden").is_some()
+            || v.attr("aria-hidden") == Some("true")
+            || (v.name() == "input"
+                && v.attr("type").map(|t| t.to_lowercase()).as_deref() == Some("hidden"))
+    };
+
+    hidden(element)
+        || element
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|a| hidden(&a))
+}
+
+fn first_child_text(element: &ElementRef, tag: &str) -> Option<String> {
+    element
+        .children()
+        .filter_map(ElementRef::wrap)
+        .find(|c| c.value().name() == tag)
+        .map(|c| text_content(&c))
+}
+
+/// Looks up an element by `id` within the same document as the given `element`.
+fn element_by_id<'a>(element: &ElementRef<'a>, id: &str) -> Option<ElementRef<'a>> {
+    document_elements(element).find(|e| e.value().attr("id") == Some(id))
+}
+
+fn document_elements<'a>(element: &ElementRef<'a>) -> impl Iterator<Item = ElementRef<'a>> {
+    element
+        .tree()
+        .root()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+}
+
+fn elements(html: &Html) -> impl Iterator<Item = ElementRef<'_>> {
+    html.root_element()
+        .descendants()
+        .filter_map(ElementRef::wrap)
+}
+
+pub(crate) fn text_content(element: &ElementRef) -> String {
+    normalize(&element.text().collect::<String>())
+}
+
+pub(crate) fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{find_all, near_misses, By, TextMatch};
+    use scraper::Html;
+
+    static PAGE_001: &str = r#"
+        <html>
+            <body>
+                <nav><a href="/home">Home</a><a>Not a link</a></nav>
+                <h1>Welcome   back!</h1>
+                <form>
+                    <label for="email">E-Mail</label>
+                    <input id="email" type="email" name="email" placeholder="you@example.com">
+                    <label>Password <input type="password" name="password"></label>
+                    <input type="text" name="nick" aria-label="Nickname" value="ferris">
+                    <select name="lang"><option>de</option><option selected>en</option></select>
+                    <input type="hidden" name="csrf" value="secret">
+                    <input type="submit" value="Sign in">
+                    <button type="reset" data-testid="reset-btn">Start <b>over</b></button>
+                </form>
+                <div hidden><button>Invisible</button></div>
+            </body>
+        </html>
+    "#;
+
+    fn names(html: &Html, by: By) -> Vec<String> {
+        find_all(html, &by)
+            .iter()
+            .map(|e| {
+                e.value()
+                    .attr("name")
+                    .unwrap_or(e.value().name())
+                    .to_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_by_role() {
+        let html = Html::parse_document(PAGE_001);
+
+        assert_eq!(names(&html, By::role("link")), vec!["a"]);
+        assert_eq!(
+            names(&html, By::role("heading").name("Welcome back!")),
+            vec!["h1"]
+        );
+        assert_eq!(names(&html, By::role("textbox")), vec!["email", "nick"]);
+        assert_eq!(
+            names(&html, By::role("textbox").name("E-Mail")),
+            vec!["email"]
+        );
+        assert_eq!(names(&html, By::role("button")), vec!["input", "button"]);
+        assert_eq!(
+            names(&html, By::role("button").name("Sign in")),
+            vec!["input"]
+        );
+        assert_eq!(
+            names(&html, By::role("button").name("Start over")),
+            vec!["button"]
+        );
+        assert_eq!(names(&html, By::role("combobox")), vec!["lang"]);
+        assert!(names(&html, By::role("button").name("Invisible")).is_empty());
+    }
+
+    #[test]
+    fn find_by_other_queries() {
+        let html = Html::parse_document(PAGE_001);
+
+        assert_eq!(names(&html, By::label_text("E-Mail")), vec!["email"]);
+        assert_eq!(names(&html, By::label_text("Password")), vec!["password"]);
+        assert_eq!(names(&html, By::label_text("Nickname")), vec!["nick"]);
+        assert_eq!(
+            names(&html, By::placeholder_text("you@example.com")),
+            vec!["email"]
+        );
+        assert_eq!(names(&html, By::text("Welcome back!")), vec!["h1"]);
+        assert_eq!(
+            names(&html, By::text(TextMatch::contains("WELCOME"))),
+            vec!["h1"]
+        );
+        assert_eq!(names(&html, By::text("Sign in")), vec!["input"]);
+        assert_eq!(names(&html, By::display_value("ferris")), vec!["nick"]);
+        assert_eq!(names(&html, By::display_value("en")), vec!["lang"]);
+        assert_eq!(names(&html, By::test_id("reset-btn")), vec!["button"]);
+        assert!(names(&html, By::display_value("secret")).is_empty());
+    }
+
+    #[test]
+    fn find_by_other_queries_and_name() {
+        let html = Html::parse_document(PAGE_001);
+
+        assert_eq!(
+            names(
+                &html,
+                By::placeholder_text(TextMatch::contains("@")).name("E-Mail")
+            ),
+            vec!["email"]
+        );
+        assert_eq!(
+            names(&html, By::label_text("Nickname").name("Nickname")),
+            vec!["nick"]
+        );
+        assert_eq!(
+            names(
+                &html,
+                By::test_id("reset-btn").name("Sign in").name("Start over")
+            ),
+            vec!["button"]
+        );
+        assert!(names(&html, By::test_id("reset-btn").name("Sign in")).is_empty());
+        assert_eq!(
+            By::test_id("reset-btn").name("Start").to_string(),
+            "test id 'reset-btn' and name 'Start'"
+        );
+    }
+
+    #[test]
+    fn report_near_misses() {
+        let html = Html::parse_document(PAGE_001);
+
+        let misses = near_misses(&html, &By::label_text("Email"));
+        assert_eq!(misses.first().unwrap(), "label text 'E-Mail'");
+
+        let misses = near_misses(&html, &By::role("button").name("Sign on"));
+        assert_eq!(misses.first().unwrap(), "role 'button' and name 'Sign in'");
+        assert!(misses.len() <= 5);
+
+        let misses = near_misses(&html, &By::test_id("reset-btn").name("Start"));
+        assert_eq!(misses, ["test id 'reset-btn' and name 'Start over'"]);
+    }
+}
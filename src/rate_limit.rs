@@ -0,0 +1,293 @@
+//! Module containing the per-host [`RateLimit`][RateLimit] of a [`Browser`][crate::Browser].
+//!
+//! A rate limit combines a [token bucket](https://en.wikipedia.org/wiki/Token_bucket), refilled at a steady number of
+//! requests per second and holding up to `burst` tokens, with an optional maximum of concurrent requests. Limits are
+//! set with `BrowserBuilder::rate_limit()` for every host and `BrowserBuilder::host_rate_limit()` for single hosts, and
+//! are tracked separately for each host. They apply to every request the browser sends over its
+//! [`Transport`][crate::transport::Transport]: navigation, form submissions, redirects, retries and subresources.
+//! Responses served from the [`HttpCache`][crate::cache::HttpCache] don't count. As a [`Browser`][crate::Browser] can
+//! be shared by threads, requests exceeding a limit block until they may proceed.
+//!
+//! With `BrowserBuilder::respect_robots_txt()` enabled, the `Crawl-delay` of a site's robots.txt lowers the limit of
+//! its host to at most one request per delay.
+//!
+//! ```
+//! use no_browser::{rate_limit::RateLimit, Browser};
+//!
+//! let browser = Browser::builder()
+//!     // at most 5 requests per second with bursts of 10, and 2 at a time, to every host ...
+//!     .rate_limit(RateLimit::per_second(5.0).burst(10).max_concurrent(2))
+//!     // ... except staging, which only takes 30 requests per minute
+//!     .host_rate_limit("staging.example.com", RateLimit::per_minute(30.0))
+//!     .finish()?;
+//! # Ok::<(), no_browser::browser::Error>(())
+//! ```
+
+use reqwest::Url;
+use std::{
+    collections::HashMap,
+    sync::{Condvar, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The limits for requests to a single host. See the [module documentation][crate::rate_limit] for details.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    requests_per_second: Option<f64>,
+    burst: u32,
+    max_concurrent: Option<usize>,
+}
+
+impl RateLimit {
+    /// Returns a limit of `requests` per second, without bursts and concurrency limit. Use
+    /// [`unlimited()`][Self::unlimited] for no limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests` is not a positive, finite number.
+    pub fn per_second(requests: f64) -> Self {
+        assert!(
+            requests.is_finite() && requests > 0.0,
+            "rate limit must be a positive, finite number of requests, got {requests}"
+        );
+        Self {
+            requests_per_second: Some(requests),
+            burst: 1,
+            max_concurrent: None,
+        }
+    }
+
+    /// Returns a limit of `requests` per minute, without bursts and concurrency limit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `requests` is not a positive, finite number.
+    pub fn per_minute(requests: f64) -> Self {
+        Self::per_second(requests / 60.0)
+    }
+
+    /// Returns no limit at all, e.g. to exempt a single host from the limit for all hosts.
+    pub const fn unlimited() -> Self {
+        Self {
+            requests_per_second: None,
+            burst: 1,
+            max_concurrent: None,
+        }
+    }
+
+    /// Sets the number of requests that may be sent at once after a period of inactivity. Defaults to 1.
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Sets the maximum number of requests in flight at the same time. Defaults to no limit.
+    pub fn max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent = Some(max_concurrent.max(1));
+        self
+    }
+
+    /// Returns this limit lowered to at most one request per `interval`, without bursts.
+    fn at_most_every(self, interval: Duration) -> Self {
+        let rate = 1.0 / interval.as_secs_f64();
+        Self {
+            requests_per_second: Some(
+                self.requests_per_second
+                    .map_or(rate, |requests| requests.min(rate)),
+            ),
+            burst: 1,
+            ..self
+        }
+    }
+}
+
+/// The state of the token bucket and the requests in flight of a single host.
+#[derive(Debug)]
+struct HostState {
+    tokens: f64,
+    refilled: Instant,
+    in_flight: usize,
+}
+
+/// Enforces [`RateLimit`][RateLimit]s per host for all threads sharing a [`Browser`][crate::Browser].
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    limit: Option<RateLimit>,
+    host_limits: HashMap<String, RateLimit>,
+    hosts: Mutex<HashMap<String, HostState>>,
+    released: Condvar,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: Option<RateLimit>, host_limits: HashMap<String, RateLimit>) -> Self {
+        Self {
+            limit,
+            host_limits,
+            hosts: Mutex::default(),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Blocks until a request to `url` is allowed by the rate limit of its host, lowered by the `crawl_delay` of its
+    /// robots.txt, if any. The returned permit counts as request in flight until it is dropped.
+    pub(crate) fn acquire(&self, url: &Url, crawl_delay: Option<Duration>) -> Permit<'_> {
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        let limit = self.host_limits.get(&host).or(self.limit.as_ref()).copied();
+        let limit = match crawl_delay.filter(|crawl_delay| !crawl_delay.is_zero()) {
+            Some(crawl_delay) => Some(
+                limit
+                    .unwrap_or_else(RateLimit::unlimited)
+                    .at_most_every(crawl_delay),
+            ),
+            None => limit,
+        };
+        let Some(limit) = limit else {
+            return Permit {
+                limiter: self,
+                host: None,
+            };
+        };
+
+        let mut hosts = self.hosts.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            let state = hosts.entry(host.clone()).or_insert_with(|| HostState {
+                tokens: f64::from(limit.burst),
+                refilled: now,
+                in_flight: 0,
+            });
+
+            if let Some(rate) = limit.requests_per_second {
+                let elapsed = now.duration_since(state.refilled).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * rate).min(f64::from(limit.burst));
+                state.refilled = now;
+            }
+
+            let concurrency_ok = limit
+                .max_concurrent
+                .is_none_or(|max_concurrent| state.in_flight < max_concurrent);
+            let wait = match limit.requests_per_second {
+                _ if !concurrency_ok => None,
+                Some(rate) if state.tokens < 1.0 => {
+                    Some(Duration::from_secs_f64((1.0 - state.tokens) / rate))
+                }
+                _ => {
+                    if limit.requests_per_second.is_some() {
+                        state.tokens -= 1.0;
+                    }
+                    state.in_flight += 1;
+                    return Permit {
+                        limiter: self,
+                        host: Some(host),
+                    };
+                }
+            };
+
+            hosts = match wait {
+                Some(wait) => self.released.wait_timeout(hosts, wait).unwrap().0,
+                None => self.released.wait(hosts).unwrap(),
+            };
+        }
+    }
+
+    fn release(&self, host: &str) {
+        if let Some(state) = self.hosts.lock().unwrap().get_mut(host) {
+            state.in_flight -= 1;
+        }
+        self.released.notify_all();
+    }
+}
+
+/// A request in flight, admitted by a [`RateLimiter`][RateLimiter].
+#[derive(Debug)]
+pub(crate) struct Permit<'a> {
+    limiter: &'a RateLimiter,
+    host: Option<String>,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        if let Some(host) = &self.host {
+            self.limiter.release(host);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimit;
+    use crate::{browser::Browser, mock};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread,
+        time::{Duration, Instant},
+    };
+
+    #[test]
+    fn reject_invalid_rates() {
+        for requests in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(std::panic::catch_unwind(|| RateLimit::per_second(requests)).is_err());
+            assert!(std::panic::catch_unwind(|| RateLimit::per_minute(requests)).is_err());
+        }
+        assert_eq!(RateLimit::per_minute(30.0), RateLimit::per_second(0.5));
+    }
+
+    #[test]
+    fn limit_request_rate_per_host() {
+        let transport = mock::MockTransport::from_fn(|_| mock::html(""));
+        let browser = Browser::builder()
+            .transport(transport)
+            .rate_limit(RateLimit::per_second(20.0).burst(2))
+            .host_rate_limit("fast.test", RateLimit::unlimited())
+            .finish()
+            .unwrap();
+
+        let start = Instant::now();
+        for _ in 0..10 {
+            browser.navigate_to("http://fast.test/", None).unwrap();
+        }
+        assert!(start.elapsed() < Duration::from_millis(80));
+
+        let start = Instant::now();
+        for _ in 0..4 {
+            browser.navigate_to("http://slow.test/", None).unwrap();
+        }
+        // 2 requests from the burst, 2 more refilled at 50ms intervals
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(90), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(500), "{elapsed:?}");
+    }
+
+    #[test]
+    fn limit_concurrent_requests_per_host() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+
+        let (current, max) = (in_flight.clone(), max_in_flight.clone());
+        let transport = mock::MockTransport::from_fn(move |_| {
+            let now = current.fetch_add(1, Ordering::SeqCst) + 1;
+            max.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            current.fetch_sub(1, Ordering::SeqCst);
+            mock::html("")
+        });
+        let browser = Browser::builder()
+            .transport(transport)
+            .rate_limit(RateLimit::unlimited().max_concurrent(2))
+            .finish()
+            .unwrap();
+
+        thread::scope(|scope| {
+            for _ in 0..6 {
+                scope.spawn(|| {
+                    browser.navigate_to("http://app.test/", None).unwrap();
+                });
+            }
+        });
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+    }
+}
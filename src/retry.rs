@@ -0,0 +1,372 @@
+//! Module containing the [`RetryPolicy`][RetryPolicy] of a [`Browser`][crate::Browser] for transient failures.
+//!
+//! With a policy attached via `BrowserBuilder::retry_policy()`, failed requests are sent again after an exponentially
+//! growing backoff. Requests are retried if sending failed with a transient transport error (e.g. connection refused or
+//! reset, timeouts) or if the server answered with one of the retryable status codes (by default `408`, `429`, `502`,
+//! `503` and `504`). A `Retry-After` header in such a response replaces the backoff, unless it asks for a longer wait
+//! than `max_retry_after()`, in which case the response is returned as is.
+//!
+//! Only idempotent requests (`GET`, `HEAD`, `OPTIONS`, `TRACE`, `PUT` and `DELETE`) are retried by default, so form
+//! submissions via `POST` are never sent twice unless enabled with `retry_non_idempotent()`. Every attempt passes the
+//! middleware chain and is recorded by the [`HarRecorder`][crate::har::HarRecorder], if any.
+//!
+//! ```
+//! use no_browser::{retry::RetryPolicy, Browser};
+//! use reqwest::StatusCode;
+//! use std::time::Duration;
+//!
+//! let browser = Browser::builder()
+//!     .retry_policy(
+//!         RetryPolicy::new()
+//!             .max_attempts(5)
+//!             .backoff(Duration::from_millis(100), Duration::from_secs(5))
+//!             .retry_on_status(StatusCode::INTERNAL_SERVER_ERROR),
+//!     )
+//!     .finish()?;
+//! # Ok::<(), no_browser::browser::Error>(())
+//! ```
+
+use crate::transport::{self, Request, Response};
+use reqwest::{header::RETRY_AFTER, Method, StatusCode};
+use std::time::{Duration, SystemTime};
+
+/// A policy deciding whether and when failed requests are retried. See the [module documentation][crate::retry] for
+/// details.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: bool,
+    statuses: Vec<StatusCode>,
+    retry_non_idempotent: bool,
+    max_retry_after: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: true,
+            statuses: vec![
+                StatusCode::REQUEST_TIMEOUT,
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::BAD_GATEWAY,
+                StatusCode::SERVICE_UNAVAILABLE,
+                StatusCode::GATEWAY_TIMEOUT,
+            ],
+            retry_non_idempotent: false,
+            max_retry_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns a policy with the default settings: 3 attempts, backoff from 200ms doubling up to 10s with jitter,
+    /// retrying on `408`, `429`, `502`, `503` and `504`, waiting at most 60s as requested by `Retry-After`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a policy never retrying anything.
+    pub fn never() -> Self {
+        Self::default().max_attempts(1)
+    }
+
+    /// Sets the maximum number of attempts per request, including the first one. Defaults to 3.
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the backoff before the first retry and the upper limit for the growing backoffs of further retries.
+    /// Defaults to 200ms and 10s.
+    pub const fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets the factor the backoff grows by with every retry. Defaults to 2.
+    pub const fn multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Sets whether backoffs are randomized to between half and all of their value, so that concurrent clients don't
+    /// retry in lockstep. Defaults to `true`.
+    pub const fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Adds `status` to the response status codes triggering a retry.
+    pub fn retry_on_status(mut self, status: StatusCode) -> Self {
+        if !self.statuses.contains(&status) {
+            self.statuses.push(status);
+        }
+        self
+    }
+
+    /// Replaces the response status codes triggering a retry. Pass an empty list to retry on transport errors only.
+    pub fn retry_on_statuses(mut self, statuses: &[StatusCode]) -> Self {
+        self.statuses = statuses.to_vec();
+        self
+    }
+
+    /// Sets whether non-idempotent requests, like form submissions with `POST`, are retried, too. Defaults to `false`.
+    pub const fn retry_non_idempotent(mut self, retry_non_idempotent: bool) -> Self {
+        self.retry_non_idempotent = retry_non_idempotent;
+        self
+    }
+
+    /// Sets the longest wait requested by a `Retry-After` header that is honoured. Longer waits end the retries.
+    /// Defaults to 60s.
+    pub const fn max_retry_after(mut self, max_retry_after: Duration) -> Self {
+        self.max_retry_after = max_retry_after;
+        self
+    }
+
+    /// Returns the backoff before retry number `retry` (starting at 1), without jitter.
+    pub fn backoff_for(&self, retry: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powf(f64::from(retry.saturating_sub(1)));
+        Duration::try_from_secs_f64(self.initial_backoff.as_secs_f64() * factor)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+
+    /// Returns the delay before sending `request` again after attempt number `attempt` (starting at 1) ended with
+    /// `result`, or `None` if it is not to be retried.
+    pub(crate) fn retry_delay(
+        &self,
+        request: &Request,
+        attempt: u32,
+        result: &transport::Result<Response>,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts
+            || !(self.retry_non_idempotent || is_idempotent(request.method()))
+        {
+            return None;
+        }
+
+        let backoff = self.backoff_for(attempt);
+        let backoff = match self.jitter {
+            true => backoff.mul_f64(0.5 + fastrand::f64() / 2.0),
+            false => backoff,
+        };
+
+        match result {
+            Err(error) => is_transient(error).then_some(backoff),
+            Ok(response) if self.statuses.contains(&response.status()) => {
+                match retry_after(response) {
+                    Some(retry_after) if retry_after > self.max_retry_after => None,
+                    Some(retry_after) => Some(retry_after),
+                    None => Some(backoff),
+                }
+            }
+            Ok(_) => None,
+        }
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    [
+        Method::GET,
+        Method::HEAD,
+        Method::OPTIONS,
+        Method::TRACE,
+        Method::PUT,
+        Method::DELETE,
+    ]
+    .contains(method)
+}
+
+/// Returns `true` for errors that may go away by sending the request again, e.g. refused or reset connections.
+fn is_transient(error: &transport::Error) -> bool {
+    match error {
+        transport::Error::SendRequestError { source } => {
+            !source.is_builder() && !source.is_redirect()
+        }
+        transport::Error::ResponseBodyDecodeError { .. } => true,
+        _ => false,
+    }
+}
+
+/// Returns the wait requested by the `Retry-After` header of `response`, given either in seconds or as http date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    match value.parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => httpdate::parse_http_date(value)
+            .ok()
+            .map(|date| date.duration_since(SystemTime::now()).unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use crate::{
+        browser::Browser,
+        mock::{self, MockTransport},
+        page::Page,
+        transport::{self, HttpTransport, Request, Response, Transport},
+    };
+    use reqwest::{header::RETRY_AFTER, Method, StatusCode, Url};
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    /// Refuses the first `failures` connections, then answers with a page.
+    #[derive(Debug)]
+    struct Flaky {
+        failures: usize,
+        attempts: AtomicUsize,
+    }
+
+    impl Transport for Flaky {
+        fn send(&self, request: &Request) -> transport::Result<Response> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                let refused = Request::new(Method::GET, Url::parse("http://127.0.0.1:1/").unwrap());
+                return HttpTransport::new()?.send(&refused);
+            }
+            Ok(mock::html(request.url().path()))
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy::new().backoff(Duration::ZERO, Duration::ZERO)
+    }
+
+    #[test]
+    fn compute_backoff() {
+        let policy = RetryPolicy::new()
+            .backoff(Duration::from_millis(100), Duration::from_millis(500))
+            .jitter(false);
+
+        assert_eq!(policy.backoff_for(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff_for(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff_for(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff_for(4), Duration::from_millis(500));
+        assert_eq!(policy.backoff_for(100), Duration::from_millis(500));
+
+        let request = Request::new(Method::GET, Url::parse("http://app.test/").unwrap());
+        let unavailable = Ok(Response::new(StatusCode::SERVICE_UNAVAILABLE));
+        let delay = RetryPolicy::new()
+            .retry_delay(&request, 1, &unavailable)
+            .unwrap();
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+
+        let retry_after = Ok(Response::new(StatusCode::TOO_MANY_REQUESTS)
+            .with_header(RETRY_AFTER, "7".parse().unwrap()));
+        assert_eq!(
+            policy.retry_delay(&request, 1, &retry_after),
+            Some(Duration::from_secs(7))
+        );
+        let policy = policy.max_retry_after(Duration::from_secs(5));
+        assert_eq!(policy.retry_delay(&request, 1, &retry_after), None);
+        assert_eq!(policy.retry_delay(&request, 3, &unavailable), None);
+        assert_eq!(policy.retry_delay(&request, 1, &Ok(mock::html(""))), None);
+    }
+
+    #[test]
+    fn retry_transient_failures() {
+        let browser = Browser::builder()
+            .transport(Flaky {
+                failures: 2,
+                attempts: AtomicUsize::new(0),
+            })
+            .retry_policy(policy())
+            .finish()
+            .unwrap();
+        assert_eq!(
+            browser
+                .navigate_to("http://app.test/up", None)
+                .unwrap()
+                .text(),
+            "/up"
+        );
+
+        let browser = Browser::builder()
+            .transport(Flaky {
+                failures: 3,
+                attempts: AtomicUsize::new(0),
+            })
+            .retry_policy(policy())
+            .finish()
+            .unwrap();
+        assert!(browser.navigate_to("http://app.test/up", None).is_err());
+
+        let transport = MockTransport::from_fn(|request| match request.url().path() {
+            "/busy" => Response::new(StatusCode::SERVICE_UNAVAILABLE),
+            path => mock::html(path),
+        });
+        let log = transport.request_log();
+        let browser = Browser::builder()
+            .transport(transport)
+            .retry_policy(policy().max_attempts(4))
+            .finish()
+            .unwrap();
+        let page = browser.navigate_to("http://app.test/busy", None).unwrap();
+        assert_eq!(*page.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(log.len(), 4);
+    }
+
+    #[test]
+    fn never_retry_post_unless_asked() {
+        let transport = MockTransport::new()
+            .respond(
+                Method::GET,
+                "/",
+                mock::html(
+                    "<form action='/order' method='post'><input type='text' name='item'></form>",
+                ),
+            )
+            .respond(
+                Method::POST,
+                "/order",
+                Response::new(StatusCode::BAD_GATEWAY),
+            );
+        let log = transport.request_log();
+        let browser = Browser::builder()
+            .transport(transport)
+            .retry_policy(policy())
+            .finish()
+            .unwrap();
+
+        let page = browser.navigate_to("http://shop.test/", None).unwrap();
+        let page = browser.submit_form(page.form(0).unwrap(), None).unwrap();
+        assert_eq!(*page.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(log.len(), 2);
+
+        let transport = MockTransport::new().respond(
+            Method::POST,
+            "/order",
+            Response::new(StatusCode::BAD_GATEWAY),
+        );
+        let log = transport.request_log();
+        let browser = Browser::builder()
+            .transport(transport)
+            .retry_policy(policy().retry_non_idempotent(true))
+            .finish()
+            .unwrap();
+        let form_page = Page::from_html(
+            "http://shop.test/",
+            "<form action='/order' method='post'></form>",
+        )
+        .unwrap();
+        browser
+            .submit_form(form_page.form(0).unwrap(), None)
+            .unwrap();
+        assert_eq!(log.len(), 3);
+    }
+}
@@ -0,0 +1,284 @@
+//! Module containing the [`RobotsTxt`][RobotsTxt] parser for the
+//! [Robots Exclusion Protocol (RFC 9309)](https://www.rfc-editor.org/rfc/rfc9309).
+//!
+//! A robots.txt file consists of groups of rules for one or more user agents. Rules allow or disallow url paths,
+//! optionally using the wildcard `*` and the end anchor `$`; the longest matching rule wins, with ties going to
+//! `Allow`. Crawlers not named by any group follow the rules of the group for `*`. The non-standard `Crawl-delay` and
+//! `Sitemap` lines are supported, too.
+//!
+//! Enable `BrowserBuilder::respect_robots_txt()` to make a [`Browser`][crate::Browser] fetch and enforce robots.txt
+//! files automatically.
+//!
+//! ```
+//! use no_browser::robots::RobotsTxt;
+//! use reqwest::Url;
+//!
+//! let robots = RobotsTxt::parse(
+//!     "User-agent: *\n\
+//!      Disallow: /private/\n\
+//!      Allow: /private/public.html\n\
+//!      Crawl-delay: 2\n\
+//!      Sitemap: https://example.com/sitemap.xml",
+//! );
+//!
+//! let url = |path: &str| Url::parse("https://example.com").unwrap().join(path).unwrap();
+//! assert!(robots.is_allowed("MyBot/1.0", &url("/index.html")));
+//! assert!(!robots.is_allowed("MyBot/1.0", &url("/private/secret.html")));
+//! assert!(robots.is_allowed("MyBot/1.0", &url("/private/public.html")));
+//! assert_eq!(robots.crawl_delay("MyBot/1.0").unwrap().as_secs(), 2);
+//! assert_eq!(robots.sitemaps(), ["https://example.com/sitemap.xml"]);
+//! ```
+
+use reqwest::Url;
+use std::time::Duration;
+
+/// A parsed robots.txt file. See the [module documentation][crate::robots] for details.
+#[derive(Debug, Clone, Default)]
+pub struct RobotsTxt {
+    groups: Vec<Group>,
+    sitemaps: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Group {
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+impl RobotsTxt {
+    /// Parses the content of a robots.txt file. Parsing is lenient: unknown and malformed lines are ignored.
+    pub fn parse(text: &str) -> Self {
+        let mut robots = Self::default();
+        let mut group: Option<Group> = None;
+        // whether a rule or crawl-delay line (even an empty one) was seen since the last user-agent line
+        let mut in_rules = false;
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let value = value.trim();
+
+            match key.trim().to_ascii_lowercase().as_str() {
+                "user-agent" => {
+                    // consecutive user-agent lines share a group, a user-agent line after rules starts a new one
+                    if in_rules {
+                        robots.groups.extend(group.take());
+                        in_rules = false;
+                    }
+                    group
+                        .get_or_insert_with(Group::default)
+                        .agents
+                        .push(value.to_ascii_lowercase());
+                }
+                key @ ("allow" | "disallow") => {
+                    let Some(group) = group.as_mut() else {
+                        continue; // rules before the first user-agent line
+                    };
+                    in_rules = true;
+                    if !value.is_empty() {
+                        group.rules.push(Rule {
+                            allow: key == "allow",
+                            pattern: value.to_owned(),
+                        });
+                    }
+                }
+                "crawl-delay" => {
+                    in_rules |= group.is_some();
+                    if let (Some(group), Ok(delay)) = (group.as_mut(), value.parse::<f64>()) {
+                        group.crawl_delay = Duration::try_from_secs_f64(delay).ok();
+                    }
+                }
+                "sitemap" if !value.is_empty() => robots.sitemaps.push(value.to_owned()),
+                _ => {}
+            }
+        }
+        robots.groups.extend(group);
+
+        robots
+    }
+
+    /// Returns a [`RobotsTxt`][RobotsTxt] allowing everything, as used for missing robots.txt files.
+    pub fn allow_all() -> Self {
+        Self::default()
+    }
+
+    /// Returns a [`RobotsTxt`][RobotsTxt] disallowing everything, as used for unreachable robots.txt files.
+    pub fn disallow_all() -> Self {
+        Self::parse("User-agent: *\nDisallow: /")
+    }
+
+    /// Returns `true` if the crawler identified by `user_agent` may access `url`. The product token of `user_agent`
+    /// (e.g. `MyBot` for `MyBot/1.0 (+https://example.com)`) is matched case-insensitively against the groups. The
+    /// robots.txt file itself is always allowed.
+    pub fn is_allowed(&self, user_agent: &str, url: &Url) -> bool {
+        let path = match url.query() {
+            Some(query) => format!("{}?{query}", url.path()),
+            None => url.path().to_owned(),
+        };
+        if path == "/robots.txt" {
+            return true;
+        }
+
+        self.groups_for(user_agent)
+            .flat_map(|group| &group.rules)
+            .filter(|rule| matches(&rule.pattern, &path))
+            .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+            .is_none_or(|rule| rule.allow)
+    }
+
+    /// Returns the delay between requests requested for the crawler identified by `user_agent`, if any.
+    pub fn crawl_delay(&self, user_agent: &str) -> Option<Duration> {
+        self.groups_for(user_agent)
+            .filter_map(|group| group.crawl_delay)
+            .max()
+    }
+
+    /// Returns the sitemap urls listed in the robots.txt file.
+    pub fn sitemaps(&self) -> &[String] {
+        &self.sitemaps
+    }
+
+    /// Returns the groups applying to `user_agent`: all groups naming its product token or, if there are none, all
+    /// groups for `*`.
+    fn groups_for(&self, user_agent: &str) -> impl Iterator<Item = &Group> {
+        let token = product_token(user_agent);
+        let named = self
+            .groups
+            .iter()
+            .any(|group| group.agents.contains(&token));
+        let wanted = if named { token } else { "*".to_owned() };
+
+        self.groups
+            .iter()
+            .filter(move |group| group.agents.contains(&wanted))
+    }
+}
+
+/// Returns the lowercase product token of `user_agent`, e.g. `mybot` for `MyBot/1.0 (+https://example.com)`.
+fn product_token(user_agent: &str) -> String {
+    user_agent
+        .split(|c: char| c == '/' || c.is_whitespace())
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Returns `true` if `path` matches the rule `pattern`, which is a path prefix with wildcards `*` and an optional end
+/// anchor `$`.
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let pattern = pattern.as_bytes();
+    let path = path.as_bytes();
+
+    // matched[j]: the pattern processed so far matches the first j bytes of path
+    let mut matched = vec![false; path.len() + 1];
+    matched[0] = true;
+    for &p in pattern {
+        if p == b'*' {
+            for j in 1..=path.len() {
+                matched[j] = matched[j] || matched[j - 1];
+            }
+        } else {
+            for j in (1..=path.len()).rev() {
+                matched[j] = matched[j - 1] && path[j - 1] == p;
+            }
+            matched[0] = false;
+        }
+    }
+
+    match anchored {
+        true => matched[path.len()],
+        false => matched.contains(&true),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{matches, RobotsTxt};
+    use reqwest::Url;
+    use std::time::Duration;
+
+    static ROBOTS: &str = r#"
+# comments are ignored
+Sitemap: https://example.com/sitemap.xml
+
+User-agent: FooBot
+User-agent: BarBot
+Disallow: /                 # everything ...
+Allow: /public/             # ... but the public part
+Allow: /*.css$
+Crawl-delay: 0.5
+
+User-agent: *
+Disallow: /admin
+Disallow: /search?
+Disallow:
+
+User-agent: foobot
+Disallow: /public/drafts/
+"#;
+
+    fn url(path: &str) -> Url {
+        Url::parse("https://example.com")
+            .unwrap()
+            .join(path)
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_and_match_groups() {
+        let robots = RobotsTxt::parse(ROBOTS);
+
+        assert_eq!(robots.sitemaps(), ["https://example.com/sitemap.xml"]);
+
+        assert!(!robots.is_allowed("FooBot/2.1", &url("/index.html")));
+        assert!(robots.is_allowed("FooBot/2.1", &url("/public/index.html")));
+        assert!(!robots.is_allowed("foobot", &url("/public/drafts/1.html")));
+        assert!(robots.is_allowed("FooBot", &url("/assets/main.css")));
+        assert!(!robots.is_allowed("FooBot", &url("/assets/main.css?v=1")));
+        assert!(robots.is_allowed("BarBot", &url("/robots.txt")));
+        assert_eq!(
+            robots.crawl_delay("BarBot"),
+            Some(Duration::from_millis(500))
+        );
+
+        assert!(robots.is_allowed("OtherBot", &url("/index.html")));
+        assert!(!robots.is_allowed("OtherBot", &url("/admin/users")));
+        assert!(!robots.is_allowed("OtherBot", &url("/search?q=rust")));
+        assert!(robots.is_allowed("OtherBot", &url("/search")));
+        assert_eq!(robots.crawl_delay("OtherBot"), None);
+
+        // an empty disallow allows everything and still ends its group
+        let robots =
+            RobotsTxt::parse("User-agent: Googlebot\nDisallow:\n\nUser-agent: *\nDisallow: /");
+        assert!(robots.is_allowed("Googlebot/2.1", &url("/index.html")));
+        assert!(!robots.is_allowed("OtherBot", &url("/index.html")));
+
+        assert!(RobotsTxt::allow_all().is_allowed("FooBot", &url("/admin")));
+        assert!(!RobotsTxt::disallow_all().is_allowed("FooBot", &url("/")));
+    }
+
+    #[test]
+    fn match_wildcards() {
+        assert!(matches("/fish", "/fish.html"));
+        assert!(matches("/fish*", "/fish/salmon.html"));
+        assert!(!matches("/fish", "/Fish.asp"));
+        assert!(matches("/*.php", "/folder/filename.php?parameters"));
+        assert!(!matches("/*.php$", "/filename.php?parameters"));
+        assert!(matches("/*.php$", "/folder/filename.php"));
+        assert!(matches("/fish*.php", "/fishheads/catfish.php?parameters"));
+        assert!(!matches("/fish*.php", "/Fish.PHP"));
+    }
+}
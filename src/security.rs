@@ -0,0 +1,799 @@
+//! Module containing the [`SecurityReport`][SecurityReport] of a [`Page`][Page], as used for security reviews.
+//!
+//! The report audits the response headers of a page, using [`TypedHeaders`][TypedHeaders], and the references in its
+//! html:
+//! * the `Content-Security-Policy`, e.g. missing policies, `'unsafe-inline'` or wildcard script sources;
+//! * `Strict-Transport-Security` for https pages, and pages served over plain http;
+//! * clickjacking protection via `X-Frame-Options` or the `frame-ancestors` directive;
+//! * `X-Content-Type-Options`, `Referrer-Policy` and `Permissions-Policy`;
+//! * the `Secure`, `HttpOnly` and `SameSite` flags and name prefixes of cookies set by the response;
+//! * mixed content, i.e. `http:` scripts, frames, stylesheets, images, media and form targets on https pages.
+//!
+//! Every issue is reported as [`Finding`][Finding] with a [`Severity`][Severity]; a page without findings passes all
+//! checks. The checks follow common recommendations (e.g. the [OWASP Secure Headers
+//! Project](https://owasp.org/www-project-secure-headers/)) and don't replace a review of the context of a page.
+//!
+//! ```
+//! use no_browser::{
+//!     page::Page,
+//!     security::{Check, Severity},
+//! };
+//! use reqwest::header::{HeaderValue, SET_COOKIE};
+//!
+//! let page = Page::builder("https://example.com/")
+//!     .header(SET_COOKIE, HeaderValue::from_static("sid=42; Secure"))
+//!     .build_from_text(r#"<script src="http://cdn.example.com/app.js"></script>"#)?;
+//! let report = page.security_report();
+//!
+//! assert_eq!(report.highest_severity(), Some(Severity::High));
+//! let mixed_content = report.findings_for(Check::MixedContent).next().unwrap();
+//! assert_eq!(mixed_content.subject(), Some("http://cdn.example.com/app.js"));
+//!
+//! for finding in report.at_least(Severity::Medium) {
+//!     println!("{finding}");
+//! }
+//! # Ok::<(), no_browser::page::Error>(())
+//! ```
+
+use crate::{
+    headers::{ContentSecurityPolicy, SameSite, TypedHeaders},
+    page::{self, Page},
+};
+use reqwest::{
+    header::{REFERRER_POLICY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS},
+    Url,
+};
+use scraper::Selector;
+use std::{
+    fmt::{self, Display, Formatter},
+    time::Duration,
+};
+
+/// The minimum `max-age` of `Strict-Transport-Security` recommended for production sites: 180 days.
+const MIN_HSTS_MAX_AGE: Duration = Duration::from_secs(180 * 24 * 60 * 60);
+
+/// Powerful features, that shouldn't be delegated to every origin by the `Permissions-Policy`.
+const POWERFUL_FEATURES: &[&str] = &[
+    "camera",
+    "display-capture",
+    "geolocation",
+    "microphone",
+    "payment",
+    "usb",
+];
+
+/// The severity of a [`Finding`][Finding], ordered from `Info` to `High`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// A hint for hardening, without an immediate risk.
+    Info,
+    /// A missing defense-in-depth measure.
+    Low,
+    /// A weakness that makes attacks considerably easier.
+    Medium,
+    /// A weakness that is exploitable as is, or is blocked by browsers and breaks the page.
+    High,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Info => "info",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        })
+    }
+}
+
+/// The check a [`Finding`][Finding] was reported by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Check {
+    /// The `Content-Security-Policy` header.
+    ContentSecurityPolicy,
+    /// The `Strict-Transport-Security` header and the use of https.
+    StrictTransportSecurity,
+    /// Clickjacking protection via `X-Frame-Options` or the `frame-ancestors` directive.
+    FrameOptions,
+    /// The `X-Content-Type-Options` header.
+    ContentTypeOptions,
+    /// The `Referrer-Policy` header.
+    ReferrerPolicy,
+    /// The `Permissions-Policy` header.
+    PermissionsPolicy,
+    /// The flags of cookies set by `Set-Cookie` headers.
+    Cookies,
+    /// `http:` references on https pages.
+    MixedContent,
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::ContentSecurityPolicy => "content-security-policy",
+            Self::StrictTransportSecurity => "strict-transport-security",
+            Self::FrameOptions => "frame-options",
+            Self::ContentTypeOptions => "content-type-options",
+            Self::ReferrerPolicy => "referrer-policy",
+            Self::PermissionsPolicy => "permissions-policy",
+            Self::Cookies => "cookies",
+            Self::MixedContent => "mixed-content",
+        })
+    }
+}
+
+/// A single issue found by a [`Check`][Check].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    check: Check,
+    severity: Severity,
+    subject: Option<String>,
+    message: String,
+}
+
+impl Finding {
+    fn new(check: Check, severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            check,
+            severity,
+            subject: None,
+            message: message.into(),
+        }
+    }
+
+    fn with_subject(mut self, subject: impl Into<String>) -> Self {
+        self.subject = Some(subject.into());
+        self
+    }
+
+    /// Returns the check that reported this finding.
+    pub const fn check(&self) -> Check {
+        self.check
+    }
+
+    /// Returns the severity of this finding.
+    pub const fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns what this finding is about, if it's not the header as a whole, e.g. the name of a cookie, a CSP
+    /// directive or the url of a mixed content reference.
+    pub fn subject(&self) -> Option<&str> {
+        self.subject.as_deref()
+    }
+
+    /// Returns a human-readable description of the issue.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.severity, self.check)?;
+        if let Some(subject) = &self.subject {
+            write!(f, " ({subject})")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The security audit of a single [`Page`][Page]. See the [module documentation][crate::security] for details.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecurityReport {
+    url: Url,
+    findings: Vec<Finding>,
+}
+
+impl SecurityReport {
+    /// Audits the response headers and html references of `page`.
+    pub fn audit(page: &Page) -> Self {
+        let headers = page.typed_headers();
+        let https = page.url().scheme() == "https";
+        let policies = headers.content_security_policy();
+
+        let mut findings = Vec::new();
+        check_content_security_policy(&headers, &policies, &mut findings);
+        check_strict_transport_security(&headers, https, &mut findings);
+        check_frame_options(&headers, &policies, &mut findings);
+        check_content_type_options(&headers, &mut findings);
+        check_referrer_policy(&headers, &mut findings);
+        check_permissions_policy(&headers, &mut findings);
+        check_cookies(&headers, https, &mut findings);
+        if https {
+            check_mixed_content(page, &mut findings);
+        }
+
+        // most severe first, keeping the order of the checks otherwise
+        findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+
+        Self {
+            url: page.url().clone(),
+            findings,
+        }
+    }
+
+    /// Returns the url of the audited page.
+    pub const fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns all findings, most severe first.
+    pub fn findings(&self) -> &[Finding] {
+        &self.findings
+    }
+
+    /// Returns the findings reported by `check`.
+    pub fn findings_for(&self, check: Check) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(move |finding| finding.check == check)
+    }
+
+    /// Returns the findings with at least the given `severity`.
+    pub fn at_least(&self, severity: Severity) -> impl Iterator<Item = &Finding> {
+        self.findings
+            .iter()
+            .filter(move |finding| finding.severity >= severity)
+    }
+
+    /// Returns the severity of the most severe finding, or `None` if there are no findings.
+    pub fn highest_severity(&self) -> Option<Severity> {
+        self.findings.first().map(Finding::severity)
+    }
+
+    /// Returns `true` if there are no findings with at least the given `severity`, e.g. to fail a release pipeline on
+    /// `Medium` and `High` findings only.
+    pub fn passes(&self, severity: Severity) -> bool {
+        self.at_least(severity).next().is_none()
+    }
+}
+
+fn check_content_security_policy(
+    headers: &TypedHeaders,
+    policies: &[ContentSecurityPolicy],
+    findings: &mut Vec<Finding>,
+) {
+    let check = Check::ContentSecurityPolicy;
+    if policies.is_empty() {
+        let message = match headers.content_security_policy_report_only().is_empty() {
+            true => "No Content-Security-Policy is set.",
+            false => "The Content-Security-Policy is only monitored (report-only), not enforced.",
+        };
+        findings.push(Finding::new(check, Severity::Medium, message));
+        return;
+    }
+
+    // the policies apply at the same time, so a directive is safe if any policy restricts it
+    let restricted = |directive: &str, safe: &dyn Fn(&[String]) -> bool| {
+        policies
+            .iter()
+            .any(|policy| policy.effective_directive(directive).is_some_and(safe))
+    };
+
+    if !restricted("script-src", &|_| true) {
+        findings.push(
+            Finding::new(
+                check,
+                Severity::Medium,
+                "Scripts are not restricted, neither by script-src nor by default-src.",
+            )
+            .with_subject("script-src"),
+        );
+    } else {
+        if !restricted("script-src", &|sources| {
+            !has_source(sources, "'unsafe-inline'") || allows_strict_inline(sources)
+        }) {
+            findings.push(
+                Finding::new(
+                    check,
+                    Severity::High,
+                    "Inline scripts are allowed by 'unsafe-inline', without nonces or hashes.",
+                )
+                .with_subject("script-src"),
+            );
+        }
+        if !restricted("script-src", &|sources| {
+            !sources.iter().any(|source| is_broad_source(source))
+        }) {
+            findings.push(
+                Finding::new(
+                    check,
+                    Severity::High,
+                    "Scripts may be loaded from any host or scheme (e.g. '*', 'https:' or 'data:').",
+                )
+                .with_subject("script-src"),
+            );
+        }
+        if !restricted("script-src", &|sources| {
+            !has_source(sources, "'unsafe-eval'")
+        }) {
+            findings.push(
+                Finding::new(
+                    check,
+                    Severity::Medium,
+                    "String evaluation, like eval(), is allowed by 'unsafe-eval'.",
+                )
+                .with_subject("script-src"),
+            );
+        }
+    }
+
+    if !restricted("object-src", &|sources| {
+        sources.len() == 1 && has_source(sources, "'none'")
+    }) {
+        findings.push(
+            Finding::new(
+                check,
+                Severity::Low,
+                "Plugins are not disabled by object-src 'none'.",
+            )
+            .with_subject("object-src"),
+        );
+    }
+    if policies
+        .iter()
+        .all(|policy| policy.directive("base-uri").is_none())
+    {
+        findings.push(
+            Finding::new(
+                check,
+                Severity::Low,
+                "The <base> element is not restricted by base-uri.",
+            )
+            .with_subject("base-uri"),
+        );
+    }
+}
+
+fn has_source(sources: &[String], source: &str) -> bool {
+    sources
+        .iter()
+        .any(|other| other.eq_ignore_ascii_case(source))
+}
+
+/// Returns `true` if browsers ignore `'unsafe-inline'` in `sources`, because of nonces, hashes or `'strict-dynamic'`.
+fn allows_strict_inline(sources: &[String]) -> bool {
+    sources.iter().any(|source| {
+        let source = source.to_ascii_lowercase();
+        source.starts_with("'nonce-")
+            || source.starts_with("'sha256-")
+            || source.starts_with("'sha384-")
+            || source.starts_with("'sha512-")
+            || source == "'strict-dynamic'"
+    })
+}
+
+fn is_broad_source(source: &str) -> bool {
+    matches!(
+        source.to_ascii_lowercase().as_str(),
+        "*" | "http:" | "https:" | "data:" | "blob:" | "http://*" | "https://*"
+    )
+}
+
+fn check_strict_transport_security(
+    headers: &TypedHeaders,
+    https: bool,
+    findings: &mut Vec<Finding>,
+) {
+    let check = Check::StrictTransportSecurity;
+    if !https {
+        findings.push(Finding::new(
+            check,
+            Severity::High,
+            "The page is served over plain http.",
+        ));
+        return;
+    }
+
+    let Some(hsts) = headers.strict_transport_security() else {
+        findings.push(Finding::new(
+            check,
+            Severity::Medium,
+            "No valid Strict-Transport-Security is set.",
+        ));
+        return;
+    };
+
+    if hsts.max_age().is_zero() {
+        findings.push(Finding::new(
+            check,
+            Severity::Medium,
+            "Strict-Transport-Security is disabled by max-age=0.",
+        ));
+    } else if hsts.max_age() < MIN_HSTS_MAX_AGE {
+        findings.push(Finding::new(
+            check,
+            Severity::Low,
+            format!(
+                "The max-age of {}s is shorter than the recommended 180 days.",
+                hsts.max_age().as_secs()
+            ),
+        ));
+    }
+    if !hsts.include_subdomains() {
+        findings.push(Finding::new(
+            check,
+            Severity::Info,
+            "Subdomains are not covered, as includeSubDomains is missing.",
+        ));
+    }
+}
+
+fn check_frame_options(
+    headers: &TypedHeaders,
+    policies: &[ContentSecurityPolicy],
+    findings: &mut Vec<Finding>,
+) {
+    let check = Check::FrameOptions;
+    // frame-ancestors supersedes X-Frame-Options in all current browsers
+    if let Some(ancestors) = policies
+        .iter()
+        .find_map(|policy| policy.directive("frame-ancestors"))
+    {
+        if ancestors.iter().any(|source| is_broad_source(source)) {
+            findings.push(
+                Finding::new(
+                    check,
+                    Severity::Medium,
+                    "Any site may frame the page, as frame-ancestors allows all sources.",
+                )
+                .with_subject("frame-ancestors"),
+            );
+        }
+        return;
+    }
+
+    match headers.get(X_FRAME_OPTIONS).map(str::trim) {
+        Some(value) if value.eq_ignore_ascii_case("deny") || value.eq_ignore_ascii_case("sameorigin") => {}
+        Some(value) => findings.push(Finding::new(
+            check,
+            Severity::Medium,
+            format!(
+                "X-Frame-Options '{value}' is not supported, only DENY and SAMEORIGIN are, and frame-ancestors is missing."
+            ),
+        )),
+        None => findings.push(Finding::new(
+            check,
+            Severity::Medium,
+            "The page may be framed by any site (clickjacking), as both X-Frame-Options and frame-ancestors are missing.",
+        )),
+    }
+}
+
+fn check_content_type_options(headers: &TypedHeaders, findings: &mut Vec<Finding>) {
+    let check = Check::ContentTypeOptions;
+    match headers.get(X_CONTENT_TYPE_OPTIONS).map(str::trim) {
+        Some(value) if value.eq_ignore_ascii_case("nosniff") => {}
+        Some(value) => findings.push(Finding::new(
+            check,
+            Severity::Low,
+            format!("X-Content-Type-Options '{value}' is invalid, only 'nosniff' is supported."),
+        )),
+        None => findings.push(Finding::new(
+            check,
+            Severity::Low,
+            "MIME type sniffing is not disabled by X-Content-Type-Options: nosniff.",
+        )),
+    }
+}
+
+fn check_referrer_policy(headers: &TypedHeaders, findings: &mut Vec<Finding>) {
+    let check = Check::ReferrerPolicy;
+    let Some(value) = headers.get(REFERRER_POLICY) else {
+        findings.push(Finding::new(
+            check,
+            Severity::Low,
+            "No Referrer-Policy is set, so the browser default applies.",
+        ));
+        return;
+    };
+
+    // browsers use the last policy they know, to allow fallbacks for new policies
+    let policy = value
+        .split(',')
+        .map(|policy| policy.trim().to_ascii_lowercase())
+        .rfind(|policy| {
+            matches!(
+                policy.as_str(),
+                "no-referrer"
+                    | "no-referrer-when-downgrade"
+                    | "same-origin"
+                    | "origin"
+                    | "strict-origin"
+                    | "origin-when-cross-origin"
+                    | "strict-origin-when-cross-origin"
+                    | "unsafe-url"
+            )
+        });
+
+    match policy.as_deref() {
+        None => findings.push(Finding::new(
+            check,
+            Severity::Low,
+            format!("Referrer-Policy '{value}' is invalid, so the browser default applies."),
+        )),
+        Some("unsafe-url") => findings.push(Finding::new(
+            check,
+            Severity::Medium,
+            "Full urls, including paths and queries, are sent to every site by 'unsafe-url'.",
+        )),
+        Some(policy @ ("no-referrer-when-downgrade" | "origin-when-cross-origin")) => findings
+            .push(Finding::new(
+                check,
+                Severity::Low,
+                format!("'{policy}' sends full urls to other sites."),
+            )),
+        Some(_) => {}
+    }
+}
+
+fn check_permissions_policy(headers: &TypedHeaders, findings: &mut Vec<Finding>) {
+    let check = Check::PermissionsPolicy;
+    let Some(value) = headers.get("permissions-policy") else {
+        findings.push(Finding::new(
+            check,
+            Severity::Low,
+            "No Permissions-Policy is set to restrict powerful browser features.",
+        ));
+        return;
+    };
+
+    for directive in value.split(',') {
+        let Some((feature, allowlist)) = directive.split_once('=') else {
+            continue;
+        };
+        let feature = feature.trim().to_ascii_lowercase();
+        if allowlist.trim() == "*" && POWERFUL_FEATURES.contains(&feature.as_str()) {
+            findings.push(
+                Finding::new(
+                    check,
+                    Severity::Low,
+                    format!("The feature '{feature}' is delegated to every origin."),
+                )
+                .with_subject(feature),
+            );
+        }
+    }
+}
+
+fn check_cookies(headers: &TypedHeaders, https: bool, findings: &mut Vec<Finding>) {
+    let check = Check::Cookies;
+    for cookie in headers.set_cookies() {
+        let finding = |severity, message: &str| {
+            Finding::new(check, severity, message).with_subject(cookie.name())
+        };
+
+        if !cookie.secure() {
+            let message = match https {
+                true => "The cookie lacks the Secure flag and may be sent over plain http.",
+                false => "The cookie is set over plain http without the Secure flag.",
+            };
+            findings.push(finding(Severity::Medium, message));
+        }
+        if !cookie.http_only() {
+            findings.push(finding(
+                Severity::Low,
+                "The cookie lacks the HttpOnly flag and is readable by scripts.",
+            ));
+        }
+        match cookie.same_site() {
+            Some(SameSite::None) if !cookie.secure() => findings.push(finding(
+                Severity::Medium,
+                "SameSite=None without the Secure flag is rejected by browsers.",
+            )),
+            Some(SameSite::None) => findings.push(finding(
+                Severity::Info,
+                "SameSite=None sends the cookie with cross-site requests.",
+            )),
+            Some(_) => {}
+            None => findings.push(finding(
+                Severity::Info,
+                "The cookie has no SameSite attribute, so the browser default applies.",
+            )),
+        }
+
+        let name = cookie.name();
+        let prefixed = |prefix: &str| {
+            name.get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+        };
+        if prefixed("__Host-")
+            && (!cookie.secure() || cookie.domain().is_some() || cookie.path() != Some("/"))
+        {
+            findings.push(finding(
+                Severity::Medium,
+                "__Host- cookies must be Secure, with Path=/ and without Domain, or browsers reject them.",
+            ));
+        } else if prefixed("__Secure-") && !cookie.secure() {
+            findings.push(finding(
+                Severity::Medium,
+                "__Secure- cookies must be Secure, or browsers reject them.",
+            ));
+        }
+    }
+}
+
+fn check_mixed_content(page: &Page, findings: &mut Vec<Finding>) {
+    // (selector, attribute, severity): active content is blocked by browsers, passive content is upgraded or
+    // displayed with a warning, and forms send user input unencrypted
+    const REFERENCES: &[(&str, &str, Severity)] = &[
+        ("script[src]", "src", Severity::High),
+        ("iframe[src]", "src", Severity::High),
+        ("frame[src]", "src", Severity::High),
+        ("object[data]", "data", Severity::High),
+        ("embed[src]", "src", Severity::High),
+        ("link[rel~=stylesheet][href]", "href", Severity::High),
+        ("link[rel~=modulepreload][href]", "href", Severity::High),
+        ("form[action]", "action", Severity::Medium),
+        ("img[src]", "src", Severity::Low),
+        ("img[srcset]", "srcset", Severity::Low),
+        ("source[src]", "src", Severity::Low),
+        ("source[srcset]", "srcset", Severity::Low),
+        ("audio[src]", "src", Severity::Low),
+        ("video[src]", "src", Severity::Low),
+        ("video[poster]", "poster", Severity::Low),
+        ("link[rel~=icon][href]", "href", Severity::Low),
+    ];
+
+    let base = page.base_url();
+    for (selector, attribute, severity) in REFERENCES {
+        let selector = Selector::parse(selector).unwrap();
+        for element in page.html().select(&selector) {
+            let Some(value) = element.value().attr(attribute) else {
+                continue;
+            };
+            let urls = page::attribute_urls(attribute, value);
+            for url in urls.into_iter().filter_map(|value| base.join(value).ok()) {
+                if url.scheme() == "http" {
+                    findings.push(
+                        Finding::new(
+                            Check::MixedContent,
+                            *severity,
+                            format!(
+                                "<{} {attribute}> references an http url on an https page.",
+                                element.value().name()
+                            ),
+                        )
+                        .with_subject(url.as_str()),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Check, SecurityReport, Severity};
+    use crate::page::Page;
+    use reqwest::header::{HeaderName, HeaderValue};
+
+    fn page(url: &str, headers: &[(&'static str, &'static str)], html: &str) -> Page {
+        let mut builder = Page::builder(url);
+        for (name, value) in headers {
+            builder = builder.header(
+                HeaderName::from_static(name),
+                HeaderValue::from_static(value),
+            );
+        }
+        builder.build_from_text(html).unwrap()
+    }
+
+    #[test]
+    fn pass_hardened_page() {
+        let page = page(
+            "https://app.test/",
+            &[
+                (
+                    "content-security-policy",
+                    "default-src 'self'; script-src 'self' 'unsafe-inline' 'nonce-r4nd0m'; object-src 'none'; \
+                     base-uri 'none'; frame-ancestors 'self'",
+                ),
+                ("strict-transport-security", "max-age=63072000; includeSubDomains; preload"),
+                ("x-content-type-options", "nosniff"),
+                ("referrer-policy", "no-referrer, strict-origin-when-cross-origin"),
+                ("permissions-policy", "camera=(), geolocation=(self)"),
+                ("set-cookie", "__Host-sid=1; Path=/; Secure; HttpOnly; SameSite=Lax"),
+            ],
+            r#"<script src="/app.js"></script><a href="http://other.test/">other</a>"#,
+        );
+
+        let report = page.security_report();
+        assert!(report.findings().is_empty(), "{:?}", report.findings());
+        assert!(report.passes(Severity::Info));
+        assert_eq!(report.highest_severity(), None);
+        assert_eq!(report.url().as_str(), "https://app.test/");
+    }
+
+    #[test]
+    fn report_weak_page() {
+        let page = page(
+            "https://app.test/shop/",
+            &[
+                (
+                    "content-security-policy",
+                    "script-src 'self' 'unsafe-inline' https:",
+                ),
+                ("strict-transport-security", "max-age=3600"),
+                ("x-frame-options", "ALLOW-FROM https://partner.test/"),
+                ("referrer-policy", "unsafe-url"),
+                ("permissions-policy", "camera=*, fullscreen=*"),
+                ("set-cookie", "__Secure-id=1; SameSite=None"),
+            ],
+            r#"<link rel="stylesheet" href="http://cdn.test/shop.css">
+               <img srcset="/logo.png 1x, http://cdn.test/logo@2x.png 2x">
+               <form action="http://app.test/pay"></form>"#,
+        );
+
+        let report = page.security_report();
+        let findings = |check| {
+            report
+                .findings_for(check)
+                .map(|finding| (finding.severity(), finding.subject()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            findings(Check::ContentSecurityPolicy),
+            [
+                (Severity::High, Some("script-src")),
+                (Severity::High, Some("script-src")),
+                (Severity::Low, Some("object-src")),
+                (Severity::Low, Some("base-uri")),
+            ]
+        );
+        assert_eq!(
+            findings(Check::StrictTransportSecurity),
+            [(Severity::Low, None), (Severity::Info, None)]
+        );
+        assert_eq!(findings(Check::FrameOptions), [(Severity::Medium, None)]);
+        assert_eq!(findings(Check::ContentTypeOptions), [(Severity::Low, None)]);
+        assert_eq!(findings(Check::ReferrerPolicy), [(Severity::Medium, None)]);
+        assert_eq!(
+            findings(Check::PermissionsPolicy),
+            [(Severity::Low, Some("camera"))]
+        );
+        assert_eq!(
+            findings(Check::Cookies),
+            [
+                (Severity::Medium, Some("__Secure-id")),
+                (Severity::Medium, Some("__Secure-id")),
+                (Severity::Medium, Some("__Secure-id")),
+                (Severity::Low, Some("__Secure-id")),
+            ]
+        );
+        assert_eq!(
+            findings(Check::MixedContent),
+            [
+                (Severity::High, Some("http://cdn.test/shop.css")),
+                (Severity::Medium, Some("http://app.test/pay")),
+                (Severity::Low, Some("http://cdn.test/logo@2x.png")),
+            ]
+        );
+
+        assert_eq!(report.highest_severity(), Some(Severity::High));
+        assert!(!report.passes(Severity::Medium));
+        assert_eq!(
+            report.findings()[0].to_string(),
+            "[high] content-security-policy (script-src): Inline scripts are allowed by 'unsafe-inline', without \
+             nonces or hashes."
+        );
+
+        let report = Page::from_html("http://app.test/", "<img src='http://app.test/a.png'>")
+            .unwrap()
+            .security_report();
+        assert_eq!(
+            findings_of(&report, Check::StrictTransportSecurity),
+            [Severity::High]
+        );
+        assert!(findings_of(&report, Check::MixedContent).is_empty());
+    }
+
+    fn findings_of(report: &SecurityReport, check: Check) -> Vec<Severity> {
+        report
+            .findings_for(check)
+            .map(|finding| finding.severity())
+            .collect()
+    }
+}
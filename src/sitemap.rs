@@ -0,0 +1,427 @@
+//! Module containing the [`Sitemap`][Sitemap] parser for [XML sitemaps](https://www.sitemaps.org/protocol.html),
+//! sitemap indexes and plain text sitemaps, optionally gzip compressed.
+//!
+//! Sitemaps are fetched through a [`Browser`][Browser], so cookies, middlewares, caching and robots.txt settings
+//! apply. `Sitemap::discover()` finds the sitemaps of a site via robots.txt, `Sitemap::fetch_urls()` fetches a sitemap
+//! and, for sitemap indexes, all referenced sitemaps.
+//!
+//! ```no_run
+//! use no_browser::{sitemap::Sitemap, Browser};
+//! use reqwest::Url;
+//!
+//! let browser = Browser::builder().finish()?;
+//! let site = Url::parse("https://example.com/")?;
+//!
+//! for sitemap in Sitemap::discover(&browser, &site)? {
+//!     for url in Sitemap::fetch_urls(&browser, &sitemap)? {
+//!         println!("{} (last modified: {:?})", url.loc(), url.lastmod());
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{
+    browser::{self, Browser},
+    xml::{child_text, elements},
+};
+use flate2::read::GzDecoder;
+use reqwest::{StatusCode, Url};
+use roxmltree::Document;
+use std::{
+    collections::{HashSet, VecDeque},
+    io::Read,
+};
+use thiserror::Error;
+
+/// An error occurred while fetching or parsing a sitemap.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The sitemap could not be loaded by the [`Browser`][Browser].
+    #[error("Failed to fetch '{url}': {source}")]
+    BrowserError {
+        /// The `url` of the sitemap.
+        url: String,
+        /// The underlying error.
+        #[source]
+        source: Box<browser::Error>,
+    },
+
+    /// The server answered with a status other than success.
+    #[error("Failed to fetch '{url}', status: {status}")]
+    HttpStatusError {
+        /// The `url` of the sitemap.
+        url: String,
+        /// The http `status` of the response.
+        status: StatusCode,
+    },
+
+    /// The gzip compressed sitemap could not be decompressed.
+    #[error("Failed to decompress sitemap!")]
+    DecompressError {
+        /// The underlying error.
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The sitemap is not well-formed XML.
+    #[error("Failed to parse sitemap, reason: {reason}")]
+    XmlParseError {
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+
+    /// The XML document is neither a `<urlset>` nor a `<sitemapindex>`.
+    #[error("Expected <urlset> or <sitemapindex>, but found <{root}>!")]
+    InvalidSitemapError {
+        /// The name of the root element.
+        root: String,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::sitemap::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The maximum size of a decompressed sitemap, 50 MiB according to the sitemap protocol.
+const MAX_SITEMAP_SIZE: u64 = 50 * 1024 * 1024;
+
+/// A parsed sitemap: either a list of page urls or an index of further sitemaps.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Sitemap {
+    /// A `<urlset>` (or plain text sitemap) listing page urls.
+    UrlSet(Vec<SitemapUrl>),
+    /// A `<sitemapindex>` listing further sitemaps.
+    Index(Vec<SitemapRef>),
+}
+
+/// A page url listed in a sitemap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SitemapUrl {
+    loc: Url,
+    lastmod: Option<String>,
+    changefreq: Option<String>,
+    priority: Option<f64>,
+}
+
+impl SitemapUrl {
+    /// Returns the url of the page (`<loc>`).
+    pub const fn loc(&self) -> &Url {
+        &self.loc
+    }
+
+    /// Returns the date of last modification (`<lastmod>`) in W3C datetime format, if any.
+    pub fn lastmod(&self) -> Option<&str> {
+        self.lastmod.as_deref()
+    }
+
+    /// Returns the expected change frequency (`<changefreq>`), e.g. `daily`, if any.
+    pub fn changefreq(&self) -> Option<&str> {
+        self.changefreq.as_deref()
+    }
+
+    /// Returns the priority relative to other pages of the site (`<priority>`, 0.0 to 1.0), if any.
+    pub const fn priority(&self) -> Option<f64> {
+        self.priority
+    }
+}
+
+/// A sitemap listed in a sitemap index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SitemapRef {
+    loc: Url,
+    lastmod: Option<String>,
+}
+
+impl SitemapRef {
+    /// Returns the url of the sitemap (`<loc>`).
+    pub const fn loc(&self) -> &Url {
+        &self.loc
+    }
+
+    /// Returns the date of last modification (`<lastmod>`) in W3C datetime format, if any.
+    pub fn lastmod(&self) -> Option<&str> {
+        self.lastmod.as_deref()
+    }
+}
+
+impl Sitemap {
+    /// Parses a sitemap from `bytes`, which may be gzip compressed. Relative urls are resolved against `url`, the
+    /// location of the sitemap. Entries with invalid urls are skipped.
+    pub fn parse(url: &Url, bytes: &[u8]) -> Result<Self> {
+        let text = decompress(bytes)?;
+        // byte order marks, e.g. of files saved on Windows, are not whitespace
+        let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+        if !text.trim_start().starts_with('<') {
+            return Ok(Self::parse_text(url, text));
+        }
+
+        let document = Document::parse(text).map_err(|error| Error::XmlParseError {
+            reason: error.to_string(),
+        })?;
+        let root = document.root_element();
+
+        match root.tag_name().name() {
+            "urlset" => Ok(Self::UrlSet(
+                elements(root, "url")
+                    .filter_map(|node| {
+                        Some(SitemapUrl {
+                            loc: url.join(child_text(node, "loc")?.as_str()).ok()?,
+                            lastmod: child_text(node, "lastmod"),
+                            changefreq: child_text(node, "changefreq"),
+                            priority: child_text(node, "priority").and_then(|p| p.parse().ok()),
+                        })
+                    })
+                    .collect(),
+            )),
+            "sitemapindex" => Ok(Self::Index(
+                elements(root, "sitemap")
+                    .filter_map(|node| {
+                        Some(SitemapRef {
+                            loc: url.join(child_text(node, "loc")?.as_str()).ok()?,
+                            lastmod: child_text(node, "lastmod"),
+                        })
+                    })
+                    .collect(),
+            )),
+            root => Err(Error::InvalidSitemapError {
+                root: root.to_owned(),
+            }),
+        }
+    }
+
+    /// Fetches and parses the sitemap at `url` with `browser`.
+    pub fn fetch(browser: &Browser, url: &Url) -> Result<Self> {
+        let response = browser.fetch(url).map_err(|error| Error::BrowserError {
+            url: url.to_string(),
+            source: Box::new(error),
+        })?;
+        if !response.status().is_success() {
+            return Err(Error::HttpStatusError {
+                url: url.to_string(),
+                status: response.status(),
+            });
+        }
+
+        Self::parse(url, response.body())
+    }
+
+    /// Fetches the sitemap at `url` with `browser` and returns its page urls. Sitemap indexes are resolved
+    /// recursively, fetching every referenced sitemap once.
+    pub fn fetch_urls(browser: &Browser, url: &Url) -> Result<Vec<SitemapUrl>> {
+        let mut urls = Vec::new();
+        let mut queue = VecDeque::from([url.clone()]);
+        let mut seen = HashSet::from([url.clone()]);
+
+        while let Some(url) = queue.pop_front() {
+            match Self::fetch(browser, &url)? {
+                Self::UrlSet(set) => urls.extend(set),
+                Self::Index(sitemaps) => queue.extend(
+                    sitemaps
+                        .into_iter()
+                        .map(|sitemap| sitemap.loc)
+                        .filter(|loc| seen.insert(loc.clone())),
+                ),
+            }
+        }
+
+        Ok(urls)
+    }
+
+    /// Returns the sitemap urls of the site of `url` as listed in its robots.txt, falling back to `/sitemap.xml` if
+    /// robots.txt lists none or doesn't exist. A `browser` respecting robots.txt reuses the robots.txt it already
+    /// fetched for the site.
+    pub fn discover(browser: &Browser, url: &Url) -> Result<Vec<Url>> {
+        let robots = browser
+            .site_robots_txt(url)
+            .map_err(|error| Error::BrowserError {
+                url: url.join("/robots.txt").unwrap().to_string(),
+                source: Box::new(error),
+            })?;
+
+        let mut sitemaps = robots
+            .sitemaps()
+            .iter()
+            .filter_map(|sitemap| url.join(sitemap).ok())
+            .collect::<Vec<Url>>();
+        if sitemaps.is_empty() {
+            sitemaps.push(url.join("/sitemap.xml").unwrap());
+        }
+
+        Ok(sitemaps)
+    }
+
+    /// Returns the page urls, or an empty slice for a sitemap index.
+    pub fn urls(&self) -> &[SitemapUrl] {
+        match self {
+            Self::UrlSet(urls) => urls,
+            Self::Index(_) => &[],
+        }
+    }
+
+    /// Returns the referenced sitemaps, or an empty slice for a list of page urls.
+    pub fn sitemaps(&self) -> &[SitemapRef] {
+        match self {
+            Self::UrlSet(_) => &[],
+            Self::Index(sitemaps) => sitemaps,
+        }
+    }
+
+    /// Parses a plain text sitemap with one url per line.
+    fn parse_text(url: &Url, text: &str) -> Self {
+        Self::UrlSet(
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .filter_map(|line| url.join(line).ok())
+                .map(|loc| SitemapUrl {
+                    loc,
+                    lastmod: None,
+                    changefreq: None,
+                    priority: None,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Returns `bytes` as text, decompressing them first if they start with the gzip magic number.
+fn decompress(bytes: &[u8]) -> Result<String> {
+    if !bytes.starts_with(&[0x1f, 0x8b]) {
+        return Ok(String::from_utf8_lossy(bytes).into_owned());
+    }
+
+    let mut decompressed = Vec::new();
+    GzDecoder::new(bytes)
+        .take(MAX_SITEMAP_SIZE)
+        .read_to_end(&mut decompressed)
+        .map_err(|error| Error::DecompressError { source: error })?;
+
+    Ok(String::from_utf8_lossy(&decompressed).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sitemap;
+    use crate::{
+        browser::Browser,
+        mock::{self, MockTransport},
+        transport::Response,
+    };
+    use flate2::{write::GzEncoder, Compression};
+    use reqwest::{Method, StatusCode, Url};
+    use std::io::Write;
+
+    static URLSET: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+    <url>
+        <loc>https://example.com/</loc>
+        <lastmod>2024-01-15</lastmod>
+        <changefreq>daily</changefreq>
+        <priority>1.0</priority>
+    </url>
+    <url>
+        <loc>https://example.com/about?lang=en&amp;v=2</loc>
+    </url>
+    <url>
+        <lastmod>2024-01-15</lastmod>
+    </url>
+</urlset>"#;
+
+    static INDEX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+    <sitemap><loc>https://example.com/pages.xml.gz</loc><lastmod>2024-02-01</lastmod></sitemap>
+    <sitemap><loc>https://example.com/more.txt</loc></sitemap>
+    <sitemap><loc>https://example.com/sitemap.xml</loc></sitemap>
+</sitemapindex>"#;
+
+    #[test]
+    fn parse_sitemaps() {
+        let url = Url::parse("https://example.com/sitemap.xml").unwrap();
+
+        let sitemap = Sitemap::parse(&url, URLSET.as_bytes()).unwrap();
+        let urls = sitemap.urls();
+        assert_eq!(urls.len(), 2);
+        assert_eq!(urls[0].loc().as_str(), "https://example.com/");
+        assert_eq!(urls[0].lastmod(), Some("2024-01-15"));
+        assert_eq!(urls[0].changefreq(), Some("daily"));
+        assert_eq!(urls[0].priority(), Some(1.0));
+        assert_eq!(
+            urls[1].loc().as_str(),
+            "https://example.com/about?lang=en&v=2"
+        );
+        assert_eq!(urls[1].lastmod(), None);
+
+        let sitemap = Sitemap::parse(&url, INDEX.as_bytes()).unwrap();
+        assert!(sitemap.urls().is_empty());
+        assert_eq!(sitemap.sitemaps().len(), 3);
+        assert_eq!(sitemap.sitemaps()[0].lastmod(), Some("2024-02-01"));
+
+        // a leading byte order mark doesn't turn XML into a plain text sitemap
+        let sitemap = Sitemap::parse(&url, format!("\u{feff}{URLSET}").as_bytes()).unwrap();
+        assert_eq!(sitemap.urls().len(), 2);
+        let sitemap = Sitemap::parse(&url, "\u{feff}/a\n/b".as_bytes()).unwrap();
+        assert_eq!(sitemap.urls()[0].loc().as_str(), "https://example.com/a");
+
+        assert!(Sitemap::parse(&url, b"<rss></rss>").is_err());
+        assert!(Sitemap::parse(&url, b"<urlset>").is_err());
+    }
+
+    #[test]
+    fn fetch_sitemap_index() {
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(URLSET.as_bytes()).unwrap();
+        let gzip = gzip.finish().unwrap();
+
+        let transport = MockTransport::new()
+            .respond(
+                Method::GET,
+                "/robots.txt",
+                mock::html("User-agent: *\nSitemap: /sitemap.xml"),
+            )
+            .respond(Method::GET, "/sitemap.xml", mock::html(INDEX))
+            .respond(
+                Method::GET,
+                "/pages.xml.gz",
+                Response::new(StatusCode::OK).with_body(gzip),
+            )
+            .respond(
+                Method::GET,
+                "/more.txt",
+                mock::html("https://example.com/a\n\n/b\n"),
+            );
+        let log = transport.request_log();
+        let browser = Browser::builder()
+            .transport(transport)
+            .respect_robots_txt(true)
+            .finish()
+            .unwrap();
+        let site = Url::parse("https://example.com/").unwrap();
+
+        let sitemaps = Sitemap::discover(&browser, &site).unwrap();
+        assert_eq!(sitemaps.len(), 1);
+
+        let urls = Sitemap::fetch_urls(&browser, &sitemaps[0]).unwrap();
+        let locs = urls
+            .iter()
+            .map(|url| url.loc().as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            locs,
+            [
+                "https://example.com/",
+                "https://example.com/about?lang=en&v=2",
+                "https://example.com/a",
+                "https://example.com/b"
+            ]
+        );
+
+        let missing = Url::parse("https://example.com/missing.xml").unwrap();
+        assert!(Sitemap::fetch(&browser, &missing).is_err());
+        // robots.txt is shared with the browser's robots.txt handling
+        let robots_txt_requests = log
+            .requests()
+            .iter()
+            .filter(|request| request.url().path() == "/robots.txt")
+            .count();
+        assert_eq!(robots_txt_requests, 1);
+    }
+}
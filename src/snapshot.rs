@@ -0,0 +1,423 @@
+//! Module containing the [`Snapshot`][Snapshot] struct to archive pages for offline use.
+//!
+//! A snapshot is a directory holding the unmodified page body (`page.html`), optionally the page's subresources
+//! (`resources/`) and a `manifest.json` describing url, http method, status and headers of every archived response.
+//! The manifest is plain JSON, so snapshots can be inspected and diffed with standard tools.
+//!
+//! ```no_run
+//! use no_browser::{snapshot::Snapshot, Browser};
+//!
+//! let browser = Browser::builder().finish()?;
+//! let page = browser.navigate_to("https://example.com/", None)?;
+//!
+//! // archive the page and its images, scripts and stylesheets ...
+//! Snapshot::of(&page).with_resources(&browser).save("target/snapshots/example")?;
+//!
+//! // ... and reload it later on, without any http request
+//! let page = Snapshot::load("target/snapshots/example")?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{browser::Browser, page, page::Page};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method, StatusCode, Url,
+};
+use scraper::Selector;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+use thiserror::Error;
+
+/// An error occurred while saving or loading a snapshot.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A file or directory of the snapshot could not be read or written.
+    #[error("Failed to access '{path}'!")]
+    IoError {
+        /// The `path` of the file or directory.
+        path: String,
+        /// The underlying error.
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The snapshot manifest could not be parsed or contains invalid entries.
+    #[error("Invalid snapshot manifest '{path}', reason: {reason}")]
+    ManifestError {
+        /// The `path` of the manifest.
+        path: String,
+        /// The `reason` the manifest is invalid.
+        reason: String,
+    },
+
+    /// There was an error while building the [`Page`][Page] from the snapshot.
+    #[error("{source}")]
+    PageError {
+        /// The underlying error.
+        #[from]
+        source: page::Error,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::snapshot::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+const MANIFEST: &str = "manifest.json";
+const PAGE_FILE: &str = "page.html";
+const RESOURCES_DIR: &str = "resources";
+const VERSION: u32 = 1;
+
+const RESOURCE_SELECTORS: &str =
+    "img[src], script[src], link[href][rel~=stylesheet], link[href][rel~=icon], \
+    source[src], video[src], audio[src], iframe[src]";
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Manifest {
+    version: u32,
+    page: Entry,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    resources: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Entry {
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    method: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl Entry {
+    fn new(url: &Url, status: StatusCode, headers: &HeaderMap, file: String) -> Self {
+        Self {
+            url: url.to_string(),
+            method: None,
+            status: Some(status.as_u16()),
+            headers: headers
+                .iter()
+                .map(|(k, v)| {
+                    (
+                        k.to_string(),
+                        String::from_utf8_lossy(v.as_bytes()).into_owned(),
+                    )
+                })
+                .collect(),
+            file: Some(file),
+            error: None,
+        }
+    }
+}
+
+/// Saves a [`Page`][Page] (url, http method, status, headers and body) to a snapshot directory and loads it back. See
+/// the [module documentation][crate::snapshot] for the directory layout.
+#[derive(Debug)]
+pub struct Snapshot<'a> {
+    page: &'a Page,
+    browser: Option<&'a Browser>,
+}
+
+impl<'a> Snapshot<'a> {
+    /// Returns a [`Snapshot`][Snapshot] of the given `page`, without subresources.
+    pub const fn of(page: &'a Page) -> Self {
+        Self {
+            page,
+            browser: None,
+        }
+    }
+
+    /// Also archives the subresources linked from the page, i.e. images, scripts, stylesheets, icons, media sources
+    /// and iframes, fetching them with the given `browser`. Resources that cannot be fetched are recorded with their
+    /// error in the manifest, but don't fail the snapshot.
+    pub const fn with_resources(mut self, browser: &'a Browser) -> Self {
+        self.browser = Some(browser);
+        self
+    }
+
+    /// Writes the snapshot into directory `dir`, creating it if necessary. Existing snapshot files are overwritten.
+    pub fn save<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let dir = dir.as_ref();
+        create_dir(dir)?;
+
+        let page = self.page;
+        write(&dir.join(PAGE_FILE), page.text().as_bytes())?;
+
+        let mut manifest = Manifest {
+            version: VERSION,
+            page: Entry::new(
+                page.url(),
+                *page.status(),
+                page.headers(),
+                PAGE_FILE.to_owned(),
+            ),
+            resources: Vec::new(),
+        };
+        manifest.page.method = Some(page.method().to_string());
+
+        if let Some(browser) = self.browser {
+            let resources = self.resource_urls();
+            if !resources.is_empty() {
+                create_dir(&dir.join(RESOURCES_DIR))?;
+            }
+
+            for (idx, url) in resources.iter().enumerate() {
+                let entry = match browser.fetch(url) {
+                    Ok(response) => {
+                        let file = format!("{RESOURCES_DIR}/{:04}{}", idx + 1, extension(url));
+                        write(&dir.join(&file), response.body())?;
+                        Entry::new(url, response.status(), response.headers(), file)
+                    }
+                    Err(error) => Entry {
+                        url: url.to_string(),
+                        method: None,
+                        status: None,
+                        headers: Vec::new(),
+                        file: None,
+                        error: Some(format!("{error}")),
+                    },
+                };
+                manifest.resources.push(entry);
+            }
+        }
+
+        let json = serde_json::to_string_pretty(&manifest).expect("manifest is serializable");
+        write(&dir.join(MANIFEST), json.as_bytes())
+    }
+
+    /// Returns the deduplicated, absolute http(s) urls of all subresources of the page, in document order.
+    fn resource_urls(&self) -> Vec<Url> {
+        let selector = Selector::parse(RESOURCE_SELECTORS).unwrap();
+        let mut urls: Vec<Url> = Vec::new();
+
+        for element in self.page.html().select(&selector) {
+            let e = element.value();
+            let Some(src) = e.attr("src").or_else(|| e.attr("href")) else {
+                continue;
+            };
+            let Ok(mut url) = self.page.url().join(src.trim()) else {
+                continue;
+            };
+            url.set_fragment(None);
+            if matches!(url.scheme(), "http" | "https") && !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+
+        urls
+    }
+
+    /// Loads the page stored in the snapshot directory `dir`.
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Page> {
+        let dir = dir.as_ref();
+        let manifest = read_manifest(dir)?;
+
+        let entry = manifest.page;
+        let method = entry.method.as_deref().unwrap_or("GET");
+        let method = Method::from_bytes(method.as_bytes())
+            .map_err(|_| manifest_error(dir, format!("invalid method '{method}'")))?;
+        let status = StatusCode::from_u16(entry.status.unwrap_or(200))
+            .map_err(|error| manifest_error(dir, error.to_string()))?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &entry.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| manifest_error(dir, format!("invalid header name '{name}'")))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|_| manifest_error(dir, format!("invalid value for header '{name}'")))?;
+            headers.append(name, value);
+        }
+        let file = snapshot_file(dir, entry.file.as_deref().unwrap_or(PAGE_FILE))?;
+
+        Ok(Page::builder(&entry.url)
+            .method(method)
+            .status(status)
+            .headers(headers)
+            .build_from_file(file)?)
+    }
+
+    /// Returns the paths of all subresources stored in the snapshot directory `dir`, keyed by their original url.
+    /// Resources that could not be fetched when saving the snapshot are skipped.
+    pub fn resources<P: AsRef<Path>>(dir: P) -> Result<Vec<(Url, PathBuf)>> {
+        let dir = dir.as_ref();
+        let mut resources = Vec::new();
+
+        for entry in read_manifest(dir)?.resources {
+            let (Ok(url), Some(file)) = (Url::parse(&entry.url), entry.file) else {
+                continue;
+            };
+            resources.push((url, snapshot_file(dir, &file)?));
+        }
+
+        Ok(resources)
+    }
+}
+
+/// Reads and parses the manifest of the snapshot directory `dir`.
+fn read_manifest(dir: &Path) -> Result<Manifest> {
+    let path = dir.join(MANIFEST);
+    let json = fs::read_to_string(&path).map_err(|error| Error::IoError {
+        path: path.display().to_string(),
+        source: error,
+    })?;
+    let manifest: Manifest =
+        serde_json::from_str(&json).map_err(|error| manifest_error(dir, error.to_string()))?;
+    if manifest.version != VERSION {
+        return Err(manifest_error(
+            dir,
+            format!("unsupported version {}", manifest.version),
+        ));
+    }
+
+    Ok(manifest)
+}
+
+/// Returns the path of the snapshot `file` within `dir`. Absolute paths and paths leaving `dir` (e.g. via `..`) are
+/// rejected, so that manifests can't point to arbitrary files.
+fn snapshot_file(dir: &Path, file: &str) -> Result<PathBuf> {
+    let path = Path::new(file);
+    let relative = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if file.is_empty() || !relative {
+        return Err(manifest_error(
+            dir,
+            format!("file '{file}' is outside of the snapshot"),
+        ));
+    }
+
+    Ok(dir.join(path))
+}
+
+fn manifest_error(dir: &Path, reason: String) -> Error {
+    Error::ManifestError {
+        path: dir.join(MANIFEST).display().to_string(),
+        reason,
+    }
+}
+
+fn create_dir(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).map_err(|error| Error::IoError {
+        path: dir.display().to_string(),
+        source: error,
+    })
+}
+
+fn write(path: &Path, bytes: &[u8]) -> Result<()> {
+    fs::write(path, bytes).map_err(|error| Error::IoError {
+        path: path.display().to_string(),
+        source: error,
+    })
+}
+
+/// Returns the (sanitized) file extension of the url path, including the leading dot, or an empty string.
+fn extension(url: &Url) -> String {
+    let name = url.path().rsplit('/').next().unwrap_or_default();
+    match name.rsplit_once('.') {
+        Some((_, ext))
+            if !ext.is_empty()
+                && ext.len() <= 8
+                && ext.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            format!(".{}", ext.to_ascii_lowercase())
+        }
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Snapshot};
+    use crate::{browser::Browser, page::Page};
+    use reqwest::{
+        header::{HeaderValue, CONTENT_TYPE},
+        Method, StatusCode,
+    };
+    use std::{fs, thread};
+    use tiny_http::{Response, Server};
+
+    #[test]
+    fn save_and_load_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let page = Page::builder("https://example.com/search?q=crab")
+            .method(Method::POST)
+            .status(StatusCode::IM_A_TEAPOT)
+            .header(CONTENT_TYPE, HeaderValue::from_static("text/html"))
+            .build_from_text("<h1>Crabs</h1>")
+            .unwrap();
+
+        Snapshot::of(&page).save(dir.path()).unwrap();
+        let loaded = Snapshot::load(dir.path()).unwrap();
+
+        assert_eq!(loaded.url(), page.url());
+        assert_eq!(loaded.method(), page.method());
+        assert_eq!(loaded.status(), page.status());
+        assert_eq!(loaded.headers(), page.headers());
+        assert_eq!(loaded.text(), page.text());
+        assert!(Snapshot::resources(dir.path()).unwrap().is_empty());
+
+        fs::write(dir.path().join("manifest.json"), r#"{ "version": 2 }"#).unwrap();
+        assert!(matches!(
+            Snapshot::load(dir.path()),
+            Err(Error::ManifestError { .. })
+        ));
+
+        for file in ["/etc/passwd", "../../x", "resources/../../x"] {
+            let manifest = format!(
+                r#"{{ "version": 1, "page": {{ "url": "https://example.com/", "file": "{file}" }},
+                      "resources": [{{ "url": "https://example.com/a.css", "file": "{file}" }}] }}"#
+            );
+            fs::write(dir.path().join("manifest.json"), manifest).unwrap();
+            assert!(matches!(
+                Snapshot::load(dir.path()),
+                Err(Error::ManifestError { .. })
+            ));
+            assert!(matches!(
+                Snapshot::resources(dir.path()),
+                Err(Error::ManifestError { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn save_page_with_resources() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        thread::spawn(move || {
+            for request in server.incoming_requests().take(2) {
+                let response = match request.url() {
+                    "/style.css" => Response::from_string("h1 { color: orange; }"),
+                    _ => Response::from_string("gone").with_status_code(404),
+                };
+                request.respond(response).unwrap();
+            }
+        });
+
+        let html = r#"<link rel="stylesheet" href="/style.css"><img src="img/crab.png"><img src="/style.css#x">"#;
+        let page = Page::from_html(&format!("http://{addr}/index.html"), html).unwrap();
+        let browser = Browser::builder().finish().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        Snapshot::of(&page)
+            .with_resources(&browser)
+            .save(dir.path())
+            .unwrap();
+
+        let resources = Snapshot::resources(dir.path()).unwrap();
+        assert_eq!(resources.len(), 2);
+        assert_eq!(resources[0].0.path(), "/style.css");
+        assert_eq!(
+            fs::read_to_string(&resources[0].1).unwrap(),
+            "h1 { color: orange; }"
+        );
+        assert_eq!(resources[1].0.path(), "/img/crab.png");
+        assert!(resources[1].1.ends_with("resources/0002.png"));
+    }
+}
@@ -0,0 +1,253 @@
+//! Module containing the [`ExpectedStatus`][ExpectedStatus] of pages and the [`UnexpectedResponse`][UnexpectedResponse]
+//! carried by errors about pages loaded with any other status.
+//!
+//! By default, a [`Browser`][crate::Browser] returns a [`Page`][Page] for every response, whatever its status. Enable
+//! `BrowserBuilder::error_on_status()` to fail navigation and form submissions with an `HttpStatusError` for every
+//! status except `2xx`, or pass the expected status per call with `Browser::navigate_to_expecting()` and
+//! `Browser::submit_form_expecting()`. Pages at hand can be checked with `Page::error_for_status()` and
+//! `Page::expect_status()`.
+//!
+//! The error still carries the complete response, so error pages can be inspected with `Error::page()`.
+//!
+//! ```
+//! use no_browser::{
+//!     mock::{self, MockTransport},
+//!     Browser,
+//! };
+//! use reqwest::{Method, StatusCode};
+//!
+//! let transport = MockTransport::new().respond(Method::GET, "/", mock::html("<h1>Welcome</h1>"));
+//! let browser = Browser::builder()
+//!     .transport(transport)
+//!     .error_on_status(true)
+//!     .finish()?;
+//!
+//! let error = browser.navigate_to("http://app.test/missing", None).unwrap_err();
+//! assert_eq!(error.page().unwrap().status(), &StatusCode::NOT_FOUND);
+//!
+//! // expect a specific status for a single call
+//! browser.navigate_to_expecting("http://app.test/missing", None, StatusCode::NOT_FOUND)?;
+//! # Ok::<(), no_browser::browser::Error>(())
+//! ```
+
+use crate::page::Page;
+use reqwest::{header::HeaderMap, Method, StatusCode, Url};
+use std::fmt::{self, Display, Formatter};
+
+/// The status (or statuses) a page is expected to be loaded with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpectedStatus {
+    /// Any status is fine.
+    Any,
+    /// A success status, i.e. `2xx`.
+    Success,
+    /// Exactly the given status.
+    Exactly(StatusCode),
+    /// One of the given statuses.
+    OneOf(Vec<StatusCode>),
+}
+
+impl ExpectedStatus {
+    /// Returns `true` if `status` is as expected.
+    pub fn matches(&self, status: StatusCode) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Success => status.is_success(),
+            Self::Exactly(expected) => status == *expected,
+            Self::OneOf(expected) => expected.contains(&status),
+        }
+    }
+}
+
+impl From<StatusCode> for ExpectedStatus {
+    fn from(status: StatusCode) -> Self {
+        Self::Exactly(status)
+    }
+}
+
+impl From<&[StatusCode]> for ExpectedStatus {
+    fn from(statuses: &[StatusCode]) -> Self {
+        Self::OneOf(statuses.to_vec())
+    }
+}
+
+impl Display for ExpectedStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => write!(f, "any status"),
+            Self::Success => write!(f, "a success status (2xx)"),
+            Self::Exactly(status) => write!(f, "{status}"),
+            Self::OneOf(statuses) => {
+                let statuses = statuses
+                    .iter()
+                    .map(StatusCode::to_string)
+                    .collect::<Vec<String>>();
+                write!(f, "one of {}", statuses.join(", "))
+            }
+        }
+    }
+}
+
+/// A response with an unexpected status. Unlike [`Page`][Page], it can be sent between threads, so that errors
+/// carrying it can, too. Use `page()` to inspect it like any other page.
+#[derive(Debug, Clone)]
+pub struct UnexpectedResponse {
+    method: Method,
+    url: Url,
+    status: StatusCode,
+    headers: HeaderMap,
+    text: String,
+}
+
+impl UnexpectedResponse {
+    pub(crate) fn new(page: &Page) -> Self {
+        Self {
+            method: page.method().clone(),
+            url: page.url().clone(),
+            status: *page.status(),
+            headers: page.headers().clone(),
+            text: page.text().to_owned(),
+        }
+    }
+
+    /// Returns the http method used to fetch the page.
+    pub const fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the url of the page after following all redirects.
+    pub const fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the unexpected http status.
+    pub const fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the response headers.
+    pub const fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns the unparsed html content.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the response parsed as [`Page`][Page].
+    pub fn page(&self) -> Page {
+        Page::build(
+            self.method.clone(),
+            self.url.clone(),
+            self.status,
+            self.headers.clone(),
+            self.text.clone(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExpectedStatus;
+    use crate::{
+        browser::{Browser, Error},
+        mock::{self, MockTransport},
+        page::Page,
+        transport::Response,
+    };
+    use reqwest::{Method, StatusCode};
+
+    #[test]
+    fn match_expected_status() {
+        assert!(ExpectedStatus::Any.matches(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(ExpectedStatus::Success.matches(StatusCode::NO_CONTENT));
+        assert!(!ExpectedStatus::Success.matches(StatusCode::NOT_MODIFIED));
+        assert!(ExpectedStatus::from(StatusCode::GONE).matches(StatusCode::GONE));
+        let one_of = ExpectedStatus::from(&[StatusCode::OK, StatusCode::NOT_FOUND][..]);
+        assert!(one_of.matches(StatusCode::NOT_FOUND));
+        assert!(!one_of.matches(StatusCode::GONE));
+        assert_eq!(one_of.to_string(), "one of 200 OK, 404 Not Found");
+
+        let page = Page::builder("https://app.test/")
+            .status(StatusCode::FORBIDDEN)
+            .build_from_text("<h1>Forbidden</h1>")
+            .unwrap();
+        let page = page.expect_status(StatusCode::FORBIDDEN).unwrap();
+        let error = page.error_for_status().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected status 403 Forbidden for 'https://app.test/', expected a success status (2xx)!"
+        );
+    }
+
+    #[test]
+    fn fail_on_unexpected_status() {
+        let transport = MockTransport::new().respond(Method::GET, "/old", mock::redirect("/gone"));
+
+        let browser = Browser::builder().transport(transport).finish().unwrap();
+        assert!(browser.navigate_to("http://app.test/old", None).is_ok());
+        assert!(browser
+            .navigate_to_expecting("http://app.test/old", None, ExpectedStatus::Success)
+            .is_err());
+        let page = browser
+            .navigate_to_expecting("http://app.test/old", None, StatusCode::NOT_FOUND)
+            .unwrap();
+        assert_eq!(page.url().path(), "/gone");
+
+        let transport = MockTransport::new()
+            .respond(
+                Method::GET,
+                "/",
+                mock::html("<form action='/save' method='post'></form>"),
+            )
+            .respond(
+                Method::POST,
+                "/save",
+                Response::new(StatusCode::INTERNAL_SERVER_ERROR)
+                    .with_body("<p id='trace'>oops</p>"),
+            );
+        let browser = Browser::builder()
+            .transport(transport)
+            .error_on_status(true)
+            .finish()
+            .unwrap();
+        let page = browser.navigate_to("http://app.test/", None).unwrap();
+
+        let error = browser
+            .submit_form(page.form(0).unwrap(), None)
+            .unwrap_err();
+        let Error::HttpStatusError {
+            url,
+            status,
+            response,
+            ..
+        } = &error
+        else {
+            panic!("unexpected error: {error}");
+        };
+        assert!(url.ends_with("/save"));
+        assert_eq!(*status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(response.method(), Method::POST);
+        assert_eq!(
+            error
+                .page()
+                .unwrap()
+                .select_first("#trace")
+                .unwrap()
+                .inner_html(),
+            "oops"
+        );
+
+        assert!(browser
+            .submit_form_expecting(
+                page.form(0).unwrap(),
+                None,
+                StatusCode::INTERNAL_SERVER_ERROR
+            )
+            .is_ok());
+        assert!(browser
+            .navigate_to_expecting("http://app.test/missing", None, ExpectedStatus::Any)
+            .is_ok());
+    }
+}
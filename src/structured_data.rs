@@ -0,0 +1,475 @@
+//! Module containing the [`StructuredData`][StructuredData] struct.
+
+use crate::query;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// An error occurred while parsing structured data.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A `<script type="application/ld+json">` block contains malformed JSON.
+    #[error(
+        "Failed to parse JSON-LD block #{index} at line {line}, column {column}, reason: {reason}"
+    )]
+    JsonLdParseError {
+        /// The zero-based index of the block among all JSON-LD blocks of the page.
+        index: usize,
+        /// The line within the block where parsing failed.
+        line: usize,
+        /// The column within the block where parsing failed.
+        column: usize,
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::structured_data::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Struct [`StructuredData`][StructuredData] holds the structured data (e.g. [schema.org](https://schema.org)) embedded
+/// into a [`Page`][crate::page::Page] as [JSON-LD](https://json-ld.org/),
+/// [Microdata](https://html.spec.whatwg.org/multipage/microdata.html) or
+/// [RDFa Lite](https://www.w3.org/TR/rdfa-lite/).
+///
+/// All items are represented uniformly as JSON objects in JSON-LD style: the item type is given as `@type` (plus
+/// `@context` for the vocabulary, e.g. `https://schema.org`), the item identifier as `@id`; properties map to strings,
+/// nested items or arrays thereof, if a property occurs multiple times. Urls in Microdata and RDFa are resolved against
+/// the page url. Top-level JSON-LD arrays and `@graph` contents are flattened into individual items.
+///
+/// Malformed JSON-LD blocks are skipped and reported via `errors()`.
+///
+/// ```no_run
+/// # let page: Option<no_browser::page::Page> = None;
+/// # let page = page.unwrap();
+/// let data = page.structured_data();
+/// assert!(data.errors().is_empty());
+///
+/// let product = data.items_of_type("Product").into_iter().next().unwrap();
+/// assert_eq!(product["name"], "Ferris plush");
+/// assert_eq!(product["offers"]["priceCurrency"], "EUR");
+/// ```
+#[derive(Debug, Default)]
+pub struct StructuredData {
+    json_ld: Vec<Value>,
+    microdata: Vec<Value>,
+    rdfa: Vec<Value>,
+    errors: Vec<Error>,
+}
+
+impl StructuredData {
+    pub(crate) fn parse(html: &Html, url: &Url) -> Self {
+        let mut data = Self::default();
+        data.parse_json_ld(html);
+
+        let root = html.root_element();
+        let parser = ItemParser { url, rdfa: false };
+        data.microdata = parser.top_level_items(root);
+        let parser = ItemParser { url, rdfa: true };
+        data.rdfa = parser.top_level_items(root);
+
+        data
+    }
+
+    fn parse_json_ld(&mut self, html: &Html) {
+        let selector = Selector::parse("script[type]").unwrap();
+        let blocks = html.select(&selector).filter(|script| {
+            let mime = script.value().attr("type").unwrap();
+            let mime = mime.split(';').next().unwrap().trim();
+            mime.eq_ignore_ascii_case("application/ld+json")
+        });
+
+        for (index, block) in blocks.enumerate() {
+            let text: String = block.text().collect();
+            match serde_json::from_str::<Value>(&text) {
+                Ok(value) => Self::flatten(value, &mut self.json_ld),
+                Err(error) => self.errors.push(Error::JsonLdParseError {
+                    index,
+                    line: error.line(),
+                    column: error.column(),
+                    reason: error.to_string(),
+                }),
+            }
+        }
+    }
+
+    fn flatten(value: Value, items: &mut Vec<Value>) {
+        match value {
+            Value::Array(values) => values.into_iter().for_each(|v| Self::flatten(v, items)),
+            Value::Object(mut object) if object.contains_key("@graph") => {
+                let context = object.get("@context").cloned();
+                let graph = object.remove("@graph").unwrap();
+                let graph = match graph {
+                    Value::Array(values) => values,
+                    value => vec![value],
+                };
+                for mut item in graph {
+                    if let (Some(context), Value::Object(item)) = (&context, &mut item) {
+                        item.entry("@context").or_insert_with(|| context.clone());
+                    }
+                    Self::flatten(item, items);
+                }
+            }
+            value => items.push(value),
+        }
+    }
+
+    /// Returns all items parsed from JSON-LD blocks.
+    pub fn json_ld(&self) -> &[Value] {
+        &self.json_ld
+    }
+
+    /// Returns all top-level Microdata items, i.e. elements with `itemscope`, but without `itemprop`.
+    pub fn microdata(&self) -> &[Value] {
+        &self.microdata
+    }
+
+    /// Returns all top-level RDFa Lite items, i.e. elements with `typeof`, but without `property`.
+    pub fn rdfa(&self) -> &[Value] {
+        &self.rdfa
+    }
+
+    /// Returns all items from JSON-LD, Microdata and RDFa (in that order).
+    pub fn items(&self) -> impl Iterator<Item = &Value> {
+        self.json_ld
+            .iter()
+            .chain(self.microdata.iter())
+            .chain(self.rdfa.iter())
+    }
+
+    /// Returns all top-level items with the given type, e.g. `Product`. Types given as full IRI, e.g.
+    /// `https://schema.org/Product`, match as well.
+    pub fn items_of_type(&self, item_type: &str) -> Vec<&Value> {
+        self.items()
+            .filter(|item| has_type(item, item_type))
+            .collect()
+    }
+
+    /// Returns the errors encountered while parsing JSON-LD blocks.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+}
+
+fn has_type(item: &Value, item_type: &str) -> bool {
+    let matches = |t: &Value| {
+        t.as_str()
+            .is_some_and(|t| t == item_type || t.rsplit(['/', '#', ':']).next() == Some(item_type))
+    };
+
+    match item.get("@type") {
+        Some(Value::Array(types)) => types.iter().any(matches),
+        Some(t) => matches(t),
+        None => false,
+    }
+}
+
+/// Parses Microdata (`itemscope`, `itemtype`, `itemid`, `itemprop`) or RDFa Lite (`typeof`, `vocab`, `resource`,
+/// `property`) items, which share the same structure.
+struct ItemParser<'u> {
+    url: &'u Url,
+    rdfa: bool,
+}
+
+impl ItemParser<'_> {
+    fn is_scope(&self, element: &ElementRef) -> bool {
+        let attr = if self.rdfa { "typeof" } else { "itemscope" };
+        element.value().attr(attr).is_some()
+    }
+
+    fn properties<'e>(&self, element: &ElementRef<'e>) -> Option<&'e str> {
+        let attr = if self.rdfa { "property" } else { "itemprop" };
+        element.value().attr(attr)
+    }
+
+    fn top_level_items(&self, element: ElementRef) -> Vec<Value> {
+        let mut items = Vec::new();
+
+        for child in element.children().filter_map(ElementRef::wrap) {
+            if self.is_scope(&child) {
+                if self.properties(&child).is_none() {
+                    items.push(self.item(&child));
+                }
+            } else {
+                items.extend(self.top_level_items(child));
+            }
+        }
+
+        items
+    }
+
+    fn item(&self, element: &ElementRef) -> Value {
+        let e = element.value();
+        let mut item = Map::new();
+
+        let (types, id) = if self.rdfa {
+            (e.attr("typeof").unwrap_or_default(), e.attr("resource"))
+        } else {
+            (e.attr("itemtype").unwrap_or_default(), e.attr("itemid"))
+        };
+
+        let mut contexts = Vec::new();
+        let mut local_types = Vec::new();
+        for t in types.split_ascii_whitespace() {
+            let (context, local) = self.split_type(element, t);
+            if let Some(context) = context {
+                if !contexts.contains(&context) {
+                    contexts.push(context);
+                }
+            }
+            local_types.push(Value::String(local));
+        }
+        if contexts.len() == 1 {
+            item.insert("@context".to_owned(), Value::String(contexts.remove(0)));
+        }
+        match local_types.len() {
+            0 => {}
+            1 => {
+                item.insert("@type".to_owned(), local_types.remove(0));
+            }
+            _ => {
+                item.insert("@type".to_owned(), Value::Array(local_types));
+            }
+        }
+        if let Some(id) = id {
+            item.insert("@id".to_owned(), Value::String(self.resolve(id)));
+        }
+
+        self.collect_properties(element, &mut item);
+
+        Value::Object(item)
+    }
+
+    /// Splits a type like `https://schema.org/Product` (Microdata), `Product` with `vocab="https://schema.org/"` or
+    /// `schema:Product` with `prefix="schema: https://schema.org/"` (RDFa) into vocabulary and local name.
+    fn split_type(&self, element: &ElementRef, t: &str) -> (Option<String>, String) {
+        if self.rdfa {
+            if let Some((prefix, local)) = t.split_once(':').filter(|(_, l)| !l.starts_with("//")) {
+                let context = self.inherited(element, "prefix").and_then(|prefixes| {
+                    let mut tokens = prefixes.split_ascii_whitespace();
+                    while let Some(token) = tokens.next() {
+                        let iri = tokens.next()?;
+                        if token.strip_suffix(':') == Some(prefix) {
+                            return Some(iri.trim_end_matches(['/', '#']).to_owned());
+                        }
+                    }
+                    None
+                });
+                return (context, local.to_owned());
+            }
+            if !t.contains('/') {
+                let context = self
+                    .inherited(element, "vocab")
+                    .map(|vocab| vocab.trim_end_matches(['/', '#']).to_owned());
+                return (context, t.to_owned());
+            }
+        }
+
+        match t.rfind(['/', '#']) {
+            Some(idx) if idx + 1 < t.len() => (Some(t[..idx].to_owned()), t[idx + 1..].to_owned()),
+            _ => (None, t.to_owned()),
+        }
+    }
+
+    fn inherited<'e>(&self, element: &ElementRef<'e>, attr: &str) -> Option<&'e str> {
+        std::iter::once(**element)
+            .chain(element.ancestors())
+            .filter_map(ElementRef::wrap)
+            .find_map(|e| e.value().attr(attr))
+    }
+
+    fn collect_properties(&self, element: &ElementRef, item: &mut Map<String, Value>) {
+        for child in element.children().filter_map(ElementRef::wrap) {
+            if let Some(names) = self.properties(&child) {
+                let value = if self.is_scope(&child) {
+                    self.item(&child)
+                } else {
+                    Value::String(self.value(&child))
+                };
+
+                for name in names.split_ascii_whitespace() {
+                    let name = if self.rdfa {
+                        name.rsplit([':', '/', '#']).next().unwrap()
+                    } else {
+                        name
+                    };
+                    match item.get_mut(name) {
+                        Some(Value::Array(values)) => values.push(value.clone()),
+                        Some(existing) => {
+                            *existing = Value::Array(vec![existing.take(), value.clone()])
+                        }
+                        None => {
+                            item.insert(name.to_owned(), value.clone());
+                        }
+                    }
+                }
+            }
+
+            if !self.is_scope(&child) {
+                self.collect_properties(&child, item);
+            }
+        }
+    }
+
+    fn value(&self, element: &ElementRef) -> String {
+        let e = element.value();
+
+        if let Some(content) = e.attr("content") {
+            return content.to_owned();
+        }
+        if self.rdfa {
+            if let Some(resource) = e.attr("resource") {
+                return self.resolve(resource);
+            }
+        }
+
+        let url_attr = match e.name() {
+            "a" | "area" | "link" => Some("href"),
+            "audio" | "embed" | "iframe" | "img" | "source" | "track" | "video" => Some("src"),
+            "object" => Some("data"),
+            _ => None,
+        };
+        if let Some(url) = url_attr.and_then(|attr| e.attr(attr)) {
+            return self.resolve(url);
+        }
+
+        let value_attr = match e.name() {
+            "data" | "meter" => e.attr("value"),
+            "time" => e.attr("datetime"),
+            _ => None,
+        };
+
+        value_attr
+            .map(str::to_owned)
+            .unwrap_or_else(|| query::normalize(&query::text_content(element)))
+    }
+
+    fn resolve(&self, url: &str) -> String {
+        match self.url.join(url.trim()) {
+            Ok(url) => url.to_string(),
+            Err(_) => url.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, StructuredData};
+    use reqwest::Url;
+    use scraper::Html;
+    use serde_json::json;
+
+    static PAGE_001: &str = r##"
+        <html>
+            <head>
+                <script type="application/ld+json">
+                    {
+                        "@context": "https://schema.org",
+                        "@graph": [
+                            { "@type": "Organization", "name": "Crab Corp" },
+                            { "@type": "WebSite", "url": "https://shop.example/" }
+                        ]
+                    }
+                </script>
+                <script type="application/ld+json">
+                    { "@context": "https://schema.org", "@type": "BreadcrumbList", }
+                </script>
+            </head>
+            <body>
+                <div itemscope itemtype="https://schema.org/Product" itemid="#ferris">
+                    <h1 itemprop="name">Ferris  plush</h1>
+                    <img itemprop="image" src="/img/ferris.png">
+                    <span itemprop="color">orange</span>, <span itemprop="color">red</span>
+                    <div itemprop="offers" itemscope itemtype="https://schema.org/Offer">
+                        <meta itemprop="priceCurrency" content="EUR">
+                        <data itemprop="price" value="12.50">12,50 €</data>
+                    </div>
+                </div>
+                <div vocab="https://schema.org/" typeof="Person" resource="/people/ferris">
+                    <span property="name">Ferris</span>
+                    <a property="sameAs" href="https://rustacean.net/">Rustacean</a>
+                    <div property="address" typeof="PostalAddress">
+                        <span property="addressLocality">Crabtown</span>
+                    </div>
+                </div>
+                <div prefix="dc: http://purl.org/dc/terms/" typeof="dc:BibliographicResource">
+                    <span property="dc:title">The Book</span>
+                </div>
+            </body>
+        </html>
+    "##;
+
+    fn parse() -> StructuredData {
+        let url = Url::parse("https://shop.example/products/ferris").unwrap();
+        StructuredData::parse(&Html::parse_document(PAGE_001), &url)
+    }
+
+    #[test]
+    fn parse_json_ld() {
+        let data = parse();
+
+        assert_eq!(
+            data.json_ld(),
+            [
+                json!({ "@context": "https://schema.org", "@type": "Organization", "name": "Crab Corp" }),
+                json!({ "@context": "https://schema.org", "@type": "WebSite", "url": "https://shop.example/" }),
+            ]
+        );
+
+        assert_eq!(data.errors().len(), 1);
+        let Error::JsonLdParseError { index, line, .. } = &data.errors()[0];
+        assert_eq!((*index, *line), (1, 2));
+    }
+
+    #[test]
+    fn parse_microdata_and_rdfa() {
+        let data = parse();
+
+        assert_eq!(
+            data.microdata(),
+            [json!({
+                "@context": "https://schema.org",
+                "@type": "Product",
+                "@id": "https://shop.example/products/ferris#ferris",
+                "name": "Ferris plush",
+                "image": "https://shop.example/img/ferris.png",
+                "color": ["orange", "red"],
+                "offers": {
+                    "@context": "https://schema.org",
+                    "@type": "Offer",
+                    "priceCurrency": "EUR",
+                    "price": "12.50",
+                },
+            })]
+        );
+
+        assert_eq!(
+            data.rdfa(),
+            [
+                json!({
+                    "@context": "https://schema.org",
+                    "@type": "Person",
+                    "@id": "https://shop.example/people/ferris",
+                    "name": "Ferris",
+                    "sameAs": "https://rustacean.net/",
+                    "address": {
+                        "@context": "https://schema.org",
+                        "@type": "PostalAddress",
+                        "addressLocality": "Crabtown",
+                    },
+                }),
+                json!({
+                    "@context": "http://purl.org/dc/terms",
+                    "@type": "BibliographicResource",
+                    "title": "The Book",
+                }),
+            ]
+        );
+
+        assert_eq!(data.items().count(), 5);
+        assert_eq!(data.items_of_type("Product")[0]["offers"]["price"], "12.50");
+        assert_eq!(data.items_of_type("WebSite").len(), 1);
+        assert!(data.items_of_type("Offer").is_empty());
+    }
+}
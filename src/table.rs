@@ -0,0 +1,360 @@
+//! Module containing the [`Table`][Table] struct.
+
+use crate::query;
+use scraper::ElementRef;
+use std::{collections::HashMap, io::Write};
+
+/// Struct [`Table`][Table] represents a parsed html `<table>` with `colspan` and `rowspan` expanded into a regular grid,
+/// i.e. a spanning cell's text is repeated in every grid position it covers. Cell texts are whitespace-normalized.
+///
+/// Header rows are taken from `<thead>`; if there is none, leading rows consisting only of `<th>` cells are used. With
+/// multiple header rows, the distinct texts of each column are joined with `" / "`. Rows of `<tbody>` and `<tfoot>`
+/// become data rows. Rows of nested tables are ignored.
+///
+/// It gives access to:
+/// * the table's caption (`caption()`);
+/// * the header texts (`headers()`) and the data rows as vectors (`rows()`, `row()`), as header-keyed maps
+///   (`records()`) or by column (`column()`);
+/// * a CSV export (`to_csv()`, `write_csv()`).
+///
+/// ```no_run
+/// # let page: Option<no_browser::page::Page> = None;
+/// # let page = page.unwrap();
+/// let table = page.table("table#report")?;
+/// for record in table.records() {
+///     println!("{} earned {}", record["Name"], record["Revenue"]);
+/// }
+/// std::fs::write("report.csv", table.to_csv()).unwrap();
+/// # Ok::<(), no_browser::page::Error>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    caption: Option<String>,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+struct Cell {
+    text: String,
+    header: bool,
+    colspan: usize,
+    rowspan: usize,
+}
+
+impl Table {
+    /// Parses the given `<table>` element. Returns `None` if `element` is not a table.
+    pub fn from_element(element: &ElementRef) -> Option<Self> {
+        if element.value().name() != "table" {
+            return None;
+        }
+
+        let mut caption = None;
+        let mut head = Vec::new();
+        let mut body = Vec::new();
+        let mut foot = Vec::new();
+
+        for child in element.children().filter_map(ElementRef::wrap) {
+            match child.value().name() {
+                "caption" => caption = Some(query::normalize(&query::text_content(&child))),
+                "thead" => head.push(Self::row_group(&child)),
+                "tfoot" => foot.push(Self::row_group(&child)),
+                "tbody" => body.push(Self::row_group(&child)),
+                "tr" => body.push(vec![Self::cells(&child)]),
+                _ => {}
+            }
+        }
+
+        let mut header_rows: Vec<Vec<(String, bool)>> =
+            head.into_iter().flat_map(Self::expand).collect();
+        let mut rows: Vec<Vec<(String, bool)>> = body
+            .into_iter()
+            .chain(foot)
+            .flat_map(Self::expand)
+            .collect();
+
+        if header_rows.is_empty() {
+            let leading = rows
+                .iter()
+                .take_while(|row| !row.is_empty() && row.iter().all(|(_, header)| *header))
+                .count();
+            header_rows = rows.drain(..leading).collect();
+        }
+
+        let width = header_rows
+            .iter()
+            .chain(rows.iter())
+            .map(Vec::len)
+            .max()
+            .unwrap_or(0);
+
+        let headers = if header_rows.is_empty() {
+            Vec::new()
+        } else {
+            (0..width)
+                .map(|col| {
+                    let mut parts: Vec<&str> = Vec::new();
+                    for row in &header_rows {
+                        match row.get(col) {
+                            Some((text, _))
+                                if !text.is_empty() && parts.last() != Some(&text.as_str()) =>
+                            {
+                                parts.push(text)
+                            }
+                            _ => {}
+                        }
+                    }
+                    parts.join(" / ")
+                })
+                .collect()
+        };
+
+        let rows = rows
+            .into_iter()
+            .map(|row| {
+                let mut row: Vec<String> = row.into_iter().map(|(text, _)| text).collect();
+                row.resize(width, String::new());
+                row
+            })
+            .collect();
+
+        Some(Self {
+            caption,
+            headers,
+            rows,
+        })
+    }
+
+    fn row_group(group: &ElementRef) -> Vec<Vec<Cell>> {
+        group
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| e.value().name() == "tr")
+            .map(|tr| Self::cells(&tr))
+            .collect()
+    }
+
+    fn cells(tr: &ElementRef) -> Vec<Cell> {
+        let span = |e: &ElementRef, attr: &str| {
+            e.value()
+                .attr(attr)
+                .and_then(|v| v.trim().parse::<usize>().ok())
+        };
+
+        tr.children()
+            .filter_map(ElementRef::wrap)
+            .filter(|e| matches!(e.value().name(), "td" | "th"))
+            .map(|e| Cell {
+                text: query::normalize(&query::text_content(&e)),
+                header: e.value().name() == "th",
+                colspan: span(&e, "colspan").unwrap_or(1).clamp(1, 1000),
+                // rowspan="0" spans all remaining rows of the row group
+                rowspan: span(&e, "rowspan").map_or(1, |n| {
+                    if n == 0 {
+                        usize::MAX
+                    } else {
+                        n.min(65534)
+                    }
+                }),
+            })
+            .collect()
+    }
+
+    /// Expands the cells of a row group into a grid, resolving `colspan` and `rowspan`.
+    fn expand(group: Vec<Vec<Cell>>) -> Vec<Vec<(String, bool)>> {
+        // per column: remaining rows to fill and the spanning cell's content
+        let mut pending: Vec<Option<(usize, String, bool)>> = Vec::new();
+        let mut grid = Vec::with_capacity(group.len());
+
+        for cells in group {
+            let mut row: Vec<(String, bool)> = Vec::new();
+
+            for cell in cells {
+                Self::fill_pending(&mut row, &mut pending);
+                for _ in 0..cell.colspan {
+                    if cell.rowspan > 1 {
+                        let col = row.len();
+                        if pending.len() <= col {
+                            pending.resize(col + 1, None);
+                        }
+                        pending[col] = Some((cell.rowspan - 1, cell.text.clone(), cell.header));
+                    }
+                    row.push((cell.text.clone(), cell.header));
+                }
+            }
+
+            // spans reaching beyond the last cell of this row still occupy their grid positions
+            let end = pending
+                .iter()
+                .rposition(Option::is_some)
+                .map_or(0, |col| col + 1);
+            while row.len() < end {
+                if pending[row.len()].is_some() {
+                    Self::fill_pending(&mut row, &mut pending);
+                } else {
+                    row.push((String::new(), false));
+                }
+            }
+
+            grid.push(row);
+        }
+
+        grid
+    }
+
+    fn fill_pending(row: &mut Vec<(String, bool)>, pending: &mut [Option<(usize, String, bool)>]) {
+        while let Some(Some((remaining, text, header))) = pending.get_mut(row.len()) {
+            row.push((text.clone(), *header));
+            *remaining -= 1;
+            if *remaining == 0 {
+                pending[row.len() - 1] = None;
+            }
+        }
+    }
+
+    /// Returns the text of the table's `<caption>`, if any.
+    pub fn caption(&self) -> Option<&str> {
+        self.caption.as_deref()
+    }
+
+    /// Returns the header texts, one per column. Empty if the table has no header rows.
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    /// Returns all data rows. Every row has the same number of cells; missing cells are empty strings.
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+
+    /// Returns the data row at index `idx`, if any.
+    pub fn row(&self, idx: usize) -> Option<&[String]> {
+        self.rows.get(idx).map(Vec::as_slice)
+    }
+
+    /// Returns all cells of the column with the given header text, if any.
+    pub fn column(&self, header: &str) -> Option<Vec<&str>> {
+        let col = self.headers.iter().position(|h| h == header)?;
+
+        Some(self.rows.iter().map(|row| row[col].as_str()).collect())
+    }
+
+    /// Returns all data rows as maps from header text to cell text. If multiple columns share the same header text,
+    /// the last one wins.
+    pub fn records(&self) -> Vec<HashMap<&str, &str>> {
+        self.rows
+            .iter()
+            .map(|row| {
+                self.headers
+                    .iter()
+                    .map(String::as_str)
+                    .zip(row.iter().map(String::as_str))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns the table as CSV according to [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180), with the headers as
+    /// first line (if any).
+    pub fn to_csv(&self) -> String {
+        let mut csv = Vec::new();
+        self.write_csv(&mut csv)
+            .expect("writing to a Vec<u8> is infallible");
+
+        String::from_utf8(csv).expect("CSV is built from valid strings")
+    }
+
+    /// Writes the table as CSV to `writer`; see `to_csv()`.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        let lines = (!self.headers.is_empty())
+            .then_some(&self.headers)
+            .into_iter()
+            .chain(self.rows.iter());
+
+        for line in lines {
+            let fields = line.iter().map(|field| {
+                if field.contains([',', '"', '\r', '\n']) {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                } else {
+                    field.clone()
+                }
+            });
+            writer.write_all(fields.collect::<Vec<String>>().join(",").as_bytes())?;
+            writer.write_all(b"\r\n")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Table;
+    use scraper::{Html, Selector};
+
+    fn parse(html: &str) -> Table {
+        let html = Html::parse_document(html);
+        let selector = Selector::parse("table").unwrap();
+        let element = html.select(&selector).next().unwrap();
+        Table::from_element(&element).unwrap()
+    }
+
+    #[test]
+    fn expand_spans() {
+        let table = parse(
+            r#"<table>
+                <caption> Quarterly  report </caption>
+                <thead>
+                    <tr><th rowspan="2">Name</th><th colspan="2">Revenue</th></tr>
+                    <tr><th>Q1</th><th>Q2</th></tr>
+                </thead>
+                <tbody>
+                    <tr><td rowspan="2">Ferris</td><td>10</td><td>12</td></tr>
+                    <tr><td colspan="2">n/a</td></tr>
+                    <tr><td>Corro</td><td>7, maybe "8"</td></tr>
+                </tbody>
+            </table>"#,
+        );
+
+        assert_eq!(table.caption(), Some("Quarterly report"));
+        assert_eq!(table.headers(), ["Name", "Revenue / Q1", "Revenue / Q2"]);
+        assert_eq!(
+            table.rows(),
+            [
+                ["Ferris", "10", "12"],
+                ["Ferris", "n/a", "n/a"],
+                ["Corro", "7, maybe \"8\"", ""],
+            ]
+        );
+        assert_eq!(
+            table.column("Name"),
+            Some(vec!["Ferris", "Ferris", "Corro"])
+        );
+        assert_eq!(table.records()[2]["Revenue / Q1"], "7, maybe \"8\"");
+        assert_eq!(
+            table.to_csv(),
+            "Name,Revenue / Q1,Revenue / Q2\r\n\
+             Ferris,10,12\r\n\
+             Ferris,n/a,n/a\r\n\
+             Corro,\"7, maybe \"\"8\"\"\",\r\n"
+        );
+    }
+
+    #[test]
+    fn header_rows_without_thead() {
+        let table = parse(
+            r#"<table>
+                <tr><th>Key</th><th>Value</th></tr>
+                <tr><td>a</td><td>1 <table><tr><td>nested</td></tr></table></td></tr>
+                <tr><th>b</th><td>2</td></tr>
+            </table>"#,
+        );
+
+        assert_eq!(table.caption(), None);
+        assert_eq!(table.headers(), ["Key", "Value"]);
+        assert_eq!(table.rows(), [["a", "1 nested"], ["b", "2"]]);
+
+        let table = parse("<table><tr><td>x</td></tr></table>");
+        assert!(table.headers().is_empty());
+        assert_eq!(table.to_csv(), "x\r\n");
+    }
+}
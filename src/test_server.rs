@@ -0,0 +1,634 @@
+//! Module containing the [`TestServer`][TestServer], a local http(s) server for integration tests. Requires the
+//! `test-server` feature.
+//!
+//! A test server runs on a background thread, bound to a random port on `127.0.0.1`. It answers requests, in this
+//! order, with
+//! * scripted responses (`route()`, `respond()`, `redirect()`),
+//! * echo pages (`echo()`) describing the received request and containing a form to submit,
+//! * static files from a fixture directory (`serve_dir()`),
+//! * or a `404 Not Found` response.
+//!
+//! All received requests are recorded for assertions. With `https()`, the server uses a freshly generated certificate
+//! which browsers have to trust via `BrowserBuilder::add_cert()`. The server shuts down when dropped.
+//!
+//! ```
+//! use no_browser::{test_server::TestServer, Browser, InputType};
+//!
+//! let server = TestServer::builder().echo("/echo").https().start()?;
+//!
+//! let browser = Browser::builder()
+//!     .add_cert(server.certificate().unwrap())
+//!     .finish()?;
+//! let mut page = browser.navigate_to(server.url("/echo?method=post").as_str(), None)?;
+//!
+//! let form = page.form_by_id_mut("form")?;
+//! form.input_mut(InputType::Text, "text")?.set_value(Some("hello".to_owned()));
+//! let page = browser.submit_form(form, None)?;
+//!
+//! assert_eq!(page.select_first("#method")?.inner_html(), "POST");
+//! assert_eq!(page.select_first("#payload")?.inner_html(), "text=hello");
+//! assert_eq!(server.requests().len(), 2);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{
+    mock::{self, RequestLog},
+    transport::{Request, Response},
+};
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+use reqwest::{
+    header::{
+        HeaderName, HeaderValue, CONTENT_LENGTH, CONTENT_TYPE, SET_COOKIE, TRANSFER_ENCODING,
+    },
+    Certificate, Method, StatusCode, Url,
+};
+use std::{
+    fmt::{self, Debug, Formatter},
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    thread::{self, JoinHandle},
+};
+use thiserror::Error;
+use tiny_http::{Header, Server, SslConfig};
+
+/// An error occurred while starting a [`TestServer`][TestServer].
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The server could not bind to a local port.
+    #[error("Failed to start the test server!")]
+    StartServerError {
+        /// The underlying error.
+        #[source]
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// The certificate for https could not be generated.
+    #[error("Failed to generate the test server certificate!")]
+    GenerateCertificateError {
+        /// The underlying error.
+        #[source]
+        source: rcgen::Error,
+    },
+
+    /// The fixture directory to serve does not exist.
+    #[error("Directory '{path}' does not exist!")]
+    DirectoryNotFoundError {
+        /// The `path` of the directory.
+        path: String,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::test_server::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+type Handler = Box<dyn Fn(&Request) -> Response + Send + Sync>;
+
+struct Route {
+    method: Option<Method>,
+    path: String,
+    handler: Handler,
+}
+
+/// A builder to configure and start a [`TestServer`][TestServer]. Use `start()` to launch the server.
+#[derive(Default)]
+pub struct TestServerBuilder {
+    routes: Vec<Route>,
+    echo_paths: Vec<String>,
+    dir: Option<PathBuf>,
+    cookies: Vec<HeaderValue>,
+    https: bool,
+}
+
+impl Debug for TestServerBuilder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestServerBuilder")
+            .field("routes", &route_names(&self.routes))
+            .field("echo_paths", &self.echo_paths)
+            .field("dir", &self.dir)
+            .field("cookies", &self.cookies)
+            .field("https", &self.https)
+            .finish()
+    }
+}
+
+impl TestServerBuilder {
+    /// Answers requests with the given http `method` and url `path` (without query string) with the given `handler`.
+    pub fn route<F>(mut self, method: Method, path: &str, handler: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        self.routes.push(Route {
+            method: Some(method),
+            path: path.to_owned(),
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    /// Answers requests with the given http `method` and url `path` with (a clone of) `response`.
+    pub fn respond(self, method: Method, path: &str, response: Response) -> Self {
+        self.route(method, path, move |_| response.clone())
+    }
+
+    /// Redirects requests of any http method for the url `path` to `location` with a `303 See Other` response.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `location` is not a valid header value, e.g. if it contains line breaks.
+    pub fn redirect(mut self, path: &str, location: &str) -> Self {
+        let response = mock::redirect(location);
+        self.routes.push(Route {
+            method: None,
+            path: path.to_owned(),
+            handler: Box::new(move |_| response.clone()),
+        });
+        self
+    }
+
+    /// Answers requests for the url `path` with an echo page. The page lists method (`#method`), url (`#url`), path
+    /// (`#path`), headers (`li.header`), query parameters (`li.query`), form data (`li.form-data`) and raw payload
+    /// (`#payload`) of the request. It also contains a form (`#form`) with a text input `text` and a submit button
+    /// `submit`; the query parameters `action` and `method` set the form's action and method.
+    pub fn echo(mut self, path: &str) -> Self {
+        self.echo_paths.push(path.to_owned());
+        self
+    }
+
+    /// Serves the files in directory `dir`, e.g. `dir/css/main.css` for the url path `/css/main.css`. Requests for a
+    /// directory are answered with its `index.html`.
+    pub fn serve_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dir = Some(dir.as_ref().to_owned());
+        self
+    }
+
+    /// Adds a `Set-Cookie` header with the given `cookie` (e.g. `"SESSION=1; Path=/; HttpOnly"`) to every response.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cookie` is not a valid header value, e.g. if it contains line breaks.
+    pub fn cookie(mut self, cookie: &str) -> Self {
+        let cookie = HeaderValue::from_str(cookie).expect("cookie must be a valid header value");
+        self.cookies.push(cookie);
+        self
+    }
+
+    /// Serves https instead of http, using a certificate for `localhost` and `127.0.0.1` issued by a freshly
+    /// generated certificate authority. See `TestServer::certificate()`.
+    pub const fn https(mut self) -> Self {
+        self.https = true;
+        self
+    }
+
+    /// Starts the [`TestServer`][TestServer] on a random local port.
+    pub fn start(self) -> Result<TestServer> {
+        if let Some(dir) = self.dir.as_ref().filter(|dir| !dir.is_dir()) {
+            return Err(Error::DirectoryNotFoundError {
+                path: dir.display().to_string(),
+            });
+        }
+
+        let (server, certificate) = if self.https {
+            let (ssl_config, certificate) = generate_certificates()?;
+            let server = Server::https("127.0.0.1:0", ssl_config)
+                .map_err(|error| Error::StartServerError { source: error })?;
+            (server, Some(certificate))
+        } else {
+            let server = Server::http("127.0.0.1:0")
+                .map_err(|error| Error::StartServerError { source: error })?;
+            (server, None)
+        };
+
+        let addr = server.server_addr().to_ip().unwrap();
+        let scheme = if self.https { "https" } else { "http" };
+        let url = Url::parse(&format!("{scheme}://{addr}/")).unwrap();
+
+        let server = Arc::new(server);
+        let log = RequestLog::default();
+
+        let worker = Worker {
+            builder: self,
+            url: url.clone(),
+            log: log.clone(),
+        };
+        let thread = {
+            let server = server.clone();
+            thread::spawn(move || worker.run(&server))
+        };
+
+        Ok(TestServer {
+            url,
+            certificate,
+            log,
+            server,
+            thread: Some(thread),
+        })
+    }
+}
+
+/// A local http(s) server for integration tests, running on a background thread. See the
+/// [module documentation][crate::test_server] for an example.
+pub struct TestServer {
+    url: Url,
+    certificate: Option<String>,
+    log: RequestLog,
+    server: Arc<Server>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Debug for TestServer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestServer")
+            .field("url", &self.url.as_str())
+            .field("https", &self.certificate.is_some())
+            .field("log", &self.log)
+            .finish()
+    }
+}
+
+impl TestServer {
+    /// Returns a [`TestServerBuilder`][TestServerBuilder] to configure the server.
+    pub fn builder() -> TestServerBuilder {
+        TestServerBuilder::default()
+    }
+
+    /// Returns the base url of the server, e.g. `http://127.0.0.1:34567/`.
+    pub const fn base_url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns the absolute url of `path` (which may include a query string) on the server.
+    pub fn url(&self, path: &str) -> Url {
+        self.url.join(path).unwrap()
+    }
+
+    /// Returns the certificate authority to trust for https connections to the server, if started with `https()`.
+    pub fn certificate(&self) -> Option<Certificate> {
+        self.certificate_pem()
+            .map(|pem| Certificate::from_pem(pem.as_bytes()).unwrap())
+    }
+
+    /// Returns the PEM encoded certificate authority, if started with `https()`.
+    pub fn certificate_pem(&self) -> Option<&str> {
+        self.certificate.as_deref()
+    }
+
+    /// Returns (clones of) all requests received so far, in order.
+    pub fn requests(&self) -> Vec<Request> {
+        self.log.requests()
+    }
+
+    /// Returns a handle to the log of all requests received by the server.
+    pub fn request_log(&self) -> RequestLog {
+        self.log.clone()
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+struct Worker {
+    builder: TestServerBuilder,
+    url: Url,
+    log: RequestLog,
+}
+
+impl Worker {
+    fn run(&self, server: &Server) {
+        for mut incoming in server.incoming_requests() {
+            let request = self.convert_request(&mut incoming);
+            self.log.push(request.clone());
+
+            let mut response = self.handle(&request);
+            for cookie in &self.builder.cookies {
+                response.headers_mut().append(SET_COOKIE, cookie.clone());
+            }
+
+            let _ = incoming.respond(convert_response(response));
+        }
+    }
+
+    fn convert_request(&self, incoming: &mut tiny_http::Request) -> Request {
+        let method = Method::from_bytes(incoming.method().as_str().as_bytes()).unwrap_or_default();
+        let url = self
+            .url
+            .join(incoming.url())
+            .unwrap_or_else(|_| self.url.clone());
+
+        let mut request = Request::new(method, url);
+        for header in incoming.headers() {
+            let name = HeaderName::from_bytes(header.field.as_str().as_str().as_bytes());
+            let value = HeaderValue::from_str(header.value.as_str());
+            if let (Ok(name), Ok(value)) = (name, value) {
+                request.headers_mut().append(name, value);
+            }
+        }
+
+        let mut body = Vec::new();
+        if incoming.as_reader().read_to_end(&mut body).is_ok() && !body.is_empty() {
+            request = request.with_body(body);
+        }
+
+        request
+    }
+
+    fn handle(&self, request: &Request) -> Response {
+        let path = request.url().path();
+
+        let route = self.builder.routes.iter().find(|route| {
+            route
+                .method
+                .as_ref()
+                .is_none_or(|method| method == request.method())
+                && route.path == path
+        });
+        if let Some(route) = route {
+            return (route.handler)(request);
+        }
+
+        if self.builder.echo_paths.iter().any(|echo| echo == path) {
+            return mock::html(&echo_page(request));
+        }
+
+        self.builder
+            .dir
+            .as_ref()
+            .and_then(|dir| serve_file(dir, request.url()))
+            .unwrap_or_else(|| Response::new(StatusCode::NOT_FOUND))
+    }
+}
+
+fn route_names(routes: &[Route]) -> Vec<String> {
+    routes
+        .iter()
+        .map(|route| match &route.method {
+            Some(method) => format!("{method} {}", route.path),
+            None => format!("* {}", route.path),
+        })
+        .collect()
+}
+
+fn convert_response(response: Response) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter(|(name, _)| **name != CONTENT_LENGTH && **name != TRANSFER_ENCODING)
+        .filter_map(|(name, value)| Header::from_bytes(name.as_str(), value.as_bytes()).ok())
+        .collect::<Vec<Header>>();
+
+    let mut converted =
+        tiny_http::Response::from_data(response.into_body()).with_status_code(status);
+    for header in headers {
+        converted.add_header(header);
+    }
+
+    converted
+}
+
+fn serve_file(dir: &Path, url: &Url) -> Option<Response> {
+    let mut path = dir.to_owned();
+    for segment in url.path_segments()?.filter(|segment| !segment.is_empty()) {
+        if segment == ".." || segment.contains(['/', '\\']) {
+            return None;
+        }
+        path.push(segment);
+    }
+    if path.is_dir() {
+        path.push("index.html");
+    }
+
+    let body = fs::read(&path).ok()?;
+    let content_type = content_type(&path);
+
+    Some(
+        Response::new(StatusCode::OK)
+            .with_header(CONTENT_TYPE, HeaderValue::from_static(content_type))
+            .with_body(body),
+    )
+}
+
+fn content_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    match extension.as_str() {
+        "html" | "htm" => "text/html; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "txt" => "text/plain; charset=utf-8",
+        "csv" => "text/csv; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
+
+fn echo_page(request: &Request) -> String {
+    let url = request.url();
+
+    let headers = request
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let header = format!("{name}: {}", String::from_utf8_lossy(value.as_bytes()));
+            format!("\n        <li class=\"header\">{}</li>", escape(&header))
+        })
+        .collect::<String>();
+    let list = |class: &str, pairs: &[(String, String)]| {
+        pairs
+            .iter()
+            .map(|(key, value)| {
+                format!(
+                    "\n        <li class=\"{class}\">{}={}</li>",
+                    escape(key),
+                    escape(value)
+                )
+            })
+            .collect::<String>()
+    };
+    let query = url
+        .query_pairs()
+        .into_owned()
+        .collect::<Vec<(String, String)>>();
+    let form_data = request.form_data();
+    let payload = String::from_utf8_lossy(request.body().unwrap_or_default());
+
+    let param = |name: &str, default: &str| {
+        query
+            .iter()
+            .find(|(key, _)| key == name)
+            .map_or(default.to_owned(), |(_, value)| value.clone())
+    };
+    let form_action = param("action", url.path());
+    let form_method = param("method", "get");
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Echo</title>
+</head>
+<body>
+    <h1>Method</h1>
+    <p id="method">{}</p>
+    <h1>URL</h1>
+    <p id="url">{}</p>
+    <h1>Path</h1>
+    <p id="path">{}</p>
+    <h1>Headers</h1>
+    <ul>{headers}
+    </ul>
+    <h1>Query parameters</h1>
+    <ul>{}
+    </ul>
+    <h1>Form data</h1>
+    <ul>{}
+    </ul>
+    <h1>Payload</h1>
+    <p id="payload">{}</p>
+    <h1>Form</h1>
+    <form id="form" action="{}" method="{}">
+        <input type="text" name="text" value="">
+        <button type="submit" name="submit" value="submit">SUBMIT</button>
+    </form>
+</body>
+</html>
+"#,
+        escape(request.method().as_str()),
+        escape(url.as_str()),
+        escape(url.path()),
+        list("query", &query),
+        list("form-data", &form_data),
+        escape(&payload),
+        escape(&form_action),
+        escape(&form_method),
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn generate_certificates() -> Result<(SslConfig, String)> {
+    let generate = || -> std::result::Result<(SslConfig, String), rcgen::Error> {
+        let ca_key = KeyPair::generate()?;
+        let mut ca_params = CertificateParams::new(Vec::new())?;
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key)?;
+
+        let key = KeyPair::generate()?;
+        let params = CertificateParams::new(vec!["localhost".to_owned(), "127.0.0.1".to_owned()])?;
+        let cert = params.signed_by(&key, &ca, &ca_key)?;
+
+        let ssl_config = SslConfig {
+            certificate: cert.pem().into_bytes(),
+            private_key: key.serialize_pem().into_bytes(),
+        };
+
+        Ok((ssl_config, ca.pem()))
+    };
+
+    generate().map_err(|error| Error::GenerateCertificateError { source: error })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TestServer;
+    use crate::{browser::Browser, mock, transport::Response};
+    use reqwest::{Method, StatusCode};
+    use std::fs;
+
+    #[test]
+    fn serve_routes_and_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("docs")).unwrap();
+        fs::write(dir.path().join("docs/index.html"), "<h1>Docs</h1>").unwrap();
+        fs::write(dir.path().join("style.css"), "h1 {}").unwrap();
+
+        let server = TestServer::builder()
+            .serve_dir(dir.path())
+            .respond(
+                Method::GET,
+                "/teapot",
+                Response::new(StatusCode::IM_A_TEAPOT),
+            )
+            .route(Method::GET, "/whoami", |request| {
+                mock::html(&format!("cookies: {:?}", request.headers().get("cookie")))
+            })
+            .redirect("/old", "/docs/")
+            .cookie("SESSION=42; Path=/")
+            .start()
+            .unwrap();
+        let browser = Browser::builder().finish().unwrap();
+
+        let page = browser
+            .navigate_to(server.url("/old").as_str(), None)
+            .unwrap();
+        assert_eq!(page.url().path(), "/docs/");
+        assert_eq!(page.select_first("h1").unwrap().inner_html(), "Docs");
+
+        let page = browser
+            .navigate_to(server.url("/style.css").as_str(), None)
+            .unwrap();
+        assert_eq!(page.headers()["content-type"], "text/css; charset=utf-8");
+
+        let page = browser
+            .navigate_to(server.url("/whoami").as_str(), None)
+            .unwrap();
+        assert_eq!(page.text(), "cookies: Some(\"SESSION=42\")");
+
+        let page = browser
+            .navigate_to(server.url("/teapot").as_str(), None)
+            .unwrap();
+        assert_eq!(*page.status(), StatusCode::IM_A_TEAPOT);
+
+        let page = browser
+            .navigate_to(server.url("/../missing").as_str(), None)
+            .unwrap();
+        assert_eq!(*page.status(), StatusCode::NOT_FOUND);
+
+        let paths = server
+            .requests()
+            .iter()
+            .map(|request| request.url().path().to_owned())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            paths,
+            [
+                "/old",
+                "/docs/",
+                "/style.css",
+                "/whoami",
+                "/teapot",
+                "/missing"
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_directory() {
+        let result = TestServer::builder().serve_dir("does/not/exist").start();
+        assert!(result.is_err());
+    }
+}
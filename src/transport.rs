@@ -0,0 +1,369 @@
+//! Module containing the [`Transport`][Transport] trait, the pluggable layer sending http requests for a
+//! [`Browser`][crate::Browser].
+//!
+//! By default, a browser sends requests over the network using the [`HttpTransport`][HttpTransport]. Alternative
+//! transports, like the [`CassetteTransport`][crate::cassette::CassetteTransport] to record and replay traffic, are
+//! attached with `BrowserBuilder::transport()`.
+//!
+//! Transports only exchange single requests and responses: following redirects and handling cookies remain the
+//! responsibility of the [`Browser`][crate::Browser].
+
+use crate::{cassette, headers::TypedHeaders};
+use encoding_rs::{Encoding, UTF_8};
+use reqwest::{
+    blocking::Client,
+    header::{HeaderMap, HeaderName, HeaderValue},
+    redirect::Policy,
+    Method, StatusCode, Url, Version,
+};
+use std::fmt::Debug;
+use thiserror::Error;
+
+/// An error occurred while sending a request with a [`Transport`][Transport].
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The underlying [reqwest `Client`](https://crates.io/crates/reqwest) could not be built.
+    #[error("Failed to construct the http client!")]
+    ConstructHttpClientError {
+        /// The underlying error.
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// There was an error with [reqwest](https://crates.io/crates/reqwest) while sending the web request.
+    #[error("Failed to send the request!")]
+    SendRequestError {
+        /// The underlying error.
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// The server response could not be decoded by [reqwest](https://crates.io/crates/reqwest).
+    #[error("Failed to decode repsonse body!")]
+    ResponseBodyDecodeError {
+        /// The underlying error.
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// There was an error while recording or replaying a cassette.
+    #[error("{source}")]
+    CassetteError {
+        /// The underlying error.
+        #[from]
+        source: cassette::Error,
+    },
+
+    /// There was an error in a custom [`Transport`][Transport] implementation or a transport wrapping another one.
+    #[error("{message}")]
+    CustomError {
+        /// A description of the error.
+        message: String,
+        /// The underlying error, if any.
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::transport::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A single http request to be sent by a [`Transport`][Transport].
+#[derive(Debug, Clone)]
+pub struct Request {
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Option<Vec<u8>>,
+    #[cfg(feature = "tracing")]
+    masked_query_params: Vec<String>,
+}
+
+impl Request {
+    /// Returns a new [`Request`][Request] without headers and body.
+    pub fn new(method: Method, url: Url) -> Self {
+        Self {
+            method,
+            url,
+            headers: HeaderMap::new(),
+            body: None,
+            #[cfg(feature = "tracing")]
+            masked_query_params: Vec::new(),
+        }
+    }
+
+    /// Masks the values of the query parameters `names` in `loggable_url()`, e.g. of the password inputs of a form.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn with_masked_query_params(mut self, names: Vec<String>) -> Self {
+        self.masked_query_params = names;
+        self
+    }
+
+    /// Returns the names of the query parameters masked in `loggable_url()`.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn masked_query_params(&self) -> &[String] {
+        &self.masked_query_params
+    }
+
+    /// Returns the url of the request for logging, with the values of masked query parameters replaced.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn loggable_url(&self) -> Url {
+        mask_query(&self.url, &self.masked_query_params)
+    }
+
+    /// Appends a header to the request.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets the body of the request.
+    pub fn with_body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// Returns the http method of the request.
+    pub const fn method(&self) -> &Method {
+        &self.method
+    }
+
+    /// Returns the url of the request.
+    pub const fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Returns a mutable reference to the url of the request.
+    pub fn url_mut(&mut self) -> &mut Url {
+        &mut self.url
+    }
+
+    /// Returns the headers of the request.
+    pub const fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns a mutable reference to the headers of the request.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    /// Returns the body of the request, if any.
+    pub fn body(&self) -> Option<&[u8]> {
+        self.body.as_deref()
+    }
+
+    /// Returns the name/value pairs of a `application/x-www-form-urlencoded` body, e.g. of a submitted form. Returns
+    /// an empty list if there is no body.
+    pub fn form_data(&self) -> Vec<(String, String)> {
+        let Some(body) = self.body() else {
+            return Vec::new();
+        };
+
+        let mut url = Url::parse("http://localhost/").unwrap();
+        url.set_query(Some(&String::from_utf8_lossy(body)));
+
+        url.query_pairs().into_owned().collect()
+    }
+}
+
+/// Returns `url` with the values of the query parameters `names` replaced by `********`.
+#[cfg(feature = "tracing")]
+pub(crate) fn mask_query(url: &Url, names: &[String]) -> Url {
+    let mut url = url.clone();
+    if names.is_empty() || url.query().is_none() {
+        return url;
+    }
+
+    let pairs = url
+        .query_pairs()
+        .map(
+            |(name, value)| match names.iter().any(|masked| *masked == name) {
+                true => (name.into_owned(), "********".to_owned()),
+                false => (name.into_owned(), value.into_owned()),
+            },
+        )
+        .collect::<Vec<(String, String)>>();
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+
+    url
+}
+
+/// A single http response returned by a [`Transport`][Transport].
+#[derive(Debug, Clone)]
+pub struct Response {
+    status: StatusCode,
+    version: Version,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+impl Response {
+    /// Returns a new [`Response`][Response] with the given `status`, http version 1.1 and neither headers nor body.
+    pub fn new(status: StatusCode) -> Self {
+        Self {
+            status,
+            version: Version::HTTP_11,
+            headers: HeaderMap::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// Sets the http version of the response.
+    pub const fn with_version(mut self, version: Version) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Appends a header to the response.
+    pub fn with_header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets the body of the response.
+    pub fn with_body<B: Into<Vec<u8>>>(mut self, body: B) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Returns the http status of the response.
+    pub const fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns the http version of the response.
+    pub const fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns the headers of the response.
+    pub const fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns a mutable reference to the headers of the response.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        &mut self.headers
+    }
+
+    /// Returns the body of the response.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Returns the body decoded with the charset of the `Content-Type` header, like `reqwest`'s `Response::text()`.
+    /// Defaults to UTF-8 for missing or unknown charsets; malformed sequences are replaced with `U+FFFD`.
+    pub fn text(&self) -> String {
+        let encoding = TypedHeaders::new(&self.headers)
+            .content_type()
+            .and_then(|content_type| {
+                content_type
+                    .charset()
+                    .and_then(|charset| Encoding::for_label(charset.as_bytes()))
+            })
+            .unwrap_or(UTF_8);
+        let (text, _, _) = encoding.decode(&self.body);
+
+        text.into_owned()
+    }
+
+    /// Consumes the response, returning its body.
+    pub fn into_body(self) -> Vec<u8> {
+        self.body
+    }
+}
+
+/// A pluggable layer sending single http requests for a [`Browser`][crate::Browser]. Implementations must not follow
+/// redirects or handle cookies themselves.
+pub trait Transport: Debug + Send + Sync {
+    /// Sends the given `request` and returns the response.
+    fn send(&self, request: &Request) -> Result<Response>;
+}
+
+/// The default [`Transport`][Transport] sending requests over the network using a
+/// [reqwest `Client`](https://crates.io/crates/reqwest).
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    client: Client,
+}
+
+impl HttpTransport {
+    /// Returns an [`HttpTransport`][HttpTransport] using a default client.
+    pub fn new() -> Result<Self> {
+        let client = Client::builder()
+            .redirect(Policy::none())
+            .build()
+            .map_err(|error| Error::ConstructHttpClientError { source: error })?;
+
+        Ok(Self::from_client(client))
+    }
+
+    /// Returns an [`HttpTransport`][HttpTransport] using the given `client`. The client must neither follow redirects
+    /// (see [`reqwest::redirect::Policy::none()`]) nor have a cookie store, as both is handled by the
+    /// [`Browser`][crate::Browser].
+    pub const fn from_client(client: Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send(&self, request: &Request) -> Result<Response> {
+        let mut rb = self
+            .client
+            .request(request.method.clone(), request.url.clone())
+            .headers(request.headers.clone());
+        if let Some(body) = &request.body {
+            rb = rb.body(body.clone());
+        }
+
+        let resp = rb
+            .send()
+            .map_err(|error| Error::SendRequestError { source: error })?;
+
+        let status = resp.status();
+        let version = resp.version();
+        let headers = resp.headers().clone();
+        let body = resp
+            .bytes()
+            .map_err(|error| Error::ResponseBodyDecodeError { source: error })?;
+
+        Ok(Response {
+            status,
+            version,
+            headers,
+            body: body.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HttpTransport, Request, Transport};
+    use reqwest::{header::LOCATION, Method, StatusCode, Url};
+    use std::thread;
+    use tiny_http::{Header, Response, Server};
+
+    #[test]
+    fn send_without_following_redirects() {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let addr = server.server_addr().to_ip().unwrap();
+        thread::spawn(move || {
+            let mut request = server.incoming_requests().next().unwrap();
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let response = Response::from_string(body)
+                .with_status_code(302)
+                .with_header(Header::from_bytes("Location", "/elsewhere").unwrap());
+            request.respond(response).unwrap();
+        });
+
+        let url = Url::parse(&format!("http://{addr}/")).unwrap();
+        let request = Request::new(Method::PUT, url).with_body("payload");
+        let response = HttpTransport::new().unwrap().send(&request).unwrap();
+
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(response.headers()[LOCATION], "/elsewhere");
+        assert_eq!(response.body(), b"payload");
+    }
+}
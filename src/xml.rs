@@ -0,0 +1,38 @@
+//! Helpers shared by the XML based parsers, i.e. [`Feed`][crate::feed::Feed] and
+//! [`Sitemap`][crate::sitemap::Sitemap].
+
+use roxmltree::Node;
+
+/// Returns the child elements of `node` with the local `name`, regardless of their namespace.
+pub(crate) fn elements<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children()
+        .filter(move |child| child.is_element() && child.tag_name().name() == name)
+}
+
+/// Returns the first child element of `node` with the local `name`.
+pub(crate) fn child<'a, 'input>(
+    node: Node<'a, 'input>,
+    name: &'static str,
+) -> Option<Node<'a, 'input>> {
+    elements(node, name).next()
+}
+
+/// Returns the [`text()`][text] of the first child element of `node` with the local `name` that isn't blank. Blank
+/// elements are skipped, e.g. an `<atom:link href="..."/>` preceding the `<link>` of an RSS channel.
+pub(crate) fn child_text(node: Node<'_, '_>, name: &'static str) -> Option<String> {
+    elements(node, name).find_map(text)
+}
+
+/// Returns the trimmed text of `node`, including the text of nested elements (e.g. Atom `type="xhtml"` content), or
+/// `None` if it is blank.
+pub(crate) fn text(node: Node<'_, '_>) -> Option<String> {
+    let text = node
+        .descendants()
+        .filter(Node::is_text)
+        .filter_map(|node| node.text())
+        .collect::<String>();
+    Some(text.trim().to_owned()).filter(|text| !text.is_empty())
+}
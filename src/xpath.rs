@@ -0,0 +1,1715 @@
+//! Module containing the [`XPath`][XPath] evaluator.
+//!
+//! [`XPath`][XPath] implements [XPath 1.0](https://www.w3.org/TR/1999/REC-xpath-19991116/) on top of the html tree
+//! parsed by [scraper](https://crates.io/crates/scraper). It supports all axes except `namespace`, all node tests, the
+//! full operator set and the core function library. Namespaces and variables are not supported. Element and attribute
+//! names are matched case-insensitively, as html itself is case-insensitive.
+//!
+//! Use it through the `xpath*()` methods of [`Page`][crate::page::Page] or directly:
+//!
+//! ```
+//! use no_browser::xpath::XPath;
+//! use scraper::Html;
+//!
+//! let html = Html::parse_document(r#"<ul><li>a</li><li class="x">b</li></ul>"#);
+//! let xpath = XPath::compile("count(//li[@class = 'x']/preceding-sibling::li)")?;
+//! assert_eq!(xpath.evaluate(&html)?.number(), 1.0);
+//! # Ok::<(), no_browser::xpath::Error>(())
+//! ```
+
+use ego_tree::{NodeId, NodeRef};
+use scraper::{ElementRef, Html, Node};
+use std::{collections::HashMap, fmt::Debug};
+use thiserror::Error;
+
+/// An error occurred while parsing or evaluating an XPath expression.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The given XPath expression could not be parsed.
+    #[error(
+        "Failed to parse XPath expression '{expression}' at position {position}, reason: {reason}"
+    )]
+    ParseError {
+        /// The given `expression` that could not be parsed.
+        expression: String,
+        /// The character position the parser failed at.
+        position: usize,
+        /// The `reason` given by the parser.
+        reason: String,
+    },
+
+    /// The given XPath expression could not be evaluated, e.g. due to calling an unknown function.
+    #[error("Failed to evaluate XPath expression '{expression}', reason: {reason}")]
+    EvaluationError {
+        /// The given `expression` that could not be evaluated.
+        expression: String,
+        /// The `reason` the evaluation failed.
+        reason: String,
+    },
+
+    /// The given XPath expression evaluated to an unexpected type, e.g. a number instead of a node-set.
+    #[error(
+        "XPath expression '{expression}' evaluated to a {actual}, but a {expected} was expected!"
+    )]
+    ResultTypeError {
+        /// The given `expression`.
+        expression: String,
+        /// The expected result type.
+        expected: String,
+        /// The actual result type.
+        actual: String,
+    },
+}
+
+/// Short-hand for `std::result::Result<T, no_browser::xpath::Error>`.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The result of evaluating an XPath expression.
+#[derive(Debug)]
+pub enum XPathValue<'a> {
+    /// A set of nodes in document order.
+    Nodes(Vec<XPathNode<'a>>),
+    /// A boolean value.
+    Boolean(bool),
+    /// A floating point number.
+    Number(f64),
+    /// A string.
+    String(String),
+}
+
+impl<'a> XPathValue<'a> {
+    /// Converts this value to a string as defined by the XPath `string()` function, i.e. a node-set is converted to the
+    /// string-value of its first node.
+    pub fn string(&self) -> String {
+        match self {
+            Self::Nodes(nodes) => nodes.first().map(|n| n.string_value()).unwrap_or_default(),
+            Self::Boolean(b) => b.to_string(),
+            Self::Number(n) => number_to_string(*n),
+            Self::String(s) => s.clone(),
+        }
+    }
+
+    /// Converts this value to a number as defined by the XPath `number()` function.
+    pub fn number(&self) -> f64 {
+        match self {
+            Self::Boolean(b) => f64::from(u8::from(*b)),
+            Self::Number(n) => *n,
+            _ => string_to_number(&self.string()),
+        }
+    }
+
+    /// Converts this value to a boolean as defined by the XPath `boolean()` function.
+    pub fn boolean(&self) -> bool {
+        match self {
+            Self::Nodes(nodes) => !nodes.is_empty(),
+            Self::Boolean(b) => *b,
+            Self::Number(n) => *n != 0.0 && !n.is_nan(),
+            Self::String(s) => !s.is_empty(),
+        }
+    }
+
+    /// Returns the nodes if this value is a node-set.
+    pub fn into_nodes(self) -> Option<Vec<XPathNode<'a>>> {
+        match self {
+            Self::Nodes(nodes) => Some(nodes),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of this value's type, i.e. `node-set`, `boolean`, `number` or `string`.
+    pub const fn type_name(&self) -> &'static str {
+        match self {
+            Self::Nodes(_) => "node-set",
+            Self::Boolean(_) => "boolean",
+            Self::Number(_) => "number",
+            Self::String(_) => "string",
+        }
+    }
+}
+
+/// The kinds of nodes an XPath node-set may contain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum XPathNodeKind {
+    /// The document (or fragment) root.
+    Root,
+    /// An element.
+    Element,
+    /// An attribute of an element.
+    Attribute,
+    /// A text node.
+    Text,
+    /// A comment.
+    Comment,
+    /// A processing instruction.
+    ProcessingInstruction,
+}
+
+/// A node selected by an XPath expression.
+#[derive(Clone, Copy)]
+pub struct XPathNode<'a> {
+    node: N<'a>,
+}
+
+impl<'a> XPathNode<'a> {
+    /// Returns the kind of this node.
+    pub fn kind(&self) -> XPathNodeKind {
+        match self.node {
+            N::Attr(..) => XPathNodeKind::Attribute,
+            N::Tree(node) => match node.value() {
+                Node::Element(_) => XPathNodeKind::Element,
+                Node::Text(_) => XPathNodeKind::Text,
+                Node::Comment(_) => XPathNodeKind::Comment,
+                Node::ProcessingInstruction(_) => XPathNodeKind::ProcessingInstruction,
+                _ => XPathNodeKind::Root,
+            },
+        }
+    }
+
+    /// Returns this node as [`ElementRef`][ElementRef] if it is an element.
+    pub fn as_element(&self) -> Option<ElementRef<'a>> {
+        match self.node {
+            N::Tree(node) => ElementRef::wrap(node),
+            N::Attr(..) => None,
+        }
+    }
+
+    /// Returns the name of elements and attributes or the target of processing instructions.
+    pub fn name(&self) -> Option<&'a str> {
+        node_name(self.node)
+    }
+
+    /// Returns the string-value of this node, e.g. the concatenated text of an element or the value of an attribute.
+    pub fn string_value(&self) -> String {
+        string_value(self.node)
+    }
+}
+
+impl Debug for XPathNode<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("XPathNode")
+            .field("kind", &self.kind())
+            .field("name", &self.name())
+            .field("string_value", &self.string_value())
+            .finish()
+    }
+}
+
+/// A compiled XPath 1.0 expression, ready to be evaluated against html documents.
+#[derive(Clone, Debug)]
+pub struct XPath {
+    expression: String,
+    expr: Expr,
+}
+
+impl XPath {
+    /// Parses the given XPath `expression`.
+    pub fn compile(expression: &str) -> Result<Self> {
+        let parse_error = |position, reason| Error::ParseError {
+            expression: expression.to_owned(),
+            position,
+            reason,
+        };
+
+        let tokens = tokenize(expression).map_err(|(pos, reason)| parse_error(pos, reason))?;
+        let mut parser = Parser {
+            tokens,
+            idx: 0,
+            len: expression.len(),
+        };
+        let expr = parser
+            .parse_expr()
+            .map_err(|(pos, reason)| parse_error(pos, reason))?;
+
+        if let Some((token, pos)) = parser.tokens.get(parser.idx) {
+            return Err(parse_error(*pos, format!("unexpected token {token:?}")));
+        }
+
+        Ok(Self {
+            expression: expression.to_owned(),
+            expr,
+        })
+    }
+
+    /// Returns the source of this expression.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// Evaluates this expression with the document root as context node.
+    pub fn evaluate<'a>(&self, html: &'a Html) -> Result<XPathValue<'a>> {
+        self.evaluate_node(html.tree.root())
+    }
+
+    /// Evaluates this expression with the given `element` as context node.
+    pub fn evaluate_from<'a>(&self, element: ElementRef<'a>) -> Result<XPathValue<'a>> {
+        self.evaluate_node(*element)
+    }
+
+    /// Evaluates this expression and returns the resulting nodes. Fails if the result is not a node-set.
+    pub fn select<'a>(&self, html: &'a Html) -> Result<Vec<XPathNode<'a>>> {
+        let value = self.evaluate(html)?;
+        let actual = value.type_name();
+
+        value.into_nodes().ok_or_else(|| Error::ResultTypeError {
+            expression: self.expression.clone(),
+            expected: "node-set".to_owned(),
+            actual: actual.to_owned(),
+        })
+    }
+
+    fn evaluate_node<'a>(&self, context: NodeRef<'a, Node>) -> Result<XPathValue<'a>> {
+        let mut root = context;
+        while let Some(parent) = root.parent() {
+            root = parent;
+        }
+
+        let order = root
+            .descendants()
+            .enumerate()
+            .map(|(i, n)| (n.id(), i))
+            .collect();
+        let evaluator = Evaluator { root, order };
+        let ctx = Context {
+            node: N::Tree(context),
+            position: 1,
+            size: 1,
+        };
+
+        let value = evaluator
+            .eval(&self.expr, &ctx)
+            .map_err(|reason| Error::EvaluationError {
+                expression: self.expression.clone(),
+                reason,
+            })?;
+
+        Ok(match value {
+            Value::Nodes(nodes) => {
+                XPathValue::Nodes(nodes.into_iter().map(|node| XPathNode { node }).collect())
+            }
+            Value::Bool(b) => XPathValue::Boolean(b),
+            Value::Num(n) => XPathValue::Number(n),
+            Value::Str(s) => XPathValue::String(s),
+        })
+    }
+}
+
+// ---------------------------------------------------------------------------------------------------------------------
+// Tokenizer
+// ---------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Dot,
+    DotDot,
+    At,
+    Comma,
+    ColonColon,
+    Slash,
+    DoubleSlash,
+    Pipe,
+    Plus,
+    Minus,
+    Eq,
+    Neq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// `*` as name test
+    Star,
+    /// `*` as operator
+    Multiply,
+    And,
+    Or,
+    Mod,
+    Div,
+    Literal(String),
+    Number(f64),
+    Name(String),
+    Variable(String),
+}
+
+impl Token {
+    /// Whether a `*` or a name following this token is to be read as an operator, see
+    /// [XPath 1.0, section 3.7](https://www.w3.org/TR/1999/REC-xpath-19991116/#exprlex).
+    fn precedes_operator(&self) -> bool {
+        !matches!(
+            self,
+            Self::At
+                | Self::ColonColon
+                | Self::LParen
+                | Self::LBracket
+                | Self::Comma
+                | Self::And
+                | Self::Or
+                | Self::Mod
+                | Self::Div
+                | Self::Multiply
+                | Self::Slash
+                | Self::DoubleSlash
+                | Self::Pipe
+                | Self::Plus
+                | Self::Minus
+                | Self::Eq
+                | Self::Neq
+                | Self::Lt
+                | Self::Le
+                | Self::Gt
+                | Self::Ge
+        )
+    }
+}
+
+type ParseResult<T> = std::result::Result<T, (usize, String)>;
+
+fn is_name_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '.'
+}
+
+fn tokenize(expression: &str) -> ParseResult<Vec<(Token, usize)>> {
+    let chars: Vec<(usize, char)> = expression.char_indices().collect();
+    let mut tokens: Vec<(Token, usize)> = Vec::new();
+    let mut i = 0;
+
+    let peek = |i: usize| chars.get(i).map(|(_, c)| *c);
+
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        let operator_context = tokens.last().is_some_and(|(t, _)| t.precedes_operator());
+
+        let token = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '[' => Token::LBracket,
+            ']' => Token::RBracket,
+            '@' => Token::At,
+            ',' => Token::Comma,
+            '|' => Token::Pipe,
+            '+' => Token::Plus,
+            '-' => Token::Minus,
+            '=' => Token::Eq,
+            '*' if operator_context => Token::Multiply,
+            '*' => Token::Star,
+            ':' if peek(i + 1) == Some(':') => {
+                i += 1;
+                Token::ColonColon
+            }
+            '/' if peek(i + 1) == Some('/') => {
+                i += 1;
+                Token::DoubleSlash
+            }
+            '/' => Token::Slash,
+            '!' if peek(i + 1) == Some('=') => {
+                i += 1;
+                Token::Neq
+            }
+            '<' if peek(i + 1) == Some('=') => {
+                i += 1;
+                Token::Le
+            }
+            '<' => Token::Lt,
+            '>' if peek(i + 1) == Some('=') => {
+                i += 1;
+                Token::Ge
+            }
+            '>' => Token::Gt,
+            '.' if peek(i + 1) == Some('.') => {
+                i += 1;
+                Token::DotDot
+            }
+            '.' if !peek(i + 1).is_some_and(|c| c.is_ascii_digit()) => Token::Dot,
+            '"' | '\'' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|(_, q)| *q == c)
+                    .ok_or((pos, "unterminated string literal".to_owned()))?;
+                let literal = chars[i + 1..i + 1 + end].iter().map(|(_, c)| c).collect();
+                i += end + 1;
+                Token::Literal(literal)
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = i;
+                while peek(end).is_some_and(|c| c.is_ascii_digit() || c == '.') {
+                    end += 1;
+                }
+                let number: String = chars[i..end].iter().map(|(_, c)| c).collect();
+                let number = number
+                    .parse()
+                    .map_err(|_| (pos, format!("invalid number '{number}'")))?;
+                i = end - 1;
+                Token::Number(number)
+            }
+            '$' => {
+                let mut end = i + 1;
+                while peek(end).is_some_and(is_name_char) {
+                    end += 1;
+                }
+                let name: String = chars[i + 1..end].iter().map(|(_, c)| c).collect();
+                if name.is_empty() {
+                    return Err((pos, "expected variable name after '$'".to_owned()));
+                }
+                i = end - 1;
+                Token::Variable(name)
+            }
+            c if is_name_start(c) => {
+                let mut end = i;
+                while peek(end).is_some_and(is_name_char) {
+                    end += 1;
+                }
+                // QName with prefix, e.g. `svg:rect` or `svg:*`, but not an axis like `child::`
+                if peek(end) == Some(':') && peek(end + 1) != Some(':') {
+                    if peek(end + 1) == Some('*') {
+                        end += 2;
+                    } else if peek(end + 1).is_some_and(is_name_start) {
+                        end += 1;
+                        while peek(end).is_some_and(is_name_char) {
+                            end += 1;
+                        }
+                    }
+                }
+                let name: String = chars[i..end].iter().map(|(_, c)| c).collect();
+                i = end - 1;
+
+                match name.as_str() {
+                    "and" if operator_context => Token::And,
+                    "or" if operator_context => Token::Or,
+                    "mod" if operator_context => Token::Mod,
+                    "div" if operator_context => Token::Div,
+                    _ => Token::Name(name),
+                }
+            }
+            c => return Err((pos, format!("unexpected character '{c}'"))),
+        };
+
+        tokens.push((token, pos));
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+// ---------------------------------------------------------------------------------------------------------------------
+// Parser
+// ---------------------------------------------------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Axis {
+    Ancestor,
+    AncestorOrSelf,
+    Attribute,
+    Child,
+    Descendant,
+    DescendantOrSelf,
+    Following,
+    FollowingSibling,
+    Parent,
+    Preceding,
+    PrecedingSibling,
+    Itself,
+}
+
+impl Axis {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "ancestor" => Self::Ancestor,
+            "ancestor-or-self" => Self::AncestorOrSelf,
+            "attribute" => Self::Attribute,
+            "child" => Self::Child,
+            "descendant" => Self::Descendant,
+            "descendant-or-self" => Self::DescendantOrSelf,
+            "following" => Self::Following,
+            "following-sibling" => Self::FollowingSibling,
+            "parent" => Self::Parent,
+            "preceding" => Self::Preceding,
+            "preceding-sibling" => Self::PrecedingSibling,
+            "self" => Self::Itself,
+            _ => return None,
+        })
+    }
+
+    const fn is_reverse(self) -> bool {
+        matches!(
+            self,
+            Self::Ancestor | Self::AncestorOrSelf | Self::Preceding | Self::PrecedingSibling
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+enum NodeTest {
+    /// `*` or `prefix:*`
+    Any,
+    Name(String),
+    Node,
+    Text,
+    Comment,
+    ProcessingInstruction(Option<String>),
+}
+
+#[derive(Clone, Debug)]
+struct Step {
+    axis: Axis,
+    test: NodeTest,
+    predicates: Vec<Expr>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Neq,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Union,
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Negate(Box<Expr>),
+    Literal(String),
+    Number(f64),
+    Variable(String),
+    Function(String, Vec<Expr>),
+    /// A location path, absolute (starting at the root) or relative to the context node.
+    Path {
+        absolute: bool,
+        steps: Vec<Step>,
+    },
+    /// A primary expression filtered by predicates and optionally followed by a relative location path.
+    Filter {
+        primary: Box<Expr>,
+        predicates: Vec<Expr>,
+        steps: Vec<Step>,
+    },
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    idx: usize,
+    len: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.idx).map(|(t, _)| t)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.idx + offset).map(|(t, _)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.idx)
+            .map(|(_, p)| *p)
+            .unwrap_or(self.len)
+    }
+
+    fn error<T>(&self, reason: &str) -> ParseResult<T> {
+        let reason = match self.peek() {
+            Some(token) => format!("{reason}, found {token:?}"),
+            None => format!("{reason}, found end of expression"),
+        };
+        Err((self.position(), reason))
+    }
+
+    fn accept(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.idx += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &Token) -> ParseResult<()> {
+        if self.accept(token) {
+            Ok(())
+        } else {
+            self.error(&format!("expected {token:?}"))
+        }
+    }
+
+    fn parse_expr(&mut self) -> ParseResult<Expr> {
+        self.parse_binary(0)
+    }
+
+    /// Parses the binary operators by precedence level, lowest first.
+    fn parse_binary(&mut self, level: usize) -> ParseResult<Expr> {
+        const LEVELS: [&[(Token, BinaryOp)]; 6] = [
+            &[(Token::Or, BinaryOp::Or)],
+            &[(Token::And, BinaryOp::And)],
+            &[(Token::Eq, BinaryOp::Eq), (Token::Neq, BinaryOp::Neq)],
+            &[
+                (Token::Lt, BinaryOp::Lt),
+                (Token::Le, BinaryOp::Le),
+                (Token::Gt, BinaryOp::Gt),
+                (Token::Ge, BinaryOp::Ge),
+            ],
+            &[(Token::Plus, BinaryOp::Add), (Token::Minus, BinaryOp::Sub)],
+            &[
+                (Token::Multiply, BinaryOp::Mul),
+                (Token::Div, BinaryOp::Div),
+                (Token::Mod, BinaryOp::Mod),
+            ],
+        ];
+
+        if level == LEVELS.len() {
+            return self.parse_unary();
+        }
+
+        let mut lhs = self.parse_binary(level + 1)?;
+        'outer: loop {
+            for (token, op) in LEVELS[level] {
+                if self.accept(token) {
+                    let rhs = self.parse_binary(level + 1)?;
+                    lhs = Expr::Binary(*op, Box::new(lhs), Box::new(rhs));
+                    continue 'outer;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn parse_unary(&mut self) -> ParseResult<Expr> {
+        if self.accept(&Token::Minus) {
+            return Ok(Expr::Negate(Box::new(self.parse_unary()?)));
+        }
+
+        let mut lhs = self.parse_path_expr()?;
+        while self.accept(&Token::Pipe) {
+            let rhs = self.parse_path_expr()?;
+            lhs = Expr::Binary(BinaryOp::Union, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_path_expr(&mut self) -> ParseResult<Expr> {
+        let is_filter_expr = match self.peek() {
+            Some(Token::Variable(_) | Token::LParen | Token::Literal(_) | Token::Number(_)) => true,
+            Some(Token::Name(name)) => {
+                self.peek_at(1) == Some(&Token::LParen)
+                    && !matches!(
+                        name.as_str(),
+                        "node" | "text" | "comment" | "processing-instruction"
+                    )
+            }
+            _ => false,
+        };
+
+        if !is_filter_expr {
+            return self.parse_location_path();
+        }
+
+        let primary = self.parse_primary()?;
+        let predicates = self.parse_predicates()?;
+        let mut steps = Vec::new();
+
+        if self.accept(&Token::Slash) {
+            self.parse_relative_path(&mut steps)?;
+        } else if self.accept(&Token::DoubleSlash) {
+            steps.push(descendant_or_self());
+            self.parse_relative_path(&mut steps)?;
+        }
+
+        if predicates.is_empty() && steps.is_empty() {
+            return Ok(primary);
+        }
+
+        Ok(Expr::Filter {
+            primary: Box::new(primary),
+            predicates,
+            steps,
+        })
+    }
+
+    fn parse_primary(&mut self) -> ParseResult<Expr> {
+        let token = self.peek().cloned();
+        self.idx += 1;
+
+        match token {
+            Some(Token::Variable(name)) => Ok(Expr::Variable(name)),
+            Some(Token::Literal(literal)) => Ok(Expr::Literal(literal)),
+            Some(Token::Number(number)) => Ok(Expr::Number(number)),
+            Some(Token::LParen) => {
+                let expr = self.parse_expr()?;
+                self.expect(&Token::RParen)?;
+                Ok(expr)
+            }
+            Some(Token::Name(name)) => {
+                self.expect(&Token::LParen)?;
+                let mut args = Vec::new();
+                if !self.accept(&Token::RParen) {
+                    loop {
+                        args.push(self.parse_expr()?);
+                        if self.accept(&Token::RParen) {
+                            break;
+                        }
+                        self.expect(&Token::Comma)?;
+                    }
+                }
+                Ok(Expr::Function(name, args))
+            }
+            _ => {
+                self.idx -= 1;
+                self.error("expected primary expression")
+            }
+        }
+    }
+
+    fn parse_predicates(&mut self) -> ParseResult<Vec<Expr>> {
+        let mut predicates = Vec::new();
+        while self.accept(&Token::LBracket) {
+            predicates.push(self.parse_expr()?);
+            self.expect(&Token::RBracket)?;
+        }
+        Ok(predicates)
+    }
+
+    fn can_start_step(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Dot | Token::DotDot | Token::At | Token::Star | Token::Name(_))
+        )
+    }
+
+    fn parse_location_path(&mut self) -> ParseResult<Expr> {
+        let mut steps = Vec::new();
+
+        let absolute = if self.accept(&Token::Slash) {
+            if self.can_start_step() {
+                self.parse_relative_path(&mut steps)?;
+            }
+            true
+        } else if self.accept(&Token::DoubleSlash) {
+            steps.push(descendant_or_self());
+            self.parse_relative_path(&mut steps)?;
+            true
+        } else {
+            self.parse_relative_path(&mut steps)?;
+            false
+        };
+
+        Ok(Expr::Path { absolute, steps })
+    }
+
+    fn parse_relative_path(&mut self, steps: &mut Vec<Step>) -> ParseResult<()> {
+        steps.push(self.parse_step()?);
+
+        loop {
+            if self.accept(&Token::Slash) {
+                steps.push(self.parse_step()?);
+            } else if self.accept(&Token::DoubleSlash) {
+                steps.push(descendant_or_self());
+                steps.push(self.parse_step()?);
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_step(&mut self) -> ParseResult<Step> {
+        if self.accept(&Token::Dot) {
+            return Ok(Step {
+                axis: Axis::Itself,
+                test: NodeTest::Node,
+                predicates: Vec::new(),
+            });
+        }
+        if self.accept(&Token::DotDot) {
+            return Ok(Step {
+                axis: Axis::Parent,
+                test: NodeTest::Node,
+                predicates: Vec::new(),
+            });
+        }
+
+        let axis = if self.accept(&Token::At) {
+            Axis::Attribute
+        } else if let (Some(Token::Name(name)), Some(Token::ColonColon)) =
+            (self.peek(), self.peek_at(1))
+        {
+            let axis = match Axis::parse(name) {
+                Some(axis) => axis,
+                None => return self.error("unsupported axis"),
+            };
+            self.idx += 2;
+            axis
+        } else {
+            Axis::Child
+        };
+
+        let test = self.parse_node_test()?;
+        let predicates = self.parse_predicates()?;
+
+        Ok(Step {
+            axis,
+            test,
+            predicates,
+        })
+    }
+
+    fn parse_node_test(&mut self) -> ParseResult<NodeTest> {
+        match self.peek().cloned() {
+            Some(Token::Star) => {
+                self.idx += 1;
+                Ok(NodeTest::Any)
+            }
+            Some(Token::Name(name)) if self.peek_at(1) == Some(&Token::LParen) => {
+                self.idx += 2;
+                let test = match name.as_str() {
+                    "node" => NodeTest::Node,
+                    "text" => NodeTest::Text,
+                    "comment" => NodeTest::Comment,
+                    "processing-instruction" => match self.peek().cloned() {
+                        Some(Token::Literal(target)) => {
+                            self.idx += 1;
+                            NodeTest::ProcessingInstruction(Some(target))
+                        }
+                        _ => NodeTest::ProcessingInstruction(None),
+                    },
+                    _ => {
+                        self.idx -= 2;
+                        return self.error("expected node test");
+                    }
+                };
+                self.expect(&Token::RParen)?;
+                Ok(test)
+            }
+            Some(Token::Name(name)) => {
+                self.idx += 1;
+                // namespaces are not supported, so prefixes are ignored
+                match name.split_once(':') {
+                    Some((_, "*")) => Ok(NodeTest::Any),
+                    Some((_, local)) => Ok(NodeTest::Name(local.to_owned())),
+                    None => Ok(NodeTest::Name(name)),
+                }
+            }
+            _ => self.error("expected node test"),
+        }
+    }
+}
+
+fn descendant_or_self() -> Step {
+    Step {
+        axis: Axis::DescendantOrSelf,
+        test: NodeTest::Node,
+        predicates: Vec::new(),
+    }
+}
+
+// ---------------------------------------------------------------------------------------------------------------------
+// Evaluator
+// ---------------------------------------------------------------------------------------------------------------------
+
+/// A node in the XPath data model: either a node of the html tree or an attribute (owner element, attribute index).
+#[derive(Clone, Copy)]
+enum N<'a> {
+    Tree(NodeRef<'a, Node>),
+    Attr(NodeRef<'a, Node>, usize),
+}
+
+impl PartialEq for N<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Tree(a), Self::Tree(b)) => a.id() == b.id(),
+            (Self::Attr(a, i), Self::Attr(b, j)) => a.id() == b.id() && i == j,
+            _ => false,
+        }
+    }
+}
+
+enum Value<'a> {
+    Nodes(Vec<N<'a>>),
+    Bool(bool),
+    Num(f64),
+    Str(String),
+}
+
+struct Context<'a> {
+    node: N<'a>,
+    position: usize,
+    size: usize,
+}
+
+struct Evaluator<'a> {
+    root: NodeRef<'a, Node>,
+    order: HashMap<NodeId, usize>,
+}
+
+type EvalResult<T> = std::result::Result<T, String>;
+
+impl<'a> Evaluator<'a> {
+    fn eval(&self, expr: &Expr, ctx: &Context<'a>) -> EvalResult<Value<'a>> {
+        Ok(match expr {
+            Expr::Literal(s) => Value::Str(s.clone()),
+            Expr::Number(n) => Value::Num(*n),
+            Expr::Variable(name) => return Err(format!("variables are not supported (${name})")),
+            Expr::Negate(expr) => Value::Num(-self.number(self.eval(expr, ctx)?)),
+            Expr::Function(name, args) => self.call(name, args, ctx)?,
+            Expr::Binary(op, lhs, rhs) => self.binary(*op, lhs, rhs, ctx)?,
+            Expr::Path { absolute, steps } => {
+                let start = if *absolute {
+                    N::Tree(self.root)
+                } else {
+                    ctx.node
+                };
+                Value::Nodes(self.steps(vec![start], steps)?)
+            }
+            Expr::Filter {
+                primary,
+                predicates,
+                steps,
+            } => {
+                let mut nodes = match self.eval(primary, ctx)? {
+                    Value::Nodes(nodes) => nodes,
+                    _ => {
+                        return Err(
+                            "predicates and paths can only be applied to node-sets".to_owned()
+                        )
+                    }
+                };
+                for predicate in predicates {
+                    nodes = self.filter(nodes, predicate)?;
+                }
+                Value::Nodes(self.steps(nodes, steps)?)
+            }
+        })
+    }
+
+    fn binary(
+        &self,
+        op: BinaryOp,
+        lhs: &Expr,
+        rhs: &Expr,
+        ctx: &Context<'a>,
+    ) -> EvalResult<Value<'a>> {
+        // `and` and `or` short-circuit
+        match op {
+            BinaryOp::Or => {
+                let b = self.boolean(&self.eval(lhs, ctx)?) || self.boolean(&self.eval(rhs, ctx)?);
+                return Ok(Value::Bool(b));
+            }
+            BinaryOp::And => {
+                let b = self.boolean(&self.eval(lhs, ctx)?) && self.boolean(&self.eval(rhs, ctx)?);
+                return Ok(Value::Bool(b));
+            }
+            _ => {}
+        }
+
+        let lhs = self.eval(lhs, ctx)?;
+        let rhs = self.eval(rhs, ctx)?;
+
+        Ok(match op {
+            BinaryOp::Eq
+            | BinaryOp::Neq
+            | BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge => Value::Bool(self.compare(op, &lhs, &rhs)),
+            BinaryOp::Add => Value::Num(self.number(lhs) + self.number(rhs)),
+            BinaryOp::Sub => Value::Num(self.number(lhs) - self.number(rhs)),
+            BinaryOp::Mul => Value::Num(self.number(lhs) * self.number(rhs)),
+            BinaryOp::Div => Value::Num(self.number(lhs) / self.number(rhs)),
+            BinaryOp::Mod => Value::Num(self.number(lhs) % self.number(rhs)),
+            BinaryOp::Union => match (lhs, rhs) {
+                (Value::Nodes(mut a), Value::Nodes(b)) => {
+                    a.extend(b);
+                    Value::Nodes(self.sort(a))
+                }
+                _ => return Err("the union operator '|' requires node-sets".to_owned()),
+            },
+            BinaryOp::Or | BinaryOp::And => unreachable!(),
+        })
+    }
+
+    /// Compares two values following the rules of
+    /// [XPath 1.0, section 3.4](https://www.w3.org/TR/1999/REC-xpath-19991116/#booleans).
+    fn compare(&self, op: BinaryOp, lhs: &Value<'a>, rhs: &Value<'a>) -> bool {
+        match (lhs, rhs) {
+            (Value::Nodes(a), Value::Nodes(b)) => {
+                let b: Vec<String> = b.iter().map(|n| string_value(*n)).collect();
+                a.iter().any(|a| {
+                    let a = Value::Str(string_value(*a));
+                    b.iter()
+                        .any(|b| self.compare_atoms(op, &a, &Value::Str(b.clone())))
+                })
+            }
+            (Value::Nodes(a), Value::Bool(_)) => {
+                self.compare_atoms(op, &Value::Bool(!a.is_empty()), rhs)
+            }
+            (Value::Bool(_), Value::Nodes(b)) => {
+                self.compare_atoms(op, lhs, &Value::Bool(!b.is_empty()))
+            }
+            (Value::Nodes(a), _) => a
+                .iter()
+                .any(|a| self.compare_atoms(op, &Value::Str(string_value(*a)), rhs)),
+            (_, Value::Nodes(b)) => b
+                .iter()
+                .any(|b| self.compare_atoms(op, lhs, &Value::Str(string_value(*b)))),
+            _ => self.compare_atoms(op, lhs, rhs),
+        }
+    }
+
+    fn compare_atoms(&self, op: BinaryOp, lhs: &Value<'a>, rhs: &Value<'a>) -> bool {
+        match op {
+            BinaryOp::Eq | BinaryOp::Neq => {
+                let eq = match (lhs, rhs) {
+                    (Value::Bool(_), _) | (_, Value::Bool(_)) => {
+                        self.boolean(lhs) == self.boolean(rhs)
+                    }
+                    (Value::Num(_), _) | (_, Value::Num(_)) => {
+                        self.number_ref(lhs) == self.number_ref(rhs)
+                    }
+                    _ => self.string_ref(lhs) == self.string_ref(rhs),
+                };
+                eq == matches!(op, BinaryOp::Eq)
+            }
+            _ => {
+                let (a, b) = (self.number_ref(lhs), self.number_ref(rhs));
+                match op {
+                    BinaryOp::Lt => a < b,
+                    BinaryOp::Le => a <= b,
+                    BinaryOp::Gt => a > b,
+                    _ => a >= b,
+                }
+            }
+        }
+    }
+
+    fn steps(&self, mut nodes: Vec<N<'a>>, steps: &[Step]) -> EvalResult<Vec<N<'a>>> {
+        for step in steps {
+            let mut next = Vec::new();
+            for node in &nodes {
+                let mut selected: Vec<N<'a>> = self
+                    .axis(*node, step.axis)
+                    .into_iter()
+                    .filter(|n| self.test(*n, &step.test, step.axis))
+                    .collect();
+                for predicate in &step.predicates {
+                    selected = self.filter(selected, predicate)?;
+                }
+                next.extend(selected);
+            }
+            nodes = self.sort(next);
+        }
+        Ok(nodes)
+    }
+
+    /// Filters the given nodes (in axis order) by a predicate.
+    fn filter(&self, nodes: Vec<N<'a>>, predicate: &Expr) -> EvalResult<Vec<N<'a>>> {
+        let size = nodes.len();
+        let mut result = Vec::new();
+
+        for (i, node) in nodes.into_iter().enumerate() {
+            let ctx = Context {
+                node,
+                position: i + 1,
+                size,
+            };
+            let keep = match self.eval(predicate, &ctx)? {
+                Value::Num(n) => n == (i + 1) as f64,
+                other => self.boolean(&other),
+            };
+            if keep {
+                result.push(node);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Returns the nodes along the given axis in axis order, i.e. reverse axes in reverse document order.
+    fn axis(&self, node: N<'a>, axis: Axis) -> Vec<N<'a>> {
+        let tree = |n: NodeRef<'a, Node>| N::Tree(n);
+        let visible = |n: &NodeRef<'a, Node>| !matches!(n.value(), Node::Doctype(_));
+
+        let mut nodes: Vec<N<'a>> = match (axis, node) {
+            (Axis::Itself, _) => vec![node],
+            (Axis::Attribute, N::Tree(n)) => match n.value() {
+                Node::Element(e) => (0..e.attrs().count()).map(|i| N::Attr(n, i)).collect(),
+                _ => Vec::new(),
+            },
+            (Axis::Attribute, N::Attr(..)) => Vec::new(),
+            (Axis::Child, N::Tree(n)) => n.children().filter(visible).map(tree).collect(),
+            (Axis::Descendant, N::Tree(n)) => {
+                n.descendants().skip(1).filter(visible).map(tree).collect()
+            }
+            (Axis::DescendantOrSelf, N::Tree(n)) => {
+                n.descendants().filter(visible).map(tree).collect()
+            }
+            (Axis::Child | Axis::Descendant, N::Attr(..)) => Vec::new(),
+            (Axis::DescendantOrSelf, N::Attr(..)) => vec![node],
+            (Axis::Parent, N::Tree(n)) => n.parent().map(tree).into_iter().collect(),
+            (Axis::Parent, N::Attr(owner, _)) => vec![tree(owner)],
+            (Axis::Ancestor, N::Tree(n)) => n.ancestors().map(tree).collect(),
+            (Axis::Ancestor, N::Attr(owner, _)) => std::iter::once(owner)
+                .chain(owner.ancestors())
+                .map(tree)
+                .collect(),
+            (Axis::AncestorOrSelf, N::Tree(n)) => {
+                std::iter::once(n).chain(n.ancestors()).map(tree).collect()
+            }
+            (Axis::AncestorOrSelf, N::Attr(owner, _)) => std::iter::once(node)
+                .chain(std::iter::once(owner).chain(owner.ancestors()).map(tree))
+                .collect(),
+            (Axis::FollowingSibling, N::Tree(n)) => {
+                n.next_siblings().filter(visible).map(tree).collect()
+            }
+            (Axis::PrecedingSibling, N::Tree(n)) => {
+                n.prev_siblings().filter(visible).map(tree).collect()
+            }
+            (Axis::FollowingSibling | Axis::PrecedingSibling, N::Attr(..)) => Vec::new(),
+            (Axis::Following, _) => {
+                let (start, include_children) = match node {
+                    N::Tree(n) => (n, false),
+                    N::Attr(owner, _) => (owner, true),
+                };
+                let mut nodes = Vec::new();
+                if include_children {
+                    nodes.extend(start.descendants().skip(1).filter(visible).map(tree));
+                }
+                for ancestor in std::iter::once(start).chain(start.ancestors()) {
+                    for sibling in ancestor.next_siblings() {
+                        nodes.extend(sibling.descendants().filter(visible).map(tree));
+                    }
+                }
+                nodes
+            }
+            (Axis::Preceding, _) => {
+                let start = match node {
+                    N::Tree(n) | N::Attr(n, _) => n,
+                };
+                let mut nodes = Vec::new();
+                for ancestor in std::iter::once(start).chain(start.ancestors()) {
+                    for sibling in ancestor.prev_siblings() {
+                        nodes.extend(sibling.descendants().filter(visible).map(tree));
+                    }
+                }
+                nodes
+            }
+        };
+
+        nodes = self.sort(nodes);
+        if axis.is_reverse() {
+            nodes.reverse();
+        }
+        nodes
+    }
+
+    fn test(&self, node: N<'a>, test: &NodeTest, axis: Axis) -> bool {
+        match (test, node) {
+            (NodeTest::Node, _) => true,
+            (NodeTest::Any, N::Attr(..)) => axis == Axis::Attribute,
+            (NodeTest::Any, N::Tree(n)) => axis != Axis::Attribute && n.value().is_element(),
+            (NodeTest::Name(name), N::Attr(..)) => {
+                axis == Axis::Attribute
+                    && node_name(node).is_some_and(|n| n.eq_ignore_ascii_case(name))
+            }
+            (NodeTest::Name(name), N::Tree(n)) => {
+                axis != Axis::Attribute
+                    && n.value()
+                        .as_element()
+                        .is_some_and(|e| e.name().eq_ignore_ascii_case(name))
+            }
+            (NodeTest::Text, N::Tree(n)) => n.value().is_text(),
+            (NodeTest::Comment, N::Tree(n)) => n.value().is_comment(),
+            (NodeTest::ProcessingInstruction(target), N::Tree(n)) => match n.value() {
+                Node::ProcessingInstruction(pi) => target.as_ref().is_none_or(|t| *t == *pi.target),
+                _ => false,
+            },
+            (_, N::Attr(..)) => false,
+        }
+    }
+
+    /// Sorts the given nodes into document order and removes duplicates.
+    fn sort(&self, mut nodes: Vec<N<'a>>) -> Vec<N<'a>> {
+        let key = |n: &N<'a>| match n {
+            N::Tree(n) => (self.order.get(&n.id()).copied().unwrap_or(usize::MAX), 0),
+            N::Attr(n, i) => (
+                self.order.get(&n.id()).copied().unwrap_or(usize::MAX),
+                i + 1,
+            ),
+        };
+        nodes.sort_by_key(key);
+        nodes.dedup();
+        nodes
+    }
+
+    fn call(&self, name: &str, args: &[Expr], ctx: &Context<'a>) -> EvalResult<Value<'a>> {
+        let arity = |min: usize, max: usize| {
+            if args.len() < min || args.len() > max {
+                Err(format!("wrong number of arguments for function {name}()"))
+            } else {
+                Ok(())
+            }
+        };
+        let arg = |i: usize| self.eval(&args[i], ctx);
+        let string_arg = |i: usize| -> EvalResult<String> {
+            match args.get(i) {
+                Some(_) => Ok(self.string(arg(i)?)),
+                None => Ok(string_value(ctx.node)),
+            }
+        };
+        let node_arg = |i: usize| -> EvalResult<Option<N<'a>>> {
+            match args.get(i) {
+                Some(_) => match arg(i)? {
+                    Value::Nodes(nodes) => Ok(nodes.first().copied()),
+                    _ => Err(format!("function {name}() expects a node-set")),
+                },
+                None => Ok(Some(ctx.node)),
+            }
+        };
+
+        Ok(match name {
+            "last" => {
+                arity(0, 0)?;
+                Value::Num(ctx.size as f64)
+            }
+            "position" => {
+                arity(0, 0)?;
+                Value::Num(ctx.position as f64)
+            }
+            "count" => {
+                arity(1, 1)?;
+                match arg(0)? {
+                    Value::Nodes(nodes) => Value::Num(nodes.len() as f64),
+                    _ => return Err("function count() expects a node-set".to_owned()),
+                }
+            }
+            "id" => {
+                arity(1, 1)?;
+                let ids = match arg(0)? {
+                    Value::Nodes(nodes) => nodes
+                        .iter()
+                        .map(|n| string_value(*n))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                    other => self.string(other),
+                };
+                let ids: Vec<&str> = ids.split_whitespace().collect();
+                let nodes = self
+                    .root
+                    .descendants()
+                    .filter(|n| {
+                        n.value()
+                            .as_element()
+                            .and_then(|e| e.id())
+                            .is_some_and(|id| ids.contains(&id))
+                    })
+                    .map(N::Tree)
+                    .collect();
+                Value::Nodes(nodes)
+            }
+            "local-name" | "name" => {
+                arity(0, 1)?;
+                Value::Str(
+                    node_arg(0)?
+                        .and_then(node_name)
+                        .unwrap_or_default()
+                        .to_owned(),
+                )
+            }
+            "namespace-uri" => {
+                arity(0, 1)?;
+                Value::Str(String::new())
+            }
+            "string" => {
+                arity(0, 1)?;
+                Value::Str(string_arg(0)?)
+            }
+            "concat" => {
+                if args.len() < 2 {
+                    return Err("function concat() expects at least two arguments".to_owned());
+                }
+                let mut s = String::new();
+                for i in 0..args.len() {
+                    s.push_str(&string_arg(i)?);
+                }
+                Value::Str(s)
+            }
+            "starts-with" => {
+                arity(2, 2)?;
+                Value::Bool(string_arg(0)?.starts_with(&string_arg(1)?))
+            }
+            "contains" => {
+                arity(2, 2)?;
+                Value::Bool(string_arg(0)?.contains(&string_arg(1)?))
+            }
+            "substring-before" => {
+                arity(2, 2)?;
+                let (s, pattern) = (string_arg(0)?, string_arg(1)?);
+                Value::Str(
+                    s.find(&pattern)
+                        .map(|i| s[..i].to_owned())
+                        .unwrap_or_default(),
+                )
+            }
+            "substring-after" => {
+                arity(2, 2)?;
+                let (s, pattern) = (string_arg(0)?, string_arg(1)?);
+                Value::Str(
+                    s.find(&pattern)
+                        .map(|i| s[i + pattern.len()..].to_owned())
+                        .unwrap_or_default(),
+                )
+            }
+            "substring" => {
+                arity(2, 3)?;
+                let s = string_arg(0)?;
+                let start = round(self.number(arg(1)?));
+                let end = match args.get(2) {
+                    Some(_) => start + round(self.number(arg(2)?)),
+                    None => f64::INFINITY,
+                };
+                let substring = s
+                    .chars()
+                    .enumerate()
+                    .filter(|(i, _)| {
+                        let p = (i + 1) as f64;
+                        p >= start && p < end
+                    })
+                    .map(|(_, c)| c)
+                    .collect();
+                Value::Str(substring)
+            }
+            "string-length" => {
+                arity(0, 1)?;
+                Value::Num(string_arg(0)?.chars().count() as f64)
+            }
+            "normalize-space" => {
+                arity(0, 1)?;
+                Value::Str(
+                    string_arg(0)?
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+            }
+            "translate" => {
+                arity(3, 3)?;
+                let (s, from, to) = (string_arg(0)?, string_arg(1)?, string_arg(2)?);
+                let from: Vec<char> = from.chars().collect();
+                let to: Vec<char> = to.chars().collect();
+                let translated = s
+                    .chars()
+                    .filter_map(|c| match from.iter().position(|f| *f == c) {
+                        Some(i) => to.get(i).copied(),
+                        None => Some(c),
+                    })
+                    .collect();
+                Value::Str(translated)
+            }
+            "boolean" => {
+                arity(1, 1)?;
+                Value::Bool(self.boolean(&arg(0)?))
+            }
+            "not" => {
+                arity(1, 1)?;
+                Value::Bool(!self.boolean(&arg(0)?))
+            }
+            "true" => {
+                arity(0, 0)?;
+                Value::Bool(true)
+            }
+            "false" => {
+                arity(0, 0)?;
+                Value::Bool(false)
+            }
+            "lang" => {
+                arity(1, 1)?;
+                let lang = string_arg(0)?.to_lowercase();
+                let start = match ctx.node {
+                    N::Tree(n) | N::Attr(n, _) => n,
+                };
+                let declared = std::iter::once(start)
+                    .chain(start.ancestors())
+                    .find_map(|n| {
+                        n.value()
+                            .as_element()
+                            .and_then(|e| e.attr("xml:lang").or_else(|| e.attr("lang")))
+                    })
+                    .map(|l| l.to_lowercase());
+                Value::Bool(declared.is_some_and(|d| {
+                    d == lang
+                        || d.strip_prefix(&lang)
+                            .is_some_and(|rest| rest.starts_with('-'))
+                }))
+            }
+            "number" => {
+                arity(0, 1)?;
+                match args.first() {
+                    Some(_) => Value::Num(self.number(arg(0)?)),
+                    None => Value::Num(string_to_number(&string_value(ctx.node))),
+                }
+            }
+            "sum" => {
+                arity(1, 1)?;
+                match arg(0)? {
+                    Value::Nodes(nodes) => Value::Num(
+                        nodes
+                            .iter()
+                            .map(|n| string_to_number(&string_value(*n)))
+                            .sum(),
+                    ),
+                    _ => return Err("function sum() expects a node-set".to_owned()),
+                }
+            }
+            "floor" => {
+                arity(1, 1)?;
+                Value::Num(self.number(arg(0)?).floor())
+            }
+            "ceiling" => {
+                arity(1, 1)?;
+                Value::Num(self.number(arg(0)?).ceil())
+            }
+            "round" => {
+                arity(1, 1)?;
+                Value::Num(round(self.number(arg(0)?)))
+            }
+            _ => return Err(format!("unknown function {name}()")),
+        })
+    }
+
+    fn boolean(&self, value: &Value<'a>) -> bool {
+        match value {
+            Value::Nodes(nodes) => !nodes.is_empty(),
+            Value::Bool(b) => *b,
+            Value::Num(n) => *n != 0.0 && !n.is_nan(),
+            Value::Str(s) => !s.is_empty(),
+        }
+    }
+
+    fn number(&self, value: Value<'a>) -> f64 {
+        self.number_ref(&value)
+    }
+
+    fn number_ref(&self, value: &Value<'a>) -> f64 {
+        match value {
+            Value::Num(n) => *n,
+            Value::Bool(b) => f64::from(u8::from(*b)),
+            _ => string_to_number(&self.string_ref(value)),
+        }
+    }
+
+    fn string(&self, value: Value<'a>) -> String {
+        match value {
+            Value::Str(s) => s,
+            other => self.string_ref(&other),
+        }
+    }
+
+    fn string_ref(&self, value: &Value<'a>) -> String {
+        match value {
+            Value::Nodes(nodes) => nodes.first().map(|n| string_value(*n)).unwrap_or_default(),
+            Value::Bool(b) => b.to_string(),
+            Value::Num(n) => number_to_string(*n),
+            Value::Str(s) => s.clone(),
+        }
+    }
+}
+
+fn node_name(node: N<'_>) -> Option<&str> {
+    match node {
+        N::Attr(owner, i) => owner
+            .value()
+            .as_element()
+            .and_then(|e| e.attrs().nth(i))
+            .map(|(name, _)| name),
+        N::Tree(n) => match n.value() {
+            Node::Element(e) => Some(e.name()),
+            Node::ProcessingInstruction(pi) => Some(&pi.target),
+            _ => None,
+        },
+    }
+}
+
+fn string_value(node: N<'_>) -> String {
+    match node {
+        N::Attr(owner, i) => owner
+            .value()
+            .as_element()
+            .and_then(|e| e.attrs().nth(i))
+            .map(|(_, value)| value.to_owned())
+            .unwrap_or_default(),
+        N::Tree(n) => match n.value() {
+            Node::Text(text) => text.to_string(),
+            Node::Comment(comment) => comment.to_string(),
+            Node::ProcessingInstruction(pi) => pi.data.to_string(),
+            Node::Doctype(_) => String::new(),
+            _ => n
+                .descendants()
+                .filter_map(|d| d.value().as_text().map(|t| t.to_string()))
+                .collect(),
+        },
+    }
+}
+
+fn string_to_number(s: &str) -> f64 {
+    let s = s.trim();
+    let digits = s.strip_prefix('-').unwrap_or(s);
+    let valid = !digits.is_empty()
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.chars().filter(|c| *c == '.').count() <= 1
+        && digits != ".";
+
+    if valid {
+        s.parse().unwrap_or(f64::NAN)
+    } else {
+        f64::NAN
+    }
+}
+
+fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_owned()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+    } else if n == 0.0 {
+        "0".to_owned()
+    } else {
+        n.to_string()
+    }
+}
+
+/// Rounds half-way values towards positive infinity as required by XPath.
+fn round(n: f64) -> f64 {
+    if n.is_nan() || n.is_infinite() {
+        n
+    } else {
+        (n + 0.5).floor()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, XPath, XPathNodeKind, XPathValue};
+    use rstest::rstest;
+    use scraper::Html;
+
+    static PAGE_001: &str = r#"
+        <html lang="en">
+            <body>
+                <table id="prices">
+                    <tr><th>Item</th><th>Price</th></tr>
+                    <tr class="row"><td>Apple</td><td>1.5</td></tr>
+                    <tr class="row"><td>Pear</td><td>2</td></tr>
+                    <tr class="row"><td>Melon</td><td>3.25</td></tr>
+                </table>
+                <div class="links">
+                    <a href="/a">First link</a>
+                    <!-- a comment -->
+                    <a href="/b" title="second">Second <b>link</b></a>
+                </div>
+            </body>
+        </html>
+    "#;
+
+    fn eval(expression: &str) -> String {
+        let html = Html::parse_document(PAGE_001);
+        let xpath = XPath::compile(expression).unwrap();
+        match xpath.evaluate(&html).unwrap() {
+            XPathValue::Nodes(nodes) => nodes
+                .iter()
+                .map(|n| n.string_value().trim().to_owned())
+                .collect::<Vec<_>>()
+                .join("|"),
+            other => other.string(),
+        }
+    }
+
+    #[rstest]
+    #[case("//td[1]", "Apple|Pear|Melon")]
+    #[case("//tr[td = 'Pear']/td[2]", "2")]
+    #[case("//td[text() = 'Melon']/following-sibling::td", "3.25")]
+    #[case("//td[. = 'Pear']/ancestor::table/@id", "prices")]
+    #[case("(//tr[@class = 'row'])[last()]/td[1]", "Melon")]
+    #[case("//tr[position() > 1 and position() < last()]/td[1]", "Apple|Pear")]
+    #[case("//a[contains(., 'Second')]/@href", "/b")]
+    #[case("//a[b]/@title", "second")]
+    #[case("//a[starts-with(@href, '/')][1]", "First link")]
+    #[case("//b/preceding::a[1]", "First link")]
+    #[case("//td[2][. > 1.75]/preceding-sibling::td", "Pear|Melon")]
+    #[case("//div/comment()", "a comment")]
+    #[case("//div/*", "First link|Second link")]
+    #[case("//td[1] | //th[1]", "Item|Apple|Pear|Melon")]
+    #[case("sum(//td[2])", "6.75")]
+    #[case("count(//tr) * 2 - 1 div 2", "7.5")]
+    #[case("7 mod 3 = 1", "true")]
+    #[case("concat(name(//table), '#', string(//table/@id))", "table#prices")]
+    #[case("normalize-space('  a   b ')", "a b")]
+    #[case("substring('12345', 1.5, 2.6)", "234")]
+    #[case("substring-after(//a[2]/@href, '/')", "b")]
+    #[case("translate('bar', 'abc', 'ABC')", "BAr")]
+    #[case("round(-2.5) + floor(2.7) + ceiling(2.1)", "3")]
+    #[case("number('x') != number('x')", "true")]
+    #[case("boolean(//td[lang('en')])", "true")]
+    #[case("//*[@id = 'prices']//tr[2]/../tr[1]/th[last()]", "Price")]
+    #[case("id('prices')//td[1][. = 'Apple']", "Apple")]
+    fn evaluate(#[case] expression: &str, #[case] expected: &str) {
+        assert_eq!(eval(expression), expected, "{expression}");
+    }
+
+    #[test]
+    fn evaluate_relative_to_element() {
+        let html = Html::parse_document(PAGE_001);
+        let div = XPath::compile("//div").unwrap().select(&html).unwrap()[0]
+            .as_element()
+            .unwrap();
+
+        let xpath = XPath::compile("a[last()]/@href").unwrap();
+        let nodes = xpath.evaluate_from(div).unwrap().into_nodes().unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].kind(), XPathNodeKind::Attribute);
+        assert_eq!(nodes[0].name(), Some("href"));
+        assert_eq!(nodes[0].string_value(), "/b");
+    }
+
+    #[rstest]
+    #[case("//td[")]
+    #[case("//td[@id = 'x]")]
+    #[case("foo::td")]
+    #[case("1 +")]
+    #[case("//td)")]
+    fn parse_errors(#[case] expression: &str) {
+        assert!(matches!(
+            XPath::compile(expression),
+            Err(Error::ParseError { .. })
+        ));
+    }
+
+    #[test]
+    fn evaluation_errors() {
+        let html = Html::parse_document(PAGE_001);
+
+        let result = XPath::compile("unknown(1)").unwrap().evaluate(&html);
+        assert!(matches!(result, Err(Error::EvaluationError { .. })));
+
+        let result = XPath::compile("$var").unwrap().evaluate(&html);
+        assert!(matches!(result, Err(Error::EvaluationError { .. })));
+
+        let result = XPath::compile("count(//td)").unwrap().select(&html);
+        assert!(matches!(result, Err(Error::ResultTypeError { .. })));
+    }
+}